@@ -3,16 +3,15 @@ use std::rc::Rc;
 use crate::{game::GameTimestamp, ids::ActionId, GameDomain, Observer, ObserverId, PlayerId};
 
 #[derive(Clone, Debug)]
-pub struct InputRequest {
+pub struct InputRequest<TGame: GameDomain> {
     /// Input is being requested from this player
     pub from_player: PlayerId,
 
-    /// Some token so that the player knows what input is being requested from them
+    /// What sort of input is being requested from the player
     ///
-    /// TODO: Could this be replaced with an enum/something more structured?
-    /// A presentation layer on top of this engine would probably want to present specialized UI
-    /// elements for each type of input
-    pub input_type: String,
+    /// A presentation layer on top of this engine can use this to present specialized UI elements
+    /// for each kind of input.
+    pub kind: TGame::InputKind,
 }
 
 #[derive(Clone, Debug)]
@@ -24,7 +23,7 @@ pub enum EngineAction<TGame: GameDomain> {
 
     /// Starts an input session, with all inputs being directed toward the observer that created
     /// this action
-    RequestInput(InputRequest),
+    RequestInput(InputRequest<TGame>),
 
     /// Ends the current input session
     EndInput,
@@ -44,7 +43,15 @@ pub enum EngineAction<TGame: GameDomain> {
     /// it observes any actions.
     /// The first action the newly attached observer will observe will be the action that attached
     /// it to the game.
+    ///
+    /// Observers that need to know the new observer's ID should use `ActionSink::attach_observer`
+    /// instead.
     AttachObserver(Box<dyn Observer<TGame>>),
+
+    /// Ends the game with the given outcome
+    ///
+    /// Once this action has been applied and broadcast, the game won't process any more actions.
+    EndGame(TGame::Outcome),
 }
 
 #[derive(Clone, Debug)]
@@ -55,7 +62,33 @@ pub enum ActionPayload<TGame: GameDomain> {
     /// An action that represents an atomic modification to the domain state
     DomainAction(TGame::Action),
 
-    Composite(Vec<Action<TGame>>),
+    /// A group of actions that are applied together, before any of them are broadcast
+    ///
+    /// The tag is purely descriptive, and has no effect on how the actions are executed.
+    Composite {
+        tag: &'static str,
+        actions: Vec<Action<TGame>>,
+    },
+}
+
+impl<TGame: GameDomain> ActionPayload<TGame> {
+    /// Groups the given actions into a single composite payload
+    ///
+    /// The actions may themselves be composites, in which case all of the leaf actions are applied
+    /// before any of them are broadcast.
+    pub fn composite(tag: &'static str, actions: Vec<Action<TGame>>) -> Self {
+        ActionPayload::Composite { tag, actions }
+    }
+}
+
+/// Who is responsible for an action, used when ordering actions that happen at the same time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Controller {
+    /// The action is performed by the game itself, eg a turn-based action
+    Game,
+
+    /// The action is performed by, or on behalf of, the given player
+    Player(PlayerId),
 }
 
 #[derive(Clone, Debug)]
@@ -66,6 +99,9 @@ pub struct Action<TGame: GameDomain> {
     /// The observer that added this action to the queue
     pub source: ObserverId,
 
+    /// Who is performing this action
+    pub controller: Controller,
+
     /// Globally unique ID for this action
     ///
     /// Each candidate replacement effect will have its own new ID, such that it is possible to
@@ -84,6 +120,10 @@ pub struct Action<TGame: GameDomain> {
 }
 
 impl<TGame: GameDomain> Action<TGame> {
+    /// The observer that emitted the action at the start of this action's replacement chain
+    ///
+    /// This is the observer whose reaction ultimately led to this action, however many
+    /// replacement effects have modified it since.
     pub fn root_source(&self) -> ObserverId {
         match &self.original {
             Some(a) => a.root_source(),
@@ -91,3 +131,36 @@ impl<TGame: GameDomain> Action<TGame> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::ActionPayload;
+    use crate::{
+        ids::{IdGenerator, ObserverId},
+        test_utils::{game_action, new_game, Add},
+    };
+
+    #[test]
+    fn test_root_source() {
+        let mut game = new_game();
+        let mut observer_ids = IdGenerator::<ObserverId>::new();
+        let [emitter, first, second] = [(); 3].map(|_| observer_ids.next_id());
+
+        let mut original = game_action(&mut game, ActionPayload::DomainAction(Add(1)));
+        original.source = emitter;
+        assert_eq!(original.root_source(), emitter);
+
+        // Replaced once, and then the replacement is replaced again
+        let mut replaced = game_action(&mut game, ActionPayload::DomainAction(Add(2)));
+        replaced.source = first;
+        replaced.original = Some(Rc::new(original));
+        let mut replaced_again = game_action(&mut game, ActionPayload::DomainAction(Add(4)));
+        replaced_again.source = second;
+        replaced_again.original = Some(Rc::new(replaced));
+
+        assert_eq!(replaced_again.source, second);
+        assert_eq!(replaced_again.root_source(), emitter);
+    }
+}
@@ -0,0 +1,13 @@
+//! Non-interactive players
+//!
+//! An agent is anything that can answer the game's requests for input on behalf of a player, eg an
+//! AI opponent or a scripted player in a test.
+
+use crate::{actions::InputRequest, game::Game, GameDomain, PlayerInput};
+
+pub trait Agent<TGame: GameDomain>: std::fmt::Debug {
+    /// Decide what input to give in response to the given request
+    ///
+    /// The request is always for input from the player this agent is playing as.
+    fn decide(&mut self, game: &Game<TGame>, request: &InputRequest<TGame>) -> PlayerInput<TGame>;
+}
@@ -1,14 +1,15 @@
 use std::{
     collections::{HashMap, VecDeque},
-    fmt::Debug,
+    fmt::{Debug, Display},
     marker::PhantomData,
     rc::Rc,
 };
 
 use crate::{
-    actions::{ActionPayload, EngineAction, InputRequest},
+    actions::{ActionPayload, Controller, EngineAction, InputRequest},
+    agent::Agent,
     ids::{ActionId, IdGenerator, ObserverId, PlayerId},
-    Action, ActionSink, Observer, PlayerInput,
+    Action, ActionSink, Observer, PlayerInput, PlayerInputPayload,
 };
 
 pub trait GameDomainAction<TGame: GameDomain>: Clone + Debug {
@@ -18,6 +19,83 @@ pub trait GameDomainAction<TGame: GameDomain>: Clone + Debug {
 pub trait GameDomain: Clone {
     type Input: Clone + Debug;
     type Action: GameDomainAction<Self>;
+
+    /// Describes the kind of input being requested from a player
+    type InputKind: Clone + Debug + Display;
+
+    /// Does the given input have the right shape to satisfy a request for the given kind of input
+    ///
+    /// Inputs that fail this check are rejected by the engine before they reach the observer that
+    /// requested the input. Every input passes by default, leaving that observer to check it.
+    fn input_matches_kind(_kind: &Self::InputKind, _input: &PlayerInputPayload<Self>) -> bool {
+        true
+    }
+
+    /// A human-facing description of something that happened in the game, eg "Alice drew a card"
+    type Event: Clone + Debug;
+
+    /// The events that the given action causes, called just before it's applied to the given game
+    /// state
+    ///
+    /// Seeing the state beforehand lets an action that turns out to do nothing, eg drawing from an
+    /// empty library, cause no events. Most actions are internal bookkeeping that a player
+    /// wouldn't care about, and so cause no events either. No action causes any by default, so a
+    /// game that doesn't describe its events can leave this alone and use `()` for `Event`.
+    fn action_events(_action: &Self::Action, _game_state: &Self) -> Vec<Self::Event> {
+        Vec::new()
+    }
+
+    /// How a finished game ended, eg who won
+    type Outcome: Clone + Debug;
+
+    /// Is it normal for this game to run out of actions without asking for player input
+    ///
+    /// Most games always have something to do next, so running out of actions indicates a bug in
+    /// one of the observers and is reported as `TickResult::Stalled`. A game that is waiting on
+    /// something outside of the engine, eg an external clock, can return true here to have the
+    /// same situation reported as `TickResult::Idle` instead.
+    fn is_idle_ok(&self) -> bool {
+        false
+    }
+
+    /// A hash of this state, used to notice the game going around in a loop (see
+    /// `Game::loop_limit`)
+    ///
+    /// Two states that compare equal must have the same hash. Returns None by default, which opts
+    /// the game out of loop detection.
+    fn state_hash(&self) -> Option<u64> {
+        None
+    }
+
+    /// How the game ends if it gets stuck in a loop that no player can break, or None if it should
+    /// keep going
+    fn loop_outcome(&self) -> Option<Self::Outcome> {
+        None
+    }
+}
+
+/// A domain event, along with when and why it happened
+#[derive(Clone, Debug)]
+pub struct GameEvent<TGame: GameDomain> {
+    pub event: TGame::Event,
+
+    /// The action that caused this event
+    pub action: ActionId,
+
+    /// The GameTimestamp at which the action that caused this event was applied
+    pub timestamp: GameTimestamp,
+}
+
+/// A game that can describe how its state has changed, opted into by games that want to use
+/// `Game::tick_with_diff`
+pub trait GameDomainDiff: GameDomain {
+    /// A single change between two states of the game, eg "Alice's life total went from 20 to 17"
+    type Diff: Clone + Debug;
+
+    /// Every change between the two given states of the game
+    ///
+    /// Only used by tooling such as `Game::tick_with_diff`, so needn't be fast.
+    fn diff(before: &Self, after: &Self) -> Vec<Self::Diff>;
 }
 
 #[derive(Clone, Debug)]
@@ -100,7 +178,8 @@ impl<TGame: GameDomain> ActionQueue<TGame> {
     fn process(
         &mut self,
         id_gen: &mut IdGenerator<ActionId>,
-        observers: &HashMap<ObserverId, Box<dyn Observer<TGame>>>,
+        observers: &mut HashMap<ObserverId, Box<dyn Observer<TGame>>>,
+        observer_order: &[ObserverId],
         game_state: &TGame,
     ) -> ActionQueueStatus {
         if self.partially_resolved_state.is_some() {
@@ -115,8 +194,23 @@ impl<TGame: GameDomain> ActionQueue<TGame> {
             let mut original_rc: Option<Rc<Action<TGame>>> = None;
 
             let mut candidate_replacements = Vec::new();
-            for (oid, observer) in observers {
+            for oid in observer_order {
+                // An observer replacing actions that it's responsible for could keep doing so
+                // forever
+                if *oid == original.root_source() {
+                    continue;
+                }
+
+                let observer = &observers[oid];
                 if let Some(candidate) = observer.propose_replacement(&original, game_state) {
+                    assert!(
+                        matches!(original.payload, ActionPayload::DomainAction(_)),
+                        "{:?} proposed a replacement for {:?}, but only domain actions can be \
+                         replaced",
+                        oid,
+                        original.id,
+                    );
+
                     original_rc = match original_rc {
                         Some(o) => Some(o),
                         None => Some(Rc::new(original.clone())),
@@ -125,6 +219,7 @@ impl<TGame: GameDomain> ActionQueue<TGame> {
                     candidate_replacements.push(Action {
                         payload: ActionPayload::DomainAction(candidate),
                         source: *oid,
+                        controller: original.controller,
                         id: id_gen.next_id(),
                         generated_at: original.generated_at,
                         original: original_rc.clone(),
@@ -132,10 +227,15 @@ impl<TGame: GameDomain> ActionQueue<TGame> {
                 }
             }
 
-            if candidate_replacements.len() == 0 {
+            if candidate_replacements.is_empty() {
                 self.resolved.push(original);
             } else if candidate_replacements.len() == 1 {
-                self.resolved.push(candidate_replacements.pop().unwrap());
+                let replacement = candidate_replacements.pop().unwrap();
+                observers
+                    .get_mut(&replacement.source)
+                    .expect("Replacing observer does not exist")
+                    .on_replacement_applied(&replacement, game_state);
+                self.resolved.push(replacement);
             } else {
                 self.partially_resolved_state = Some(ActionReplacementState {
                     subject: original,
@@ -189,7 +289,7 @@ impl<TGame: GameDomain> ActionQueue<TGame> {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct GameTimestamp(usize);
 
 impl GameTimestamp {
@@ -203,14 +303,39 @@ impl GameTimestamp {
 }
 
 #[derive(Clone, Debug)]
-pub struct InputSession {
+pub struct InputSession<TGame: GameDomain> {
     /// The original input request
-    pub request: InputRequest,
+    pub request: InputRequest<TGame>,
 
     /// The observer that requested this input session, to which each input will be sent
     pub handler: ObserverId,
 }
 
+/// Why `Game::run_with_agents` stopped running the game
+#[derive(Clone, Debug)]
+pub enum AgentRunResult<TGame: GameDomain> {
+    /// The given stopping condition was met
+    Stopped,
+
+    /// The game stalled, see `TickResult::Stalled`
+    Stalled,
+
+    /// The game is idle, see `TickResult::Idle`
+    Idle,
+
+    /// The game hit its tick limit, see `TickResult::TickLimitReached`
+    TickLimitReached,
+
+    /// The game ended with the given outcome
+    Finished(TGame::Outcome),
+
+    /// Input was requested from a player that doesn't have an agent
+    NoAgent(PlayerId),
+
+    /// The agent for the given player gave an input that the game rejected
+    Rejected(PlayerId, InputError),
+}
+
 #[derive(Clone, Debug)]
 pub enum InputError {
     /// The observer managing the current input session rejected the input with the given message
@@ -224,11 +349,39 @@ pub enum InputError {
     /// In this error case the input is not passed to the observer managing the current input session
     WrongPlayer,
 
+    /// The game has already ended, so isn't taking any more input
+    GameFinished,
+
     /// Internal error for when an observer has requested an input session, but has not defined an
     /// input handler.
     UnimplementedObserver,
 }
 
+/// Lists of observer IDs, each in reaction order, for the observers interested in each hook
+#[derive(Clone, Debug, Default)]
+pub struct ObserverSubsets {
+    /// Observers interested in `observe_action`
+    pub actions: Vec<ObserverId>,
+
+    /// Observers interested in `propose_replacement`
+    pub replacements: Vec<ObserverId>,
+
+    /// Observers interested in `observe_pending`
+    pub pending: Vec<ObserverId>,
+}
+
+/// Inserts the given observer into a list of observers in reaction order, after every observer
+/// that reacts no later than it
+fn insert_ordered<TGame: GameDomain>(
+    list: &mut Vec<ObserverId>,
+    observers: &HashMap<ObserverId, Box<dyn Observer<TGame>>>,
+    id: ObserverId,
+    order: i32,
+) {
+    let index = list.partition_point(|other| observers[other].reaction_order() <= order);
+    list.insert(index, id);
+}
+
 #[derive(Clone, Debug)]
 pub struct Game<TGame: GameDomain> {
     /// Actual state of the game being run
@@ -251,16 +404,45 @@ pub struct Game<TGame: GameDomain> {
     pub self_id: ObserverId,
 
     /// Storage for all obververs currently alive
-    ///
-    /// TODO: Not all observers have have implementations for each method in the trait.  This might
-    /// be made more efficient by storing which subsets of observers need, eg, `observe_action`
-    /// calling on them, and which observers for which doing so would be a waste of time.
-    /// Actually benchmark this in real-world cases though, the cost of maintaining the sets +
-    /// dictionary lookups for every key might be more than the cost of just calling the dummy
-    /// default implementations of the methods.
     pub observers: HashMap<ObserverId, Box<dyn Observer<TGame>>>,
 
-    pub current_input_session: Option<InputSession>,
+    /// The IDs of every observer, in the order they're shown each action
+    ///
+    /// Sorted by each observer's `reaction_order`, then by the order they were attached.
+    pub observer_order: Vec<ObserverId>,
+
+    /// The subsets of `observer_order` that are interested in each of the observer hooks
+    pub observer_subsets: ObserverSubsets,
+
+    pub current_input_session: Option<InputSession<TGame>>,
+
+    /// Events that have happened since the last call to `drain_events`
+    ///
+    /// None if the event log is disabled, in which case actions aren't translated into events at
+    /// all.
+    pub event_log: Option<Vec<GameEvent<TGame>>>,
+
+    /// Every action applied or broadcast since the last call to `drain_action_history`, in order
+    ///
+    /// None if action history recording is disabled. See `Game::replay`.
+    pub action_history: Option<Vec<Action<TGame>>>,
+
+    /// How the game ended, or None if it's still in progress
+    pub outcome: Option<TGame::Outcome>,
+
+    /// The most times `tick_until_player_input` will tick the game in one call, or None for no
+    /// limit
+    ///
+    /// Guards against a buggy observer that keeps emitting actions forever.
+    pub tick_limit: Option<usize>,
+
+    /// How many times the same game state can be seen during one call to
+    /// `tick_until_player_input` before the game is ended with its `GameDomain::loop_outcome`, or
+    /// None to never check
+    ///
+    /// Without any player input in between, a state coming around again means the observers will
+    /// keep repeating the same sequence of actions forever.
+    pub loop_limit: Option<usize>,
 }
 
 #[derive(Clone, Debug)]
@@ -274,31 +456,75 @@ pub enum TickResult<TGame: GameDomain> {
     /// Error condition: The game has run out of actions to perform, and no observer has requested
     /// a player input
     Stalled,
+
+    /// The game has run out of actions to perform, but the game domain says that's expected
+    ///
+    /// See `GameDomain::is_idle_ok`. Ticking again once something outside the engine has queued
+    /// an action will continue the game.
+    Idle,
+
+    /// The game has ended, and won't process any more actions
+    Finished(TGame::Outcome),
+
+    /// Error condition: The game was still ticking after using up its budget of ticks
+    ///
+    /// Never returned by a single `tick`, only by `tick_n` and `tick_until_player_input`.
+    TickLimitReached,
 }
 
 struct ActionSinker<'a, TGame: GameDomain> {
     id_gen: &'a mut IdGenerator<ActionId>,
+    observer_id_gen: &'a mut IdGenerator<ObserverId>,
     queue: &'a mut ActionQueue<TGame>,
     timestamp: GameTimestamp,
     oid: Option<ObserverId>,
+
+    /// Observers to attach once the current broadcast has finished, along with their IDs
+    attachments: Vec<(ObserverId, Box<dyn Observer<TGame>>)>,
 }
 
 impl<'a, TGame: GameDomain> ActionSink<TGame> for ActionSinker<'a, TGame> {
-    fn emit_single(&mut self, new_action: ActionPayload<TGame>) {
+    fn emit_controlled(&mut self, new_action: ActionPayload<TGame>, controller: Controller) {
         self.queue.add(Action {
             payload: new_action,
             source: self.oid.expect("ActionSinker OID not set"),
+            controller,
             id: self.id_gen.next_id(),
             original: None,
             generated_at: self.timestamp,
         })
     }
+
+    fn emit_composite(&mut self, tag: &'static str, actions: Vec<ActionPayload<TGame>>) {
+        let source = self.oid.expect("ActionSinker OID not set");
+        let actions = actions
+            .into_iter()
+            .map(|payload| Action {
+                payload,
+                source,
+                controller: Controller::Game,
+                id: self.id_gen.next_id(),
+                original: None,
+                generated_at: self.timestamp,
+            })
+            .collect();
+        self.emit_single(ActionPayload::composite(tag, actions));
+    }
+
+    fn attach_observer(&mut self, observer: Box<dyn Observer<TGame>>) -> ObserverId {
+        let id = self.observer_id_gen.next_id();
+        self.attachments.push((id, observer));
+        id
+    }
 }
 
 impl<TGame: GameDomain> Game<TGame> {
     fn apply_action(&mut self, action: &Action<TGame>) {
         match &action.payload {
-            ActionPayload::Composite(sub_actions) => {
+            ActionPayload::Composite {
+                actions: sub_actions,
+                ..
+            } => {
                 for sub_action in sub_actions {
                     self.apply_action(sub_action);
                 }
@@ -317,9 +543,54 @@ impl<TGame: GameDomain> Game<TGame> {
             ActionPayload::EngineAction(EngineAction::PickNextAction(_)) => todo!(),
             ActionPayload::EngineAction(EngineAction::PickReplacement(_)) => todo!(),
             ActionPayload::EngineAction(EngineAction::AttachObserver(o)) => {
-                self.attach_observer(o.clone())
+                self.attach_observer(o.clone());
             }
-            ActionPayload::DomainAction(da) => da.apply(&mut self.game_state),
+            ActionPayload::EngineAction(EngineAction::EndGame(outcome)) => {
+                self.outcome = Some(outcome.clone());
+            }
+            ActionPayload::DomainAction(da) => {
+                if let Some(event_log) = &mut self.event_log {
+                    let timestamp = self.game_timestamp;
+                    let events = TGame::action_events(da, &self.game_state);
+                    event_log.extend(events.into_iter().map(|event| GameEvent {
+                        event,
+                        action: action.id,
+                        timestamp,
+                    }));
+                }
+                da.apply(&mut self.game_state);
+            }
+        }
+    }
+
+    fn record_action(&mut self, action: &Action<TGame>) {
+        if let Some(history) = &mut self.action_history {
+            history.push(action.clone());
+        }
+    }
+
+    /// Show the given action to every observer interested in it before it's applied
+    fn broadcast_pending(&mut self, action: &Action<TGame>) {
+        match &action.payload {
+            ActionPayload::Composite {
+                actions: sub_actions,
+                ..
+            } => {
+                for sub_action in sub_actions {
+                    self.broadcast_pending(sub_action);
+                }
+                return;
+            }
+            // Only seen when replaying, as it's never applied while ticking
+            ActionPayload::EngineAction(EngineAction::NoActions) => return,
+            _ => (),
+        }
+
+        for oid in &self.observer_subsets.pending {
+            self.observers
+                .get_mut(oid)
+                .expect("Ordered observer does not exist")
+                .observe_pending(action, &self.game_state);
         }
     }
 
@@ -329,7 +600,11 @@ impl<TGame: GameDomain> Game<TGame> {
         // The composite action payload is just a bookkeeping structure for
         // action ordering, it doesn't have any semantic meaning that needs to
         // be broadcast to the observers.
-        if let ActionPayload::Composite(sub_actions) = &action.payload {
+        if let ActionPayload::Composite {
+            actions: sub_actions,
+            ..
+        } = &action.payload
+        {
             for sub_action in sub_actions {
                 self.broadcast_action(sub_action);
             }
@@ -338,27 +613,44 @@ impl<TGame: GameDomain> Game<TGame> {
 
         let mut sink = ActionSinker {
             id_gen: &mut self.action_id_gen,
+            observer_id_gen: &mut self.observer_id_gen,
             queue: &mut self.action_queue,
             timestamp: self.game_timestamp,
             oid: None,
+            attachments: Vec::new(),
         };
 
-        for (oid, o) in self.observers.iter_mut() {
+        for oid in &self.observer_subsets.actions {
+            let o = self
+                .observers
+                .get_mut(oid)
+                .expect("Ordered observer does not exist");
             sink.oid = Some(*oid);
             o.observe_action(action, &self.game_state, &mut sink);
         }
+
+        // Only attached once every observer has seen the action, so that the set of observers
+        // doesn't change part way through the broadcast
+        for (id, observer) in std::mem::take(&mut sink.attachments) {
+            self.attach_observer_with_id(id, observer);
+        }
     }
 
     /// Attempt to perform a single action
     pub fn tick(&mut self) -> TickResult<TGame> {
+        if let Some(outcome) = &self.outcome {
+            return TickResult::Finished(outcome.clone());
+        }
         if self.current_input_session.is_some() {
             return TickResult::NeedPlayerInput;
         }
 
-        match self
-            .action_queue
-            .process(&mut self.action_id_gen, &self.observers, &self.game_state)
-        {
+        match self.action_queue.process(
+            &mut self.action_id_gen,
+            &mut self.observers,
+            &self.observer_subsets.replacements,
+            &self.game_state,
+        ) {
             ActionQueueStatus::AmbiguousReplacements => {
                 todo!("Player input to choose between competing replacement effects")
             }
@@ -368,8 +660,10 @@ impl<TGame: GameDomain> Game<TGame> {
                     .action_queue
                     .pop_next()
                     .expect("Unexpectedly empty pending action set");
+                self.broadcast_pending(&action);
                 self.apply_action(&action);
                 self.broadcast_action(&action);
+                self.record_action(&action);
                 self.game_timestamp.increment();
                 TickResult::Ticked(action)
             }
@@ -378,17 +672,23 @@ impl<TGame: GameDomain> Game<TGame> {
                 let action = Action {
                     payload: ActionPayload::EngineAction(EngineAction::NoActions),
                     source: self.self_id,
+                    controller: Controller::Game,
                     id: self.action_id_gen.next_id(),
                     original: None,
                     generated_at: self.game_timestamp,
                 };
                 self.broadcast_action(&action);
+                self.record_action(&action);
                 self.game_timestamp.increment();
 
                 // If the queue is still empty after broadcasting the first NoActions, we're in the
-                // stalled error state
+                // stalled error state, unless the game is expected to idle
                 if self.action_queue.is_empty() {
-                    TickResult::Stalled
+                    if self.game_state.is_idle_ok() {
+                        TickResult::Idle
+                    } else {
+                        TickResult::Stalled
+                    }
                 } else {
                     TickResult::Ticked(action)
                 }
@@ -397,6 +697,9 @@ impl<TGame: GameDomain> Game<TGame> {
     }
 
     pub fn player_input(&mut self, input: PlayerInput<TGame>) -> Result<(), InputError> {
+        if self.outcome.is_some() {
+            Err(InputError::GameFinished)?
+        }
         let curr_session = match &self.current_input_session {
             None => Err(InputError::NoInputSession)?,
             Some(session) => session,
@@ -405,6 +708,12 @@ impl<TGame: GameDomain> Game<TGame> {
         if curr_session.request.from_player != input.source {
             Err(InputError::WrongPlayer)?
         }
+        if !TGame::input_matches_kind(&curr_session.request.kind, &input.payload) {
+            Err(InputError::Rejected(format!(
+                "Input doesn't match the requested kind of input: {}",
+                curr_session.request.kind
+            )))?
+        }
         let handler_id = curr_session.handler;
 
         let handler = self
@@ -413,40 +722,1037 @@ impl<TGame: GameDomain> Game<TGame> {
             .expect("Input session handler does not exist");
 
         let mut emitted_actions = Vec::new();
-        handler.consume_input(&input, &self.game_state, &mut |action| {
-            emitted_actions.push(action)
-        });
-
-        // Immediately apply and broadcast each of the emitted actions
+        handler
+            .consume_input(&input, &self.game_state, &mut |action| {
+                emitted_actions.push(action)
+            })
+            .map_err(InputError::Rejected)?;
+
+        // Immediately apply and broadcast each of the emitted actions, each of which is a direct
+        // consequence of the player's input
         for action_payload in emitted_actions {
             let action_id = self.action_id_gen.next_id();
             let action = Action {
                 payload: action_payload,
                 source: handler_id,
+                controller: Controller::Player(input.source),
                 id: action_id,
                 generated_at: self.game_timestamp,
                 original: None,
             };
+            self.broadcast_pending(&action);
             self.apply_action(&action);
             self.broadcast_action(&action);
+            self.record_action(&action);
         }
 
         Ok(())
     }
 
-    pub fn tick_until_player_input(&mut self) {
-        while let TickResult::Ticked(_) = self.tick() {}
+    /// Clone out just the state of the game being run and its queue of pending actions
+    ///
+    /// Unlike cloning the whole `Game`, this doesn't drag the attached observers along with it,
+    /// which is useful for analyses that only care about the game state itself.
+    pub fn split_state(&self) -> (TGame, ActionQueue<TGame>) {
+        (self.game_state.clone(), self.action_queue.clone())
+    }
+
+    /// Ends the game with the given outcome
+    ///
+    /// Observers see the game ending as an `EndGame` action, but any actions they emit in response
+    /// are left in the queue unprocessed.
+    pub fn end_game(&mut self, outcome: TGame::Outcome) {
+        let action = Action {
+            payload: ActionPayload::EngineAction(EngineAction::EndGame(outcome)),
+            source: self.self_id,
+            controller: Controller::Game,
+            id: self.action_id_gen.next_id(),
+            original: None,
+            generated_at: self.game_timestamp,
+        };
+        self.broadcast_pending(&action);
+        self.apply_action(&action);
+        self.broadcast_action(&action);
+        self.record_action(&action);
+        self.game_timestamp.increment();
+    }
+
+    /// Immediately performs the given action on behalf of something outside the engine, eg a
+    /// player leaving the game
+    ///
+    /// Can be called at any point, including while waiting for player input. As with the actions
+    /// emitted in response to a player input, anything observers emit in direct reaction to the
+    /// action is also applied straight away, so that an input session it made obsolete can be
+    /// ended before anybody tries to answer it.
+    pub fn perform_action(&mut self, payload: ActionPayload<TGame>) {
+        let action = Action {
+            payload,
+            source: self.self_id,
+            controller: Controller::Game,
+            id: self.action_id_gen.next_id(),
+            original: None,
+            generated_at: self.game_timestamp,
+        };
+        self.broadcast_pending(&action);
+        self.apply_action(&action);
+
+        // Collect the reactions separately from anything that was already queued
+        let queued = std::mem::replace(&mut self.action_queue, ActionQueue::new());
+        self.broadcast_action(&action);
+        let reactions = std::mem::replace(&mut self.action_queue, queued);
+        self.record_action(&action);
+
+        for reaction in reactions.received {
+            self.broadcast_pending(&reaction);
+            self.apply_action(&reaction);
+            self.broadcast_action(&reaction);
+            self.record_action(&reaction);
+        }
+        self.game_timestamp.increment();
+    }
+
+    /// Ticks the game at most `max` times, stopping early if it can't tick any further
+    ///
+    /// Returns the reason the game stopped ticking, which is `TickLimitReached` if every tick
+    /// succeeded.
+    pub fn tick_n(&mut self, max: usize) -> TickResult<TGame> {
+        for _ in 0..max {
+            match self.tick() {
+                TickResult::Ticked(_) => (),
+                res => return res,
+            }
+        }
+        TickResult::TickLimitReached
+    }
+
+    /// Ticks the game until it needs player input, stalls, or finishes, and returns which
+    ///
+    /// Gives up with `TickLimitReached` if the game's `tick_limit` is set and is reached first.
+    /// Ends the game if it's caught in a loop, see `loop_limit`.
+    pub fn tick_until_player_input(&mut self) -> TickResult<TGame> {
+        let mut seen_states = HashMap::<u64, usize>::new();
+        let mut ticks = 0;
+        loop {
+            if self.tick_limit.is_some_and(|limit| ticks >= limit) {
+                return TickResult::TickLimitReached;
+            }
+            match self.tick() {
+                TickResult::Ticked(_) => ticks += 1,
+                res => return res,
+            }
+
+            let limit = match self.loop_limit {
+                Some(limit) => limit,
+                None => continue,
+            };
+            let hash = match self.game_state.state_hash() {
+                Some(hash) => hash,
+                None => continue,
+            };
+            let seen = seen_states.entry(hash).or_insert(0);
+            *seen += 1;
+            if *seen >= limit {
+                if let Some(outcome) = self.game_state.loop_outcome() {
+                    self.end_game(outcome.clone());
+                    return TickResult::Finished(outcome);
+                }
+            }
+        }
+    }
+
+    /// Runs the game with every input coming from the given agents, until either the given
+    /// condition is met or the agents can't continue the game
+    ///
+    /// The condition is checked each time an agent is about to be asked for input.
+    pub fn run_with_agents(
+        &mut self,
+        mut agents: HashMap<PlayerId, Box<dyn Agent<TGame>>>,
+        mut until: impl FnMut(&TGame) -> bool,
+    ) -> AgentRunResult<TGame> {
+        loop {
+            match self.tick_until_player_input() {
+                TickResult::Ticked(_) => unreachable!("Game stopped ticking without a reason"),
+                TickResult::NeedPlayerInput => (),
+                TickResult::Stalled => return AgentRunResult::Stalled,
+                TickResult::Idle => return AgentRunResult::Idle,
+                TickResult::Finished(outcome) => return AgentRunResult::Finished(outcome),
+                TickResult::TickLimitReached => return AgentRunResult::TickLimitReached,
+            }
+
+            if until(&self.game_state) {
+                return AgentRunResult::Stopped;
+            }
+
+            let request = self
+                .current_input_request()
+                .expect("Game stopped ticking without an input session")
+                .clone();
+            let agent = match agents.get_mut(&request.from_player) {
+                Some(agent) => agent,
+                None => return AgentRunResult::NoAgent(request.from_player),
+            };
+
+            let input = agent.decide(self, &request);
+            if let Err(e) = self.player_input(input) {
+                return AgentRunResult::Rejected(request.from_player, e);
+            }
+        }
     }
 
     pub fn expecting_input_from(&self) -> Option<PlayerId> {
-        self.current_input_session
-            .as_ref()
-            .map(|s| s.request.from_player)
+        self.current_input_request().map(|r| r.from_player)
+    }
+
+    /// The request that started the current input session, if there is one
+    pub fn current_input_request(&self) -> Option<&InputRequest<TGame>> {
+        self.current_input_session.as_ref().map(|s| &s.request)
+    }
+
+    /// The observer that each input in the current input session will be sent to, if there is one
+    pub fn input_handler(&self) -> Option<ObserverId> {
+        self.current_input_session.as_ref().map(|s| s.handler)
+    }
+
+    /// Start recording the events caused by each action, to be retrieved with `drain_events`
+    pub fn enable_event_log(&mut self) {
+        if self.event_log.is_none() {
+            self.event_log = Some(Vec::new());
+        }
     }
 
-    pub fn attach_observer(&mut self, mut o: Box<dyn Observer<TGame>>) {
+    /// Every event that has happened since the last call to this method
+    ///
+    /// Always empty if the event log isn't enabled.
+    pub fn drain_events(&mut self) -> Vec<GameEvent<TGame>> {
+        match &mut self.event_log {
+            Some(event_log) => std::mem::take(event_log),
+            None => Vec::new(),
+        }
+    }
+
+    /// Start recording every action the game performs, to be retrieved with
+    /// `drain_action_history`
+    pub fn enable_action_history(&mut self) {
+        if self.action_history.is_none() {
+            self.action_history = Some(Vec::new());
+        }
+    }
+
+    /// Every action the game has performed since the last call to this method
+    ///
+    /// Always empty if action history recording isn't enabled.
+    pub fn drain_action_history(&mut self) -> Vec<Action<TGame>> {
+        match &mut self.action_history {
+            Some(history) => std::mem::take(history),
+            None => Vec::new(),
+        }
+    }
+
+    /// Applies and broadcasts each of the given previously recorded actions in order
+    ///
+    /// Replaying the history recorded from one game onto a fresh copy of that game, built the same
+    /// way with the same observers attached in the same order, brings it to the same state.
+    ///
+    /// Observers see each action as normal, so keep their internal state in step with the game.
+    /// Any actions they emit in reaction are discarded rather than queued, as the history
+    /// already contains whichever of them were performed. Actions that were still waiting in the
+    /// original game's queue when the history was taken aren't part of the history, and so are
+    /// lost.
+    ///
+    /// Player inputs aren't part of the history either, so `consume_input` isn't called again.
+    /// Anything an observer only keeps track of in `consume_input`, eg the first half of a choice
+    /// made over several inputs, won't be rebuilt. Taking the history once every input has been
+    /// fully acted on, as when the game is waiting on a fresh request, avoids this.
+    pub fn replay(&mut self, actions: &[Action<TGame>]) {
+        let queue = std::mem::replace(&mut self.action_queue, ActionQueue::new());
+        for action in actions {
+            self.broadcast_pending(action);
+            self.apply_action(action);
+            self.broadcast_action(action);
+            self.record_action(action);
+            self.action_queue = ActionQueue::new();
+            self.game_timestamp.increment();
+        }
+        self.action_queue = queue;
+    }
+
+    /// Attaches the given observer to the game, returning the ID it was allocated
+    pub fn attach_observer(&mut self, o: Box<dyn Observer<TGame>>) -> ObserverId {
         let id = self.observer_id_gen.next_id();
+        self.attach_observer_with_id(id, o);
+        id
+    }
+
+    fn attach_observer_with_id(&mut self, id: ObserverId, mut o: Box<dyn Observer<TGame>>) {
         o.set_id(id);
+
+        let interests = o.interests();
+        let order = o.reaction_order();
+        let observers = &self.observers;
+        let subsets = &mut self.observer_subsets;
+        insert_ordered(&mut self.observer_order, observers, id, order);
+        if interests.actions {
+            insert_ordered(&mut subsets.actions, observers, id, order);
+        }
+        if interests.replacements {
+            insert_ordered(&mut subsets.replacements, observers, id, order);
+        }
+        if interests.pending {
+            insert_ordered(&mut subsets.pending, observers, id, order);
+        }
         self.observers.insert(id, o);
     }
 }
+
+impl<TGame: GameDomainDiff> Game<TGame> {
+    /// Performs a single tick, as `tick`, and also returns how the game state changed
+    ///
+    /// Clones the whole game state on every call, so is intended for tooling and tests rather than
+    /// for running games.
+    pub fn tick_with_diff(&mut self) -> (TickResult<TGame>, Vec<TGame::Diff>) {
+        let before = self.game_state.clone();
+        let res = self.tick();
+        let diff = TGame::diff(&before, &self.game_state);
+        (res, diff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use crate::{
+        actions::{Action, ActionPayload, EngineAction, InputRequest},
+        game::InputError,
+        game::TickResult,
+        ids::{IdGenerator, ObserverId, PlayerId},
+        test_utils::{
+            game_action, new_game, new_game_with, queue_action, Add, Clock, ClockTick, Counter,
+            NumberKind,
+        },
+        ActionSink, BaseObserver, ObserverInterests, PlayerInput, PlayerInputPayload,
+    };
+
+    /// Asks the given player for a positive number once, and adds it to the counter
+    #[derive(Clone, Debug)]
+    struct AskForNumber {
+        player: PlayerId,
+        asked: bool,
+    }
+
+    impl BaseObserver<Counter> for AskForNumber {
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            _game_state: &Counter,
+            sink: &mut dyn ActionSink<Counter>,
+        ) {
+            if let ActionPayload::EngineAction(EngineAction::NoActions) = action.payload {
+                if !self.asked {
+                    self.asked = true;
+                    sink.emit_single(ActionPayload::EngineAction(EngineAction::RequestInput(
+                        InputRequest {
+                            from_player: self.player,
+                            kind: NumberKind::Positive,
+                        },
+                    )));
+                }
+            }
+        }
+
+        fn consume_input(
+            &mut self,
+            input: &PlayerInput<Counter>,
+            _game_state: &Counter,
+            emit_action: &mut dyn FnMut(ActionPayload<Counter>),
+        ) -> Result<(), String> {
+            let x = input.payload.as_domain_input().expect("Expected a number");
+            emit_action(ActionPayload::DomainAction(Add(*x)));
+            emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_split_state() {
+        let mut game = new_game();
+        queue_action(&mut game, Add(3));
+        game.tick();
+        queue_action(&mut game, Add(4));
+
+        let (state, queue) = game.split_state();
+        assert_eq!(state, game.game_state);
+        assert_eq!(state, Counter { value: 3 });
+        assert_eq!(queue.received.len(), 1);
+    }
+
+    #[test]
+    fn test_input_kind_mismatch_rejected() {
+        let mut game = new_game();
+        let player = IdGenerator::<PlayerId>::new().next_id();
+        game.attach_observer(Box::new(AskForNumber {
+            player,
+            asked: false,
+        }));
+        game.tick_until_player_input();
+        assert_eq!(game.expecting_input_from(), Some(player));
+
+        let res = game.player_input(PlayerInput {
+            source: player,
+            payload: PlayerInputPayload::DomainInput(-2),
+        });
+        assert!(matches!(res, Err(InputError::Rejected(_))));
+        assert_eq!(game.game_state, Counter { value: 0 });
+        assert_eq!(game.expecting_input_from(), Some(player));
+
+        game.player_input(PlayerInput {
+            source: player,
+            payload: PlayerInputPayload::DomainInput(2),
+        })
+        .unwrap();
+        assert_eq!(game.game_state, Counter { value: 2 });
+        assert_eq!(game.expecting_input_from(), None);
+    }
+
+    #[test]
+    fn test_input_session_accessors() {
+        let mut game = new_game();
+        let player = IdGenerator::<PlayerId>::new().next_id();
+        game.attach_observer(Box::new(AskForNumber {
+            player,
+            asked: false,
+        }));
+        let asker = *game.observers.keys().next().unwrap();
+        assert!(game.current_input_request().is_none());
+        assert_eq!(game.input_handler(), None);
+
+        game.tick_until_player_input();
+        let request = game.current_input_request().unwrap();
+        assert_eq!(request.from_player, player);
+        assert_eq!(request.kind, NumberKind::Positive);
+        assert_eq!(game.input_handler(), Some(asker));
+
+        game.player_input(PlayerInput {
+            source: player,
+            payload: PlayerInputPayload::DomainInput(2),
+        })
+        .unwrap();
+        assert!(game.current_input_request().is_none());
+        assert_eq!(game.input_handler(), None);
+    }
+
+    /// Gives up on any input being waited for as soon as anything is added to the counter
+    #[derive(Clone, Debug)]
+    struct EndInputOnAdd;
+
+    impl BaseObserver<Counter> for EndInputOnAdd {
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            _game_state: &Counter,
+            sink: &mut dyn ActionSink<Counter>,
+        ) {
+            if let ActionPayload::DomainAction(_) = action.payload {
+                sink.emit_single(ActionPayload::EngineAction(EngineAction::EndInput));
+            }
+        }
+    }
+
+    #[test]
+    fn test_perform_action() {
+        let mut game = new_game();
+        let player = IdGenerator::<PlayerId>::new().next_id();
+        game.attach_observer(Box::new(AskForNumber {
+            player,
+            asked: false,
+        }));
+        game.tick_until_player_input();
+        queue_action(&mut game, Add(4));
+
+        // The action is applied even though the game is waiting for input, and nothing that was
+        // already queued happens yet
+        game.perform_action(ActionPayload::DomainAction(Add(3)));
+        assert_eq!(game.game_state, Counter { value: 3 });
+        assert_eq!(game.expecting_input_from(), Some(player));
+
+        // Reactions to the action are applied straight away too
+        game.attach_observer(Box::new(EndInputOnAdd));
+        game.perform_action(ActionPayload::DomainAction(Add(2)));
+        assert_eq!(game.game_state, Counter { value: 5 });
+        assert_eq!(game.expecting_input_from(), None);
+
+        game.tick_until_player_input();
+        assert_eq!(game.game_state, Counter { value: 9 });
+    }
+
+    #[test]
+    fn test_event_log() {
+        let mut game = new_game();
+        queue_action(&mut game, Add(3));
+        game.tick();
+        assert!(game.drain_events().is_empty());
+
+        game.enable_event_log();
+        queue_action(&mut game, Add(0));
+        queue_action(&mut game, Add(4));
+        game.tick_until_player_input();
+
+        // Adding zero doesn't change the counter, so isn't an event
+        let events = game.drain_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, 7);
+        assert!(game.drain_events().is_empty());
+    }
+
+    /// Emits a composite of +1 and +2 alongside a separate +10 once, and records the value of the
+    /// counter as each addition is observed
+    #[derive(Clone, Debug, Default)]
+    struct CompositeEmitter {
+        emitted: bool,
+        seen: Rc<RefCell<Vec<i32>>>,
+    }
+
+    impl BaseObserver<Counter> for CompositeEmitter {
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            game_state: &Counter,
+            sink: &mut dyn ActionSink<Counter>,
+        ) {
+            match &action.payload {
+                ActionPayload::EngineAction(EngineAction::NoActions) if !self.emitted => {
+                    self.emitted = true;
+                    sink.emit_composite(
+                        "three",
+                        vec![
+                            ActionPayload::DomainAction(Add(1)),
+                            ActionPayload::DomainAction(Add(2)),
+                        ],
+                    );
+                    sink.emit_many(vec![ActionPayload::DomainAction(Add(10))]);
+                }
+                ActionPayload::DomainAction(_) => self.seen.borrow_mut().push(game_state.value),
+                _ => (),
+            }
+        }
+    }
+
+    #[test]
+    fn test_composite_is_atomic() {
+        let mut game = new_game();
+        let emitter = CompositeEmitter::default();
+        let seen = emitter.seen.clone();
+        game.attach_observer(Box::new(emitter));
+        game.tick_until_player_input();
+
+        // Both halves of the composite are applied before either is observed, and the separate
+        // action can't be ordered between them
+        assert_eq!(game.game_state, Counter { value: 13 });
+        let seen = seen.borrow().clone();
+        assert_eq!(seen.len(), 3);
+        assert!(seen == [3, 3, 13] || seen == [10, 13, 13], "{:?}", seen);
+    }
+
+    /// Records the amount of each addition it observes
+    #[derive(Clone, Debug, Default)]
+    struct Recorder {
+        seen: Rc<RefCell<Vec<i32>>>,
+    }
+
+    impl BaseObserver<Counter> for Recorder {
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            _game_state: &Counter,
+            _sink: &mut dyn ActionSink<Counter>,
+        ) {
+            if let ActionPayload::DomainAction(Add(x)) = action.payload {
+                self.seen.borrow_mut().push(x);
+            }
+        }
+    }
+
+    /// Logs its name whenever it observes an action
+    #[derive(Clone, Debug)]
+    struct Ordered {
+        name: &'static str,
+        order: i32,
+        log: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl BaseObserver<Counter> for Ordered {
+        fn reaction_order(&self) -> i32 {
+            self.order
+        }
+
+        fn observe_action(
+            &mut self,
+            _action: &Action<Counter>,
+            _game_state: &Counter,
+            _sink: &mut dyn ActionSink<Counter>,
+        ) {
+            self.log.borrow_mut().push(self.name);
+        }
+    }
+
+    #[test]
+    fn test_reaction_order() {
+        let mut game = new_game();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        for (name, order) in [("late", 1), ("a", 0), ("early", -1), ("b", 0)] {
+            game.attach_observer(Box::new(Ordered {
+                name,
+                order,
+                log: log.clone(),
+            }));
+        }
+
+        queue_action(&mut game, Add(1));
+        game.tick();
+        assert_eq!(*log.borrow(), ["early", "a", "b", "late"]);
+    }
+
+    /// Adds 10 to the counter whenever another observer changes it, and records the ID it's given
+    #[derive(Clone, Debug)]
+    struct Child {
+        id: Rc<Cell<Option<ObserverId>>>,
+    }
+
+    impl BaseObserver<Counter> for Child {
+        fn set_id(&mut self, id: ObserverId) {
+            self.id.set(Some(id));
+        }
+
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            _game_state: &Counter,
+            sink: &mut dyn ActionSink<Counter>,
+        ) {
+            if let ActionPayload::DomainAction(_) = action.payload {
+                if Some(action.source) != self.id.get() {
+                    sink.emit_single(ActionPayload::DomainAction(Add(10)));
+                }
+            }
+        }
+    }
+
+    /// Attaches a single child observer the first time the counter changes
+    #[derive(Clone, Debug)]
+    struct Parent {
+        child_id: Rc<Cell<Option<ObserverId>>>,
+        attached: Rc<Cell<Option<ObserverId>>>,
+    }
+
+    impl BaseObserver<Counter> for Parent {
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            _game_state: &Counter,
+            sink: &mut dyn ActionSink<Counter>,
+        ) {
+            if let (ActionPayload::DomainAction(_), None) = (&action.payload, self.attached.get()) {
+                let id = sink.attach_observer(Box::new(Child {
+                    id: self.child_id.clone(),
+                }));
+                self.attached.set(Some(id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_attach_child_observer() {
+        let mut game = new_game();
+        let child_id = Rc::new(Cell::new(None));
+        let attached = Rc::new(Cell::new(None));
+        let parent_id = game.attach_observer(Box::new(Parent {
+            child_id: child_id.clone(),
+            attached: attached.clone(),
+        }));
+        assert!(game.observers.contains_key(&parent_id));
+
+        // The child is attached straight after the action that caused it, under the ID the parent
+        // was given, but doesn't see that action itself
+        queue_action(&mut game, Add(1));
+        queue_action(&mut game, Add(2));
+        game.tick();
+        let id = attached.get().expect("Parent didn't attach a child");
+        assert_ne!(id, parent_id);
+        assert_eq!(child_id.get(), Some(id));
+        assert!(game.observers.contains_key(&id));
+        assert_eq!(game.game_state, Counter { value: 1 });
+
+        // It does see the action that was already queued behind it
+        game.tick();
+        match game.tick() {
+            TickResult::Ticked(action) => assert_eq!(action.source, id),
+            res => panic!("Expected the child's action to be applied, got {:?}", res),
+        }
+        assert_eq!(game.game_state, Counter { value: 13 });
+    }
+
+    /// Counts the calls made to its hooks
+    #[derive(Clone, Debug)]
+    struct CallCounter {
+        interests: ObserverInterests,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl BaseObserver<Counter> for CallCounter {
+        fn interests(&self) -> ObserverInterests {
+            self.interests
+        }
+
+        fn propose_replacement(&self, _action: &Action<Counter>, _game: &Counter) -> Option<Add> {
+            self.calls.set(self.calls.get() + 1);
+            None
+        }
+
+        fn observe_action(
+            &mut self,
+            _action: &Action<Counter>,
+            _game_state: &Counter,
+            _sink: &mut dyn ActionSink<Counter>,
+        ) {
+            self.calls.set(self.calls.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_inert_observers_not_called() {
+        let mut game = new_game();
+        let inert_calls = Rc::new(Cell::new(0));
+        for _ in 0..1000 {
+            game.attach_observer(Box::new(CallCounter {
+                interests: ObserverInterests::NONE,
+                calls: inert_calls.clone(),
+            }));
+        }
+        let live_calls = Rc::new(Cell::new(0));
+        game.attach_observer(Box::new(CallCounter {
+            interests: ObserverInterests::ACTIONS,
+            calls: live_calls.clone(),
+        }));
+        assert_eq!(game.observer_order.len(), 1001);
+        assert_eq!(game.observer_subsets.actions.len(), 1);
+        assert!(game.observer_subsets.replacements.is_empty());
+        assert!(game.observer_subsets.pending.is_empty());
+
+        for x in 0..10 {
+            queue_action(&mut game, Add(x));
+            game.tick();
+        }
+
+        // One call for each action, rather than a thousand
+        assert_eq!(inert_calls.get(), 0);
+        assert_eq!(live_calls.get(), 10);
+    }
+
+    /// Records the value of the counter before and after each action is applied
+    #[derive(Clone, Debug)]
+    struct BeforeAndAfter {
+        before: Vec<i32>,
+        seen: Rc<RefCell<Vec<(i32, i32)>>>,
+    }
+
+    impl BaseObserver<Counter> for BeforeAndAfter {
+        fn interests(&self) -> ObserverInterests {
+            ObserverInterests {
+                pending: true,
+                ..ObserverInterests::ACTIONS
+            }
+        }
+
+        fn observe_pending(&mut self, _action: &Action<Counter>, game_state: &Counter) {
+            self.before.push(game_state.value);
+        }
+
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            game_state: &Counter,
+            _sink: &mut dyn ActionSink<Counter>,
+        ) {
+            if let ActionPayload::DomainAction(_) = action.payload {
+                let before = self.before.remove(0);
+                self.seen.borrow_mut().push((before, game_state.value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_observe_pending() {
+        let mut game = new_game();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        game.attach_observer(Box::new(BeforeAndAfter {
+            before: Vec::new(),
+            seen: seen.clone(),
+        }));
+        assert_eq!(game.observer_subsets.pending.len(), 1);
+
+        queue_action(&mut game, Add(2));
+        game.tick();
+        queue_action(&mut game, Add(3));
+        game.tick();
+        assert_eq!(*seen.borrow(), vec![(0, 2), (2, 5)]);
+
+        // Every part of a composite action is pending before any of them are applied
+        let parts = vec![Add(1), Add(10)]
+            .into_iter()
+            .map(|add| game_action(&mut game, ActionPayload::DomainAction(add)))
+            .collect();
+        let composite = game_action(&mut game, ActionPayload::composite("both", parts));
+        game.action_queue.add(composite);
+        game.tick();
+        assert_eq!(*seen.borrow(), vec![(0, 2), (2, 5), (5, 16), (5, 16)]);
+    }
+
+    /// Doubles the first addition it sees, and no others
+    #[derive(Clone, Debug, Default)]
+    struct DoubleOnce {
+        used: bool,
+    }
+
+    impl BaseObserver<Counter> for DoubleOnce {
+        fn propose_replacement(&self, action: &Action<Counter>, _game: &Counter) -> Option<Add> {
+            match action.payload {
+                ActionPayload::DomainAction(Add(x)) if !self.used => Some(Add(x * 2)),
+                _ => None,
+            }
+        }
+
+        fn on_replacement_applied(&mut self, action: &Action<Counter>, _game: &Counter) {
+            assert!(action.original.is_some());
+            self.used = true;
+        }
+    }
+
+    #[test]
+    fn test_replacement_applied_once() {
+        let mut game = new_game();
+        game.attach_observer(Box::new(DoubleOnce::default()));
+
+        queue_action(&mut game, Add(3));
+        game.tick_until_player_input();
+        assert_eq!(game.game_state, Counter { value: 6 });
+
+        // The replacement has been used up, so declines to replace this one
+        queue_action(&mut game, Add(3));
+        game.tick_until_player_input();
+        assert_eq!(game.game_state, Counter { value: 9 });
+    }
+
+    /// Tries to replace the first input request it sees with an addition
+    #[derive(Clone, Debug)]
+    struct ReplaceInputRequest;
+
+    impl BaseObserver<Counter> for ReplaceInputRequest {
+        fn propose_replacement(&self, action: &Action<Counter>, _game: &Counter) -> Option<Add> {
+            match action.payload {
+                ActionPayload::EngineAction(EngineAction::RequestInput(_)) => Some(Add(1)),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "only domain actions can be replaced")]
+    fn test_engine_action_replacement_rejected() {
+        let mut game = new_game();
+        let player = IdGenerator::<PlayerId>::new().next_id();
+        game.attach_observer(Box::new(ReplaceInputRequest));
+
+        let request = game_action(
+            &mut game,
+            ActionPayload::EngineAction(EngineAction::RequestInput(InputRequest {
+                from_player: player,
+                kind: NumberKind::Positive,
+            })),
+        );
+        game.action_queue.add(request);
+        game.tick();
+    }
+
+    /// Adds one to every addition, and makes an addition of its own when the game quiesces
+    #[derive(Clone, Debug, Default)]
+    struct Incrementer {
+        emitted: bool,
+    }
+
+    impl BaseObserver<Counter> for Incrementer {
+        fn propose_replacement(&self, action: &Action<Counter>, _game: &Counter) -> Option<Add> {
+            match action.payload {
+                ActionPayload::DomainAction(Add(x)) => Some(Add(x + 1)),
+                _ => None,
+            }
+        }
+
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            _game_state: &Counter,
+            sink: &mut dyn ActionSink<Counter>,
+        ) {
+            if let ActionPayload::EngineAction(EngineAction::NoActions) = action.payload {
+                if !self.emitted {
+                    self.emitted = true;
+                    sink.emit_single(ActionPayload::DomainAction(Add(10)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_own_actions_not_replaced() {
+        let mut game = new_game();
+        game.attach_observer(Box::new(Incrementer::default()));
+
+        // Someone else's action is replaced
+        queue_action(&mut game, Add(1));
+        game.tick();
+        assert_eq!(game.game_state, Counter { value: 2 });
+
+        // But the incrementer's own action isn't
+        game.tick_until_player_input();
+        assert_eq!(game.game_state, Counter { value: 12 });
+    }
+
+    #[test]
+    fn test_nested_composite() {
+        let mut game = new_game();
+        let recorder = Recorder::default();
+        let seen = recorder.seen.clone();
+        game.attach_observer(Box::new(recorder));
+
+        let leaves = [1, 2, 4].map(|x| game_action(&mut game, ActionPayload::DomainAction(Add(x))));
+        let [a, b, c] = leaves;
+        let inner = game_action(&mut game, ActionPayload::composite("inner", vec![b, c]));
+        let outer = game_action(&mut game, ActionPayload::composite("outer", vec![a, inner]));
+        game.action_queue.add(outer);
+        game.tick_until_player_input();
+
+        assert_eq!(game.game_state, Counter { value: 7 });
+        assert_eq!(*seen.borrow(), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_end_game() {
+        let mut game = new_game();
+        let recorder = Recorder::default();
+        let seen = recorder.seen.clone();
+        game.attach_observer(Box::new(recorder));
+        let player = IdGenerator::<PlayerId>::new().next_id();
+
+        queue_action(&mut game, Add(1));
+        queue_action(&mut game, Add(2));
+        game.tick();
+        game.end_game(player);
+        assert!(matches!(game.tick(), TickResult::Finished(winner) if winner == player));
+        game.tick_until_player_input();
+
+        // The remaining action is never applied or broadcast, but is still in the queue
+        assert_eq!(game.game_state, Counter { value: 1 });
+        assert_eq!(*seen.borrow(), vec![1]);
+        assert!(!game.action_queue.is_empty());
+    }
+
+    #[test]
+    fn test_no_input_after_game_ends() {
+        let mut game = new_game();
+        let player = IdGenerator::<PlayerId>::new().next_id();
+        game.attach_observer(Box::new(AskForNumber {
+            player,
+            asked: false,
+        }));
+        game.tick_until_player_input();
+        assert_eq!(game.expecting_input_from(), Some(player));
+
+        // The input session is left open, but nothing can be done with it
+        game.end_game(player);
+        let res = game.player_input(PlayerInput {
+            source: player,
+            payload: PlayerInputPayload::DomainInput(2),
+        });
+        assert!(matches!(res, Err(InputError::GameFinished)));
+        assert_eq!(game.game_state, Counter { value: 0 });
+    }
+
+    #[test]
+    fn test_finished_not_stalled() {
+        let mut game = new_game();
+        assert!(matches!(game.tick(), TickResult::Stalled));
+
+        let player = IdGenerator::<PlayerId>::new().next_id();
+        game.end_game(player);
+        assert!(matches!(game.tick(), TickResult::Finished(winner) if winner == player));
+    }
+
+    #[test]
+    fn test_idle_domain() {
+        let mut game = new_game_with(Clock::default());
+        assert!(matches!(game.tick(), TickResult::Idle));
+
+        // The game picks up again once the clock ticks
+        let action = game_action(&mut game, ActionPayload::DomainAction(ClockTick));
+        game.action_queue.add(action);
+        assert!(matches!(game.tick(), TickResult::Ticked(_)));
+        assert!(matches!(game.tick(), TickResult::Idle));
+        assert_eq!(game.game_state, Clock { time: 1 });
+    }
+
+    /// Adds one to the counter every time the counter changes, forever
+    #[derive(Clone, Debug)]
+    struct Runaway;
+
+    impl BaseObserver<Counter> for Runaway {
+        fn observe_action(
+            &mut self,
+            action: &Action<Counter>,
+            _game_state: &Counter,
+            sink: &mut dyn ActionSink<Counter>,
+        ) {
+            if let ActionPayload::DomainAction(_) = action.payload {
+                sink.emit_single(ActionPayload::DomainAction(Add(1)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tick_budget() {
+        let mut game = new_game();
+        game.attach_observer(Box::new(Runaway));
+        queue_action(&mut game, Add(1));
+
+        assert!(matches!(game.tick_n(10), TickResult::TickLimitReached));
+        assert_eq!(game.game_state, Counter { value: 10 });
+
+        game.tick_limit = Some(100);
+        assert!(matches!(
+            game.tick_until_player_input(),
+            TickResult::TickLimitReached
+        ));
+        assert_eq!(game.game_state, Counter { value: 110 });
+
+        // A well-behaved game stops before using up its budget
+        let mut game = new_game();
+        queue_action(&mut game, Add(1));
+        assert!(matches!(game.tick_n(10), TickResult::Stalled));
+    }
+
+    #[test]
+    fn test_tick_with_diff() {
+        let mut game = new_game();
+        queue_action(&mut game, Add(3));
+        queue_action(&mut game, Add(0));
+
+        let (res, diff) = game.tick_with_diff();
+        assert!(matches!(res, TickResult::Ticked(_)));
+        assert_eq!(diff, vec![3]);
+
+        let (_, diff) = game.tick_with_diff();
+        assert!(diff.is_empty());
+    }
+}
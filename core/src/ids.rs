@@ -1,9 +1,15 @@
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IdGenerator<T> {
     counter: usize,
     _phantom: std::marker::PhantomData<T>,
 }
 
+impl<T> Default for IdGenerator<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> IdGenerator<T> {
     pub fn new() -> Self {
         Self {
@@ -5,13 +5,21 @@
 //! implement any state based game.
 
 pub mod actions;
+pub mod agent;
 pub mod game;
 pub mod ids;
+pub mod rng;
+pub mod turn_order;
+
+#[cfg(test)]
+mod test_utils;
 
 use actions::{Action, ActionPayload};
 use game::GameDomain;
 use ids::{ActionId, ObserverId, PlayerId};
 
+pub use actions::Controller;
+
 /// An input the player can give to be consumed by the engine itself
 #[derive(Clone, Copy, Debug)]
 pub enum EngineInput {
@@ -23,7 +31,7 @@ pub enum EngineInput {
 
 #[derive(Clone, Debug)]
 pub enum PlayerInputPayload<TGame: GameDomain> {
-    /// Inputs intended for the engine itselfj
+    /// Inputs intended for the engine itself
     EngineInput(EngineInput),
 
     /// Domain specific inputs understood by game specific observers
@@ -46,8 +54,93 @@ pub struct PlayerInput<TGame: GameDomain> {
     pub payload: PlayerInputPayload<TGame>,
 }
 
+/// Where observers send the actions they emit
+///
+/// Implemented by the engine for each observer it calls. `emit_composite` and `attach_observer`
+/// have no defaults, as only the engine can give out the action and observer IDs they need.
 pub trait ActionSink<TGame: GameDomain> {
-    fn emit_single(&mut self, new_action: ActionPayload<TGame>);
+    /// Emits an action performed by the given controller
+    fn emit_controlled(&mut self, new_action: ActionPayload<TGame>, controller: Controller);
+
+    /// Emits an action performed by the game itself
+    fn emit_single(&mut self, new_action: ActionPayload<TGame>) {
+        self.emit_controlled(new_action, Controller::Game)
+    }
+
+    /// Emits the given actions as a single composite action performed by the game itself
+    ///
+    /// The actions are all applied to the game state before any of them are broadcast, so nothing
+    /// can be ordered between them.
+    fn emit_composite(&mut self, tag: &'static str, actions: Vec<ActionPayload<TGame>>);
+
+    /// Attaches the given observer to the game, returning the ID it's been given
+    ///
+    /// The observer is attached as soon as every observer has seen the action currently being
+    /// broadcast, so it first sees whichever action is applied next. Lets an observer keep track of
+    /// the child observers it creates, eg to recognise the actions they emit.
+    fn attach_observer(&mut self, observer: Box<dyn Observer<TGame>>) -> ObserverId;
+
+    /// Emits each of the given actions separately, as if performed by the game itself
+    fn emit_many(&mut self, actions: Vec<ActionPayload<TGame>>) {
+        for action in actions {
+            self.emit_single(action);
+        }
+    }
+
+    /// Emits each of the given actions separately, as if performed by the given controller
+    fn emit_many_controlled(&mut self, actions: Vec<ActionPayload<TGame>>, controller: Controller) {
+        for action in actions {
+            self.emit_controlled(action, controller);
+        }
+    }
+}
+
+/// Which of the `BaseObserver` hooks an observer actually implements
+///
+/// The game only calls the hooks an observer is interested in, which saves calling the default
+/// no-op implementations on every observer for every action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ObserverInterests {
+    /// Should `propose_replacement` be called on this observer
+    pub replacements: bool,
+
+    /// Should `observe_action` be called on this observer
+    pub actions: bool,
+
+    /// Should `observe_pending` be called on this observer
+    pub pending: bool,
+}
+
+impl ObserverInterests {
+    pub const ALL: Self = Self {
+        replacements: true,
+        actions: true,
+        pending: true,
+    };
+
+    pub const NONE: Self = Self {
+        replacements: false,
+        actions: false,
+        pending: false,
+    };
+
+    /// Interested in replacing and reacting to actions, but not in seeing them before they're
+    /// applied
+    ///
+    /// The default for observers that don't say otherwise. Few observers need `observe_pending`,
+    /// so it's opted into explicitly rather than called on every observer for every action.
+    pub const DEFAULT: Self = Self {
+        replacements: true,
+        actions: true,
+        pending: false,
+    };
+
+    /// Only interested in reacting to actions, not replacing them
+    pub const ACTIONS: Self = Self {
+        replacements: false,
+        actions: true,
+        pending: false,
+    };
 }
 
 /// Describes an entity that watches/reacts/interjects game actions as they are queued/executed
@@ -61,6 +154,23 @@ pub trait BaseObserver<TGame: GameDomain>: std::fmt::Debug {
     /// accidentally reacting to actions emitted by different observers.
     fn set_id(&mut self, _id: ObserverId) {}
 
+    /// Where this observer comes in the order that observers are shown each action, lowest first
+    ///
+    /// Observers with the same reaction order are shown actions in the order they were attached.
+    /// Read once when the observer is attached.
+    fn reaction_order(&self) -> i32 {
+        0
+    }
+
+    /// Which of this observer's hooks the game needs to call
+    ///
+    /// Read once when the observer is attached. `consume_input` is always called on the observer
+    /// that requested the input, regardless of its interests. An observer that implements
+    /// `observe_pending` has to ask for it here.
+    fn interests(&self) -> ObserverInterests {
+        ObserverInterests::DEFAULT
+    }
+
     /// If this observer is no longer relevant, returning false from this method will cause it to
     /// be cleaned up.
     fn alive(&self, _game: &TGame) -> bool {
@@ -73,11 +183,38 @@ pub trait BaseObserver<TGame: GameDomain>: std::fmt::Debug {
     /// if there are multiple competing replacement actions, either one or zero of those
     /// replacements may be picked based on a combination of game rules and player choice.
     ///
-    /// Only domain actions may be modified
+    /// Only domain actions may be modified. Proposing a replacement for an engine action (eg an
+    /// input request) or a composite action panics; an observer that needs to change the effect of
+    /// one should react to it in `observe_action` instead.
     fn propose_replacement(&self, _action: &Action<TGame>, _game: &TGame) -> Option<TGame::Action> {
         None
     }
 
+    /// Called once a replacement proposed by this observer has been picked to replace the
+    /// original action
+    ///
+    /// The given action is the replacement, with the action it replaced as its `original`. This is
+    /// the place for a replacement effect to update its own state, eg to record that it has been
+    /// used up.
+    fn on_replacement_applied(&mut self, _action: &Action<TGame>, _game: &TGame) {}
+
+    /// The given action is about to be applied to the game state, which is still as it was before
+    ///
+    /// Lets an observer look at anything the action is going to change, eg an object as it was
+    /// before it left its zone, and remember whatever it needs to react to the action in
+    /// `observe_action`. Nothing can be emitted from here, so this can't change what happens.
+    ///
+    /// Ordering, for each action taken from the queue or emitted from `consume_input`:
+    ///  - `observe_pending` is called on every interested observer, in reaction order
+    ///  - the action is applied to the game state
+    ///  - `observe_action` is called on every interested observer, in reaction order
+    ///
+    /// The parts of a composite action are all shown to `observe_pending` before any of them are
+    /// applied, just as they're all applied before any of them are shown to `observe_action`. The
+    /// `NoActions` action that's broadcast when the queue is empty isn't applied, so it's never
+    /// pending.
+    fn observe_pending(&mut self, _action: &Action<TGame>, _game_state: &TGame) {}
+
     /// The given action has just been applied to the game state, this is this observer's chance to
     /// react to it.
     ///
@@ -100,14 +237,15 @@ pub trait BaseObserver<TGame: GameDomain>: std::fmt::Debug {
     /// The game will continue requesting input from the player until the EndInput action is
     /// emitted from this method.
     ///
-    /// TODO: Add a mechanism for the observer to indicate that the given input was invalid
-    /// (perhaps just returning a Result<T, E> from this method)
+    /// If the given input is invalid, an error message describing why should be returned. In that
+    /// case any actions emitted during the call are discarded, and the player is asked to provide
+    /// a different input.
     fn consume_input(
         &mut self,
         _input: &PlayerInput<TGame>,
         _game_state: &TGame,
         _emit_action: &mut dyn FnMut(ActionPayload<TGame>),
-    ) {
+    ) -> Result<(), String> {
         panic!("Input being passed to an observer that has no consume_input implementation")
     }
 }
@@ -0,0 +1,76 @@
+//! A small seedable pseudo-random number generator
+//!
+//! All randomness in a game (shuffling, coin flips, dice rolls) must be derived from an explicit
+//! seed so that games are reproducible. This is an implementation of SplitMix64, which is more
+//! than adequate for shuffling cards but is in no way cryptographically secure.
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniformly distributed number in the range `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0);
+        let n = n as u64;
+
+        // Reject the final partial block of values to avoid any modulo bias
+        let zone = u64::MAX - (u64::MAX % n);
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return (x % n) as usize;
+            }
+        }
+    }
+
+    /// Uniformly shuffle the given items in place
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic() {
+        let mut a = GameRng::from_seed(1234);
+        let mut b = GameRng::from_seed(1234);
+        let mut c = GameRng::from_seed(4321);
+
+        let a_vals = (0..8).map(|_| a.next_u64()).collect::<Vec<_>>();
+        let b_vals = (0..8).map(|_| b.next_u64()).collect::<Vec<_>>();
+        let c_vals = (0..8).map(|_| c.next_u64()).collect::<Vec<_>>();
+        assert_eq!(a_vals, b_vals);
+        assert_ne!(a_vals, c_vals);
+    }
+
+    #[test]
+    fn test_shuffle_is_permutation() {
+        let mut rng = GameRng::from_seed(7);
+        let mut items = (0..52).collect::<Vec<_>>();
+        rng.shuffle(&mut items);
+
+        assert_ne!(items, (0..52).collect::<Vec<_>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..52).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,163 @@
+//! A minimal game domain used to exercise the engine in unit tests
+
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    actions::{Action, ActionPayload, Controller},
+    game::{ActionQueue, Game, GameDomain, GameDomainAction, GameDomainDiff, GameTimestamp},
+    ids::{IdGenerator, ObserverId, PlayerId},
+    PlayerInputPayload,
+};
+
+/// A game whose entire state is a single number
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Counter {
+    pub value: i32,
+}
+
+/// Adds the given amount to the counter
+#[derive(Clone, Debug)]
+pub struct Add(pub i32);
+
+impl GameDomainAction<Counter> for Add {
+    fn apply(&self, state: &mut Counter) {
+        state.value += self.0;
+    }
+}
+
+/// The kinds of number the counter game can ask a player for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberKind {
+    Positive,
+}
+
+impl Display for NumberKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberKind::Positive => write!(f, "a positive number"),
+        }
+    }
+}
+
+impl GameDomain for Counter {
+    type Input = i32;
+    type Action = Add;
+    type InputKind = NumberKind;
+
+    fn input_matches_kind(kind: &NumberKind, input: &PlayerInputPayload<Self>) -> bool {
+        match (kind, input) {
+            (NumberKind::Positive, PlayerInputPayload::DomainInput(x)) => *x > 0,
+            (_, PlayerInputPayload::EngineInput(_)) => false,
+        }
+    }
+
+    /// The new value of the counter each time it changes
+    type Event = i32;
+
+    fn action_events(action: &Add, game_state: &Counter) -> Vec<i32> {
+        if action.0 == 0 {
+            Vec::new()
+        } else {
+            vec![game_state.value + action.0]
+        }
+    }
+
+    /// The player who won
+    type Outcome = PlayerId;
+}
+
+impl GameDomainDiff for Counter {
+    /// The amount the counter changed by
+    type Diff = i32;
+
+    fn diff(before: &Counter, after: &Counter) -> Vec<i32> {
+        if before == after {
+            Vec::new()
+        } else {
+            vec![after.value - before.value]
+        }
+    }
+}
+
+/// A game that does nothing except wait for an external clock to tick
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Clock {
+    pub time: u32,
+}
+
+/// Advances the clock by one unit of time
+#[derive(Clone, Debug)]
+pub struct ClockTick;
+
+impl GameDomainAction<Clock> for ClockTick {
+    fn apply(&self, state: &mut Clock) {
+        state.time += 1;
+    }
+}
+
+impl GameDomain for Clock {
+    type Input = ();
+    type Action = ClockTick;
+    type InputKind = NumberKind;
+
+    fn input_matches_kind(_kind: &NumberKind, _input: &PlayerInputPayload<Self>) -> bool {
+        false
+    }
+
+    type Event = ();
+
+    type Outcome = ();
+
+    fn is_idle_ok(&self) -> bool {
+        true
+    }
+}
+
+pub fn new_game() -> Game<Counter> {
+    new_game_with(Counter::default())
+}
+
+/// A game with the given initial state and no observers
+pub fn new_game_with<TGame: GameDomain>(game_state: TGame) -> Game<TGame> {
+    let mut observer_id_gen = IdGenerator::<ObserverId>::new();
+    let self_id = observer_id_gen.next_id();
+
+    Game {
+        game_state,
+        game_timestamp: GameTimestamp::zero(),
+        action_queue: ActionQueue::new(),
+        observer_id_gen,
+        action_id_gen: IdGenerator::new(),
+        self_id,
+        observers: HashMap::new(),
+        observer_order: Vec::new(),
+        observer_subsets: Default::default(),
+        current_input_session: None,
+        event_log: None,
+        action_history: None,
+        outcome: None,
+        tick_limit: None,
+        loop_limit: None,
+    }
+}
+
+/// Wraps the given payload in an action, as if emitted by the game
+pub fn game_action<TGame: GameDomain>(
+    game: &mut Game<TGame>,
+    payload: ActionPayload<TGame>,
+) -> Action<TGame> {
+    Action {
+        payload,
+        source: game.self_id,
+        controller: Controller::Game,
+        id: game.action_id_gen.next_id(),
+        generated_at: game.game_timestamp,
+        original: None,
+    }
+}
+
+/// Puts the given domain action directly into the game's action queue, as if emitted by the game
+pub fn queue_action(game: &mut Game<Counter>, action: Add) {
+    let action = game_action(game, ActionPayload::DomainAction(action));
+    game.action_queue.add(action);
+}
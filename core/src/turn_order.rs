@@ -0,0 +1,163 @@
+//! The order in which players take turns
+//!
+//! Players keep their seat for the whole game. Removing a player (eg because they lost) leaves
+//! their seat empty, so it is still possible to ask who comes after them, which is needed when
+//! a player leaves the game part way through their own turn.
+
+use crate::ids::PlayerId;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TurnOrder {
+    /// Every player that started the game, in turn order
+    seats: Vec<PlayerId>,
+
+    /// Players that have been removed from the game
+    removed: Vec<PlayerId>,
+}
+
+impl TurnOrder {
+    pub fn new(players: Vec<PlayerId>) -> Self {
+        assert!(
+            !players.is_empty(),
+            "A turn order must have at least one player"
+        );
+        Self {
+            seats: players,
+            removed: Vec::new(),
+        }
+    }
+
+    /// The players remaining in the game, in turn order starting from the first seat
+    pub fn players(&self) -> impl Iterator<Item = PlayerId> + '_ {
+        self.seats
+            .iter()
+            .cloned()
+            .filter(move |p| !self.removed.contains(p))
+    }
+
+    /// The number of players remaining in the game
+    pub fn len(&self) -> usize {
+        self.seats.len() - self.removed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Is the given player still in the game
+    pub fn contains(&self, player: PlayerId) -> bool {
+        self.seats.contains(&player) && !self.removed.contains(&player)
+    }
+
+    /// The remaining player that comes after the given player
+    ///
+    /// The given player doesn't need to still be in the game. If they are the only player
+    /// remaining, the result is the given player.
+    pub fn next(&self, player: PlayerId) -> PlayerId {
+        self.nth_after(player, 1)
+    }
+
+    /// The remaining player that comes `n` places after the given player, skipping over any
+    /// removed players
+    ///
+    /// `nth_after(p, 0)` is `p` itself.
+    pub fn nth_after(&self, player: PlayerId, n: usize) -> PlayerId {
+        assert!(!self.is_empty(), "Every player has been removed");
+        let seat = self
+            .seats
+            .iter()
+            .position(|&p| p == player)
+            .expect("Player isn't part of this turn order");
+
+        let mut current = player;
+        let mut offset = seat;
+        for _ in 0..n {
+            loop {
+                offset = (offset + 1) % self.seats.len();
+                if !self.removed.contains(&self.seats[offset]) {
+                    break;
+                }
+            }
+            current = self.seats[offset];
+        }
+
+        current
+    }
+
+    /// Rotates the seats so that the given player comes first, keeping everyone's relative order
+    pub fn rotate_to_front(&mut self, player: PlayerId) {
+        let seat = self
+            .seats
+            .iter()
+            .position(|&p| p == player)
+            .expect("Player isn't part of this turn order");
+        self.seats.rotate_left(seat);
+    }
+
+    /// Removes the given player from the game, so that they are skipped over by `next`
+    pub fn remove(&mut self, player: PlayerId) {
+        assert!(
+            self.contains(player),
+            "Player isn't part of this turn order"
+        );
+        self.removed.push(player);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::IdGenerator;
+
+    fn players(n: usize) -> Vec<PlayerId> {
+        let mut id_gen = IdGenerator::<PlayerId>::new();
+        (0..n).map(|_| id_gen.next_id()).collect()
+    }
+
+    #[test]
+    fn test_wraparound() {
+        let p = players(3);
+        let order = TurnOrder::new(p.clone());
+
+        assert_eq!(order.next(p[0]), p[1]);
+        assert_eq!(order.next(p[1]), p[2]);
+        assert_eq!(order.next(p[2]), p[0]);
+        assert_eq!(order.nth_after(p[1], 0), p[1]);
+        assert_eq!(order.nth_after(p[1], 2), p[0]);
+        assert_eq!(order.nth_after(p[1], 4), p[2]);
+    }
+
+    #[test]
+    fn test_remove_player() {
+        let p = players(4);
+        let mut order = TurnOrder::new(p.clone());
+
+        order.remove(p[2]);
+        assert_eq!(order.len(), 3);
+        assert!(!order.contains(p[2]));
+        assert_eq!(order.players().collect::<Vec<_>>(), vec![p[0], p[1], p[3]]);
+        assert_eq!(order.next(p[1]), p[3]);
+        assert_eq!(order.nth_after(p[0], 3), p[0]);
+
+        // The player after a removed player is whoever was seated after them
+        assert_eq!(order.next(p[2]), p[3]);
+
+        order.remove(p[3]);
+        order.remove(p[1]);
+        assert_eq!(order.next(p[0]), p[0]);
+        assert_eq!(order.next(p[3]), p[0]);
+    }
+
+    #[test]
+    fn test_rotate_to_front() {
+        let p = players(4);
+        let mut order = TurnOrder::new(p.clone());
+
+        order.rotate_to_front(p[2]);
+        assert_eq!(
+            order.players().collect::<Vec<_>>(),
+            vec![p[2], p[3], p[0], p[1]]
+        );
+        assert_eq!(order.next(p[1]), p[2]);
+    }
+}
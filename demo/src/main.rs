@@ -30,8 +30,9 @@ fn main() {
         .with_player("bob")
         .with_starting_life_total(20)
         .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
-        .with_intial_priority("alice")
-        .build();
+        .with_initial_priority("alice")
+        .build()
+        .expect("Failed to build game");
 
     game.attach_observer(Box::new(StdoutDebugObserver {}));
     dbg!(&game);
@@ -12,7 +12,7 @@ use cursive::{
 use mtg::{
     action::{AdvanceStep, MtgAction, MtgActionDowncast, PassPriority, SetPriority},
     game::{Mtg, MtgGameBuilder},
-    player_inputs::{MtgInput, PriorityInput},
+    player_inputs::{InputKind, MtgInput, MulliganInput, PriorityInput},
     steps::{Step, SubStep},
 };
 use std::ops::DerefMut;
@@ -23,8 +23,9 @@ fn build_new_game() -> Game<Mtg> {
         .with_player("bob")
         .with_starting_life_total(20)
         .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
-        .with_intial_priority("alice")
+        .with_initial_priority("alice")
         .build()
+        .expect("Failed to build game")
 }
 
 struct UiData {
@@ -46,7 +47,7 @@ impl UiData {
     }
 }
 
-fn render_domain_action(action: &Box<dyn MtgAction>) -> String {
+fn render_domain_action(action: &dyn MtgAction) -> String {
     if let Some(a) = action.as_t::<PassPriority>() {
         format!("{} passing priority", a.player)
     } else if let Some(a) = action.as_t::<SetPriority>() {
@@ -75,9 +76,50 @@ fn render_action(action: &Action<Mtg>) -> String {
             }
             EngineAction::PickNextAction(_) => String::from("-- ambiguous ordering resolution --"),
             EngineAction::AttachObserver(o) => format!("Attaching new observer: {:?}", o),
+            EngineAction::EndGame(outcome) => format!("-- Game over ({:?}) --", outcome),
         },
-        ActionPayload::DomainAction(da) => render_domain_action(&da),
-        ActionPayload::Composite(_) => String::from(" -- Composite action --"),
+        ActionPayload::DomainAction(da) => render_domain_action(&**da),
+        ActionPayload::Composite { tag, .. } => format!(" -- Composite action ({}) --", tag),
+    }
+}
+
+/// A short prompt telling the user what they can type for the given kind of input
+fn input_hint(kind: &InputKind) -> &'static str {
+    match kind {
+        InputKind::Priority => "Type \"pass\" to pass priority",
+        InputKind::Mulligan => "Type \"keep\" or \"mulligan\"",
+        InputKind::DeclareAttacker | InputKind::DeclareBlocker => {
+            "Type \"done\" to finish declaring"
+        }
+        InputKind::ChooseAttackTarget(_)
+        | InputKind::ChooseBlockedAttacker(_)
+        | InputKind::ChooseStartingPlayer
+        | InputKind::Discard
+        | InputKind::BottomCard
+        | InputKind::OrderTriggers
+        | InputKind::PlayLand
+        | InputKind::FlipCreature
+        | InputKind::SuspendCard
+        | InputKind::EffectAction
+        | InputKind::CastSpell
+        | InputKind::ActivateAbility
+        | InputKind::ChooseTarget
+        | InputKind::ChooseNumber { .. } => "This kind of input isn't supported yet",
+    }
+}
+
+fn parse_input(kind: &InputKind, input_str: &str) -> Result<MtgInput, String> {
+    match (kind, input_str) {
+        (InputKind::Priority, "pass") => Ok(MtgInput::PriorityInput(PriorityInput::PassPriority)),
+        (InputKind::Mulligan, "keep") => Ok(MtgInput::MulliganInput(MulliganInput::Keep)),
+        (InputKind::Mulligan, "mulligan") => Ok(MtgInput::MulliganInput(MulliganInput::Mulligan)),
+        (InputKind::DeclareAttacker, "done") | (InputKind::DeclareBlocker, "done") => {
+            Ok(MtgInput::Finished)
+        }
+        _ => Err(format!(
+            "Unrecognized input for \"{}\": \"{}\"",
+            kind, input_str
+        )),
     }
 }
 
@@ -88,7 +130,7 @@ fn create_game_view(siv: &mut Cursive) {
     let view: &mut LinearLayout = _view.deref_mut();
 
     // Nuke anything already inside the view
-    while view.len() > 0 {
+    while !view.is_empty() {
         view.remove_child(view.len() - 1);
     }
 
@@ -161,7 +203,7 @@ fn update_game_view(siv: &mut Cursive) {
             for new_action in &data.action_history[v.len()..] {
                 v.add_item(render_action(new_action), new_action.clone());
             }
-            if v.len() > 0 {
+            if !v.is_empty() {
                 v.set_selection(v.len() - 1);
                 v.get_item(v.len() - 1)
                     .map(|(_label, action)| action.clone())
@@ -208,6 +250,9 @@ fn tick_once(siv: &mut Cursive) {
                     }
                     core::game::TickResult::NeedPlayerInput => Err("Can't tick, need player input"),
                     core::game::TickResult::Stalled => Err("Game has stalled"),
+                    core::game::TickResult::Idle => Err("Game is idle"),
+                    core::game::TickResult::TickLimitReached => Err("Game hit its tick limit"),
+                    core::game::TickResult::Finished(_) => Err("Game has finished"),
                 }
             } else {
                 Err("Can't tick, no game in progress")
@@ -254,12 +299,19 @@ fn process_input(siv: &mut Cursive, input_str: &str) {
         }
     };
 
-    let input_payload = match input_str {
-        "pass" => {
-            PlayerInputPayload::DomainInput(MtgInput::PriorityInput(PriorityInput::PassPriority))
+    let kind = match game.current_input_request() {
+        Some(request) => request.kind,
+        None => {
+            err(siv, "No input currently expected");
+            siv.set_user_data(data);
+            return;
         }
-        _ => {
-            err(siv, &format!("Unrecognized input: \"{}\"", input_str));
+    };
+
+    let input_payload = match parse_input(&kind, input_str) {
+        Ok(input) => PlayerInputPayload::DomainInput(input),
+        Err(msg) => {
+            err(siv, &msg);
             siv.set_user_data(data);
             return;
         }
@@ -301,8 +353,8 @@ fn game_input_dialog(siv: &mut Cursive) {
         }
     };
 
-    let input_request = match &game.current_input_session {
-        Some(s) => s.request.clone(),
+    let input_request = match game.current_input_request() {
+        Some(request) => request.clone(),
         None => {
             err(siv, "No input currently expected");
             siv.set_user_data(data);
@@ -313,10 +365,8 @@ fn game_input_dialog(siv: &mut Cursive) {
     siv.add_layer(
         Dialog::around(
             LinearLayout::vertical()
-                .child(TextView::new(format!(
-                    "Input type: {}",
-                    input_request.input_type
-                )))
+                .child(TextView::new(format!("Input type: {}", input_request.kind)))
+                .child(TextView::new(input_hint(&input_request.kind)))
                 .child(TextView::new(format!(
                     "For player: {}",
                     input_request.from_player
@@ -0,0 +1,51 @@
+//! Activated abilities of objects
+//!
+//! See section 602 of the comprehensive rules
+
+use core::ids::AbilityId;
+
+use crate::{action::MtgAction, mana::ManaCost};
+
+/// Everything that has to be paid to activate an ability
+///
+/// 602.1a The activation cost is everything before the colon (:). An ability's activation cost
+///     must be paid by the player who is activating it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ActivationCost {
+    /// Does the cost include the tap symbol {T}
+    pub tap: bool,
+
+    pub mana: ManaCost,
+}
+
+/// An activated ability, written as "[Cost]: [Effect.]" (602.1)
+///
+/// Two abilities are equal if their ids, costs, and kinds are equal. Like `Object`, the effect is
+/// a trait object that can't be compared directly.
+#[derive(Clone, Debug)]
+pub struct ActivatedAbility {
+    pub id: AbilityId,
+    pub cost: ActivationCost,
+
+    /// What the ability does when it resolves
+    pub effect: Box<dyn MtgAction>,
+
+    /// 605.1a An activated ability is a mana ability if it meets all of the following criteria: it
+    ///     doesn't require a target, it could add mana to a player's mana pool when it resolves,
+    ///     and it's not a loyalty ability.
+    ///
+    /// 605.3b An activated mana ability doesn't go on the stack, so it can't be targeted,
+    ///     countered, or otherwise responded to. Rather, it resolves immediately after it is
+    ///     activated.
+    pub is_mana_ability: bool,
+}
+
+impl PartialEq for ActivatedAbility {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.cost == other.cost
+            && self.is_mana_ability == other.is_mana_ability
+    }
+}
+
+impl Eq for ActivatedAbility {}
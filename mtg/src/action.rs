@@ -1,16 +1,35 @@
 use std::any::Any;
 
-use core::{game::GameDomainAction, ids::PlayerId};
+use core::{
+    game::GameDomainAction,
+    ids::{AbilityId, ObjectId, PlayerId, ZoneId},
+};
 
 use crate::{
+    card::{CardType, HasType, KeywordAbility, SpellEffect, SuperType},
+    combat::{AttackTarget, Attacker, Blocker, CombatState},
+    counter::CounterKind,
+    effect::ContinuousEffect,
     game::Mtg,
-    steps::{GameStep, Step, SubStep},
-    zone::ZoneLocation,
-    ObjectReference,
+    mana::{pay_mana_cost, BaseManaCostComponent, Color, Mana, ManaCost, ManaCostComponent},
+    planechase::PlanarDieFace,
+    steps::{BeginningStep, GameStep, Phase, Step, SubStep},
+    target::Target,
+    trigger::{DelayedTrigger, EffectAction, TriggerCondition},
+    zone::{AbstractZoneLocation, ZoneLocation},
+    ConcreteObject, Object, ObjectFilter, ObjectReference,
 };
 
 pub trait BaseMtgAction: std::fmt::Debug + std::any::Any {
     fn apply(&self, game_state: &mut Mtg);
+
+    /// The sub-actions that this action is composed of, if any
+    ///
+    /// Allows observers to react to the individual events that make up a larger simultaneous
+    /// event, eg each creature dying as the result of a board wipe.
+    fn components(&self) -> &[Box<dyn MtgAction>] {
+        &[]
+    }
 }
 
 pub trait AsAny {
@@ -52,12 +71,23 @@ pub trait MtgActionDowncast {
     fn is<T: BaseMtgAction>(&self) -> bool {
         self.as_t::<T>().is_some()
     }
+
+    /// Every action of the given type in this action or any of its (recursive) components
+    fn find_all<T: BaseMtgAction>(&self) -> Vec<&T>;
 }
 
-impl MtgActionDowncast for Box<dyn MtgAction> {
+impl MtgActionDowncast for dyn MtgAction {
     fn as_t<T: BaseMtgAction>(&self) -> Option<&T> {
         self.as_any().downcast_ref()
     }
+
+    fn find_all<T: BaseMtgAction>(&self) -> Vec<&T> {
+        let mut found: Vec<&T> = self.as_t::<T>().into_iter().collect();
+        for component in self.components() {
+            found.extend(component.find_all::<T>());
+        }
+        found
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -72,6 +102,10 @@ impl BaseMtgAction for CompositeAction {
             sub_action.apply(game_state);
         }
     }
+
+    fn components(&self) -> &[Box<dyn MtgAction>] {
+        &self.components
+    }
 }
 
 /// Sets the game step/substep/active player in one atomic action
@@ -84,6 +118,45 @@ pub struct AdvanceStep {
 
 impl BaseMtgAction for AdvanceStep {
     fn apply(&self, game_state: &mut Mtg) {
+        if self.new_step == Step::Beginning(BeginningStep::Untap)
+            && self.new_substep.is_in_progress()
+        {
+            game_state.turn_number += 1;
+
+            // Anything the last turn skipped or had queued up, eg the rest of a phase that was
+            // skipped after an additional phase was added, doesn't carry over to this one
+            game_state.skipped_phases.clear();
+            game_state.step_queue.clear();
+
+            // Use up whichever extra turn or skipped turns led to this turn. The previous turn
+            // may not have reached its cleanup step, if its end phase was skipped.
+            let previous_turn_ended = !matches!(game_state.step.step, Step::Starting(_));
+            if previous_turn_ended && !game_state.extra_turns.is_empty() {
+                game_state.extra_turns.pop();
+            } else {
+                if previous_turn_ended {
+                    let previous = game_state.last_regular_turn;
+                    let mut player = game_state.turn_order.next(previous);
+                    while player != self.new_active_player {
+                        let skip = game_state.skipped_turns.iter().position(|&p| p == player);
+                        if let Some(index) = skip {
+                            game_state.skipped_turns.remove(index);
+                        }
+                        player = game_state.turn_order.next(player);
+                    }
+                }
+                game_state.last_regular_turn = self.new_active_player;
+            }
+        }
+
+        // Any queued steps before this one were skipped
+        if self.new_substep.is_in_progress() {
+            let queue = &mut game_state.step_queue;
+            if let Some(index) = queue.iter().position(|&step| step == self.new_step) {
+                queue.drain(..=index);
+            }
+        }
+
         game_state.step = GameStep {
             active_player: self.new_active_player,
             step: self.new_step,
@@ -92,66 +165,1930 @@ impl BaseMtgAction for AdvanceStep {
     }
 }
 
-/// Sets the current priority holder
+/// The given player takes the first turn of the game, with the rest of the turn order following
+/// on from them
+///
+/// 103.1 [...] The player chosen to take the first turn is the starting player. The game's default
+///     turn order begins with the starting player and proceeds clockwise.
 #[derive(Clone, Debug)]
-pub struct SetPriority {
-    pub new_priority: PlayerId,
+pub struct SetStartingPlayer {
+    pub player: PlayerId,
 }
 
-impl BaseMtgAction for SetPriority {
+impl BaseMtgAction for SetStartingPlayer {
     fn apply(&self, game_state: &mut Mtg) {
-        game_state.priority = Some(self.new_priority);
+        game_state.turn_order.rotate_to_front(self.player);
     }
 }
 
-/// Clears the current priority holder
+/// The given player takes an extra turn after the current one
+///
+/// 500.7 Some effects can give a player extra turns. They do this by adding the turns directly
+///     after the specified turn. If a player is given multiple extra turns, the extra turns are
+///     added one at a time. If multiple players are given extra turns, the extra turns are added
+///     one at a time, in APNAP order. The most recently created turn will be taken first.
 #[derive(Clone, Debug)]
-pub struct PassPriority {
-    /// The player that is passing priority
+pub struct TakeExtraTurn {
     pub player: PlayerId,
 }
 
-impl BaseMtgAction for PassPriority {
+impl BaseMtgAction for TakeExtraTurn {
     fn apply(&self, game_state: &mut Mtg) {
-        game_state.priority = None;
+        game_state.extra_turns.push(self.player);
     }
 }
 
-/// Attempt to move the given object to a new zone
+/// The given player skips their next turn
 ///
-/// Quietly does nothing if the object cannot be found
+/// Skipping multiple turns is done by applying this action once for each turn.
 #[derive(Clone, Debug)]
-pub struct ChangeObjectZone {
-    pub obj_ref: ObjectReference,
-    pub new_loc: ZoneLocation,
+pub struct SkipTurn {
+    pub player: PlayerId,
 }
 
-impl BaseMtgAction for ChangeObjectZone {
+impl BaseMtgAction for SkipTurn {
     fn apply(&self, game_state: &mut Mtg) {
-        let obj = match self.obj_ref {
-            ObjectReference::Concrete(concrete_obj) => game_state
-                .zones
-                .get_mut(&concrete_obj.zone)
-                .expect("Failed to find zone in game state")
-                .remove(concrete_obj.object),
-            ObjectReference::Abstract(zone_loc) => {
-                let zone = game_state
-                    .zones
-                    .get_mut(&zone_loc.zone)
-                    .expect("Failed to find zone in game state");
+        game_state.skipped_turns.push(self.player);
+    }
+}
 
-                zone.resolve_abstract_zone_location(zone_loc.loc)
-                    .map(|oid| zone.remove(oid))
-                    .flatten()
-            }
+/// Takes the given player out of the game, eg because they conceded
+///
+/// See `Mtg::remove_player`. The active player can't leave the game yet.
+#[derive(Clone, Debug)]
+pub struct RemovePlayer {
+    pub player: PlayerId,
+}
+
+impl BaseMtgAction for RemovePlayer {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.remove_player(self.player);
+    }
+}
+
+/// Skips every remaining phase of the given kind this turn, eg "skip your combat phase this turn"
+///
+/// 614.10 An effect that causes a player to skip a step, phase, or turn is a replacement effect.
+///     "Skip [something]" is the same as "Instead of doing [something], do nothing." [...]
+///
+/// Has no effect on the current phase, only on the phases that would begin after it.
+#[derive(Clone, Debug)]
+pub struct SkipPhase {
+    pub phase: Phase,
+}
+
+impl BaseMtgAction for SkipPhase {
+    fn apply(&self, game_state: &mut Mtg) {
+        if !game_state.skipped_phases.contains(&self.phase) {
+            game_state.skipped_phases.push(self.phase);
+        }
+    }
+}
+
+/// Adds an additional combat phase directly after the current phase, eg from Relentless Assault
+///
+/// 500.8 Some effects can add phases to a turn. They do this by adding the phases directly after
+///     the specified phase. If multiple extra phases are created after the same phase, the most
+///     recently created phase will occur first.
+/// 505.1a Only the first main phase of the turn is a precombat main phase. All other main phases
+///     are postcombat main phases.
+#[derive(Clone, Debug)]
+pub struct InsertCombatPhase {
+    /// Whether the additional combat phase is followed by an additional main phase
+    pub with_main_phase: bool,
+}
+
+impl BaseMtgAction for InsertCombatPhase {
+    fn apply(&self, game_state: &mut Mtg) {
+        // The rest of the current phase has to happen before the new one, and the default
+        // ordering resumes where it would have done after the current phase. If the queue is
+        // already in use, it already holds both of those.
+        let current = game_state.step.step;
+        let remaining = current.remaining_in_phase();
+        let queue = &mut game_state.step_queue;
+        if queue.is_empty() {
+            let last = remaining.last().unwrap_or(&current);
+            queue.extend(remaining.iter().cloned());
+            queue.push_back(last.default_next());
+        }
+
+        let mut inserted = Step::combat_phase();
+        if self.with_main_phase {
+            inserted.push(Step::PostCombatMain);
+        }
+
+        for step in inserted.into_iter().rev() {
+            queue.insert(remaining.len(), step);
+        }
+    }
+}
+
+/// A triggered ability controlled by the given player has triggered
+///
+/// 603.3 Once an ability has triggered, its controller puts it on the stack as an object that's
+///     not a card the next time a player would receive priority.
+///
+/// Until then the ability waits in `Mtg::pending_triggers`, see `PutTriggerOnStack`.
+#[derive(Clone, Debug)]
+pub struct TriggerAbility {
+    pub controller: PlayerId,
+
+    /// What the ability does when it resolves
+    pub effect: Box<dyn MtgAction>,
+}
+
+impl BaseMtgAction for TriggerAbility {
+    fn apply(&self, game_state: &mut Mtg) {
+        let id = game_state.object_id_gen.next_id();
+        let mut ability = Object::new(id, self.controller);
+        ability.resolve_action = Some(Box::new(CompositeAction {
+            tag: "resolve_ability",
+            components: vec![
+                self.effect.clone(),
+                Box::new(CeaseToExist {
+                    object: ConcreteObject {
+                        zone: game_state.shared_zones.stack,
+                        object: id,
+                    },
+                }),
+            ],
+        }));
+        game_state.pending_triggers.push(ability);
+    }
+}
+
+/// Creates a delayed triggered ability, along with an effect action that stops it from
+/// triggering if the creating effect allows one
+///
+/// 603.7a Delayed triggered abilities are created during the resolution of spells or abilities,
+///     as the result of a replacement effect being applied, or as a result of a static ability
+///     that allows a player to take an action.
+#[derive(Clone, Debug)]
+pub struct CreateDelayedTrigger {
+    pub controller: PlayerId,
+    pub condition: TriggerCondition,
+
+    /// What the ability does when it resolves
+    pub effect: Box<dyn MtgAction>,
+
+    /// The player who may pay the given cost to stop the ability from triggering, if anyone
+    ///
+    /// Eg "[...] unless that player pays {U} before that step."
+    pub prevention: Option<(PlayerId, ManaCost)>,
+}
+
+impl BaseMtgAction for CreateDelayedTrigger {
+    fn apply(&self, game_state: &mut Mtg) {
+        let id = game_state.ability_id_gen.next_id();
+        game_state.delayed_triggers.push(DelayedTrigger {
+            id,
+            controller: self.controller,
+            condition: self.condition.clone(),
+            effect: self.effect.clone(),
+        });
+
+        if let Some((player, cost)) = &self.prevention {
+            let action_id = game_state.ability_id_gen.next_id();
+            game_state.effect_actions.push(EffectAction {
+                id: action_id,
+                player: *player,
+                cost: cost.clone(),
+                effect: Box::new(RemoveDelayedTrigger { trigger: id }),
+                expires_with: Some(id),
+            });
+        }
+    }
+}
+
+/// Removes the given delayed triggered ability, along with any effect actions tied to it
+///
+/// Happens when the ability triggers (603.7c), or when an effect action stops it from triggering.
+/// Quietly does nothing if the ability has already gone.
+#[derive(Clone, Debug)]
+pub struct RemoveDelayedTrigger {
+    pub trigger: AbilityId,
+}
+
+impl BaseMtgAction for RemoveDelayedTrigger {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.delayed_triggers.retain(|t| t.id != self.trigger);
+        game_state
+            .effect_actions
+            .retain(|a| a.expires_with != Some(self.trigger));
+    }
+}
+
+/// The given player takes the given effect action, paying its cost from their mana pool
+///
+/// 116.2c Some effects allow a player to take an action at a later time [...] Doing so is a
+///     special action.
+///
+/// The action's effect is a component of this action, fixed when it's created, so that it can be
+/// observed like any other action. Quietly does nothing if the action isn't available to the
+/// player, or the cost can't be paid without the player choosing how to pay for some of its
+/// symbols.
+#[derive(Clone, Debug)]
+pub struct TakeEffectAction {
+    player: PlayerId,
+    action: AbilityId,
+    taken: CompositeAction,
+}
+
+impl TakeEffectAction {
+    pub fn new(player: PlayerId, action: AbilityId, game_state: &Mtg) -> Self {
+        let components = game_state
+            .effect_actions
+            .iter()
+            .filter(|a| a.id == action && a.player == player)
+            .map(|a| a.effect.clone())
+            .collect();
+
+        Self {
+            player,
+            action,
+            taken: CompositeAction {
+                tag: "effect_action",
+                components,
+            },
+        }
+    }
+
+    pub fn player(&self) -> PlayerId {
+        self.player
+    }
+
+    pub fn action(&self) -> AbilityId {
+        self.action
+    }
+}
+
+impl BaseMtgAction for TakeEffectAction {
+    fn apply(&self, game_state: &mut Mtg) {
+        let index = match game_state
+            .effect_actions
+            .iter()
+            .position(|a| a.id == self.action && a.player == self.player)
+        {
+            Some(index) => index,
+            None => return,
+        };
+
+        let player = game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state");
+        let cost = &game_state.effect_actions[index].cost;
+        let payment = match pay_mana_cost(player.life_total, cost, &mut player.mana_pool, &[]) {
+            Ok(payment) => payment,
+            Err(_) => return,
+        };
+        player.life_total -= payment.life;
+
+        game_state.effect_actions.remove(index);
+        self.taken.apply(game_state);
+    }
+
+    fn components(&self) -> &[Box<dyn MtgAction>] {
+        &self.taken.components
+    }
+}
+
+/// Puts one of the pending triggered abilities on top of the stack
+///
+/// 603.3b If multiple players have triggered abilities that have triggered since the last time a
+///     player received priority, each player, in APNAP order, puts triggered abilities they control
+///     on the stack in any order they choose.
+///
+/// Quietly does nothing if the given ability isn't pending.
+#[derive(Clone, Debug)]
+pub struct PutTriggerOnStack {
+    pub ability: ObjectId,
+}
+
+impl BaseMtgAction for PutTriggerOnStack {
+    fn apply(&self, game_state: &mut Mtg) {
+        let index = game_state
+            .pending_triggers
+            .iter()
+            .position(|a| a.id == self.ability);
+        if let Some(index) = index {
+            let ability = game_state.pending_triggers.remove(index);
+            game_state
+                .stack_mut()
+                .insert(ability, AbstractZoneLocation::Top);
+        }
+    }
+}
+
+/// The given player activates an ability of a permanent, putting it on top of the stack
+///
+/// 602.2a The player announces that they are activating the ability. If an activated ability is
+///     being activated from a hidden zone, the card that has that ability is revealed. That
+///     ability is created on the stack as an object that's not a card.
+///
+/// The ability's costs are paid separately. Mana abilities don't use the stack, so are never
+/// activated through this action.
+/// Quietly does nothing if the ability can't be found.
+#[derive(Clone, Debug)]
+pub struct ActivateAbility {
+    pub player: PlayerId,
+    pub ability: AbilityId,
+}
+
+impl BaseMtgAction for ActivateAbility {
+    fn apply(&self, game_state: &mut Mtg) {
+        let effect = match game_state.find_ability(self.ability) {
+            Some((_, ability)) => ability.effect.clone(),
+            None => return,
         };
 
-        if let Some(obj) = obj {
+        let id = game_state.object_id_gen.next_id();
+        let mut ability = Object::new(id, self.player);
+        ability.resolve_action = Some(Box::new(CompositeAction {
+            tag: "resolve_ability",
+            components: vec![
+                effect,
+                Box::new(CeaseToExist {
+                    object: ConcreteObject {
+                        zone: game_state.shared_zones.stack,
+                        object: id,
+                    },
+                }),
+            ],
+        }));
+        game_state
+            .stack_mut()
+            .insert(ability, AbstractZoneLocation::Top);
+    }
+}
+
+/// Taps the given permanent
+///
+/// Quietly does nothing if the permanent cannot be found
+#[derive(Clone, Debug)]
+pub struct Tap {
+    pub object: ObjectId,
+}
+
+impl BaseMtgAction for Tap {
+    fn apply(&self, game_state: &mut Mtg) {
+        if let Some(obj) = game_state.battlefield_mut().get_mut(self.object) {
+            obj.tapped = true;
+        }
+    }
+}
+
+/// Untaps the given permanent
+///
+/// Quietly does nothing if the permanent cannot be found
+#[derive(Clone, Debug)]
+pub struct Untap {
+    pub object: ObjectId,
+}
+
+impl BaseMtgAction for Untap {
+    fn apply(&self, game_state: &mut Mtg) {
+        if let Some(obj) = game_state.battlefield_mut().get_mut(self.object) {
+            obj.tapped = false;
+        }
+    }
+}
+
+/// The given player's permanents have now been under their control continuously since their
+/// most recent turn began, so none of them are summoning sick any more (302.6)
+#[derive(Clone, Debug)]
+pub struct ClearSummoningSickness {
+    pub player: PlayerId,
+}
+
+impl BaseMtgAction for ClearSummoningSickness {
+    fn apply(&self, game_state: &mut Mtg) {
+        let player = self.player;
+        for id in game_state
+            .battlefield()
+            .search(|obj| obj.controller == player && obj.summoning_sick)
+        {
             game_state
-                .zones
-                .get_mut(&self.new_loc.zone)
-                .expect("Failed to find zone in game state")
-                .insert(obj, self.new_loc.loc)
+                .battlefield_mut()
+                .get_mut(id)
+                .expect("Failed to find object on the battlefield")
+                .summoning_sick = false;
+        }
+    }
+}
+
+/// Forgets what every player has done so far this turn, eg how many lands they've played, ready
+/// for a new turn
+#[derive(Clone, Debug)]
+pub struct ResetTurnCounts {}
+
+impl BaseMtgAction for ResetTurnCounts {
+    fn apply(&self, game_state: &mut Mtg) {
+        for player in game_state.players.values_mut() {
+            player.lands_played_this_turn = 0;
+            player.planar_die_rolls_this_turn = 0;
+        }
+    }
+}
+
+/// Removes all damage marked on every permanent
+///
+/// 514.2 Second, the following actions happen simultaneously: all damage marked on permanents
+///     (including phased-out permanents) is removed and all "until end of turn" and "this turn"
+///     effects end. This turn-based action doesn't use the stack.
+#[derive(Clone, Debug)]
+pub struct RemoveAllDamage {}
+
+impl BaseMtgAction for RemoveAllDamage {
+    fn apply(&self, game_state: &mut Mtg) {
+        for id in game_state.battlefield().search(|obj| obj.damage_marked > 0) {
+            game_state
+                .battlefield_mut()
+                .get_mut(id)
+                .unwrap()
+                .damage_marked = 0;
+        }
+    }
+}
+
+/// Adds mana of the given color (or colorless mana if None) to the given player's mana pool
+///
+/// 106.4 When an effect instructs a player to add mana, that mana goes into a mana pool.
+#[derive(Clone, Debug)]
+pub struct AddMana {
+    pub player: PlayerId,
+    pub color: Option<Color>,
+    pub amount: u32,
+
+    /// The object producing the mana, if any
+    ///
+    /// The mana is snow mana if this object has the snow supertype at the time it's produced.
+    pub source: Option<ObjectId>,
+}
+
+impl BaseMtgAction for AddMana {
+    fn apply(&self, game_state: &mut Mtg) {
+        let snow = self
+            .source
+            .and_then(|source| game_state.find_object(source))
+            .is_some_and(|(_, obj)| obj.has_type(SuperType::Snow));
+        let player = game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state");
+        for _ in 0..self.amount {
+            player.mana_pool.add(Mana {
+                color: self.color,
+                snow,
+            });
+        }
+    }
+}
+
+/// Empties the given player's mana pool
+///
+/// 500.4 When a step or phase ends, any unused mana left in a player's mana pool empties.
+#[derive(Clone, Debug)]
+pub struct ClearManaPool {
+    pub player: PlayerId,
+}
+
+impl BaseMtgAction for ClearManaPool {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state")
+            .mana_pool
+            .clear();
+    }
+}
+
+/// The given player turns the given face-down permanent face up, paying its morph cost from
+/// their mana pool
+///
+/// 702.37e Any time you have priority, you may turn a face-down permanent you control with a morph
+///     ability face up. This is a special action; it doesn't use the stack. To do this, show all
+///     players what the permanent's morph cost would be if it were face up, pay that cost, then
+///     turn the permanent face up.
+/// 708.8 As a face-down permanent is turned face up, its copiable values revert to its normal
+///     copiable values.
+///
+/// Quietly does nothing if the permanent isn't face down, has no morph cost, or the cost can't be
+/// paid without the player choosing how to pay for some of its symbols.
+#[derive(Clone, Debug)]
+pub struct TurnFaceUp {
+    pub player: PlayerId,
+    pub object: ObjectId,
+}
+
+impl BaseMtgAction for TurnFaceUp {
+    fn apply(&self, game_state: &mut Mtg) {
+        let cost = match game_state.battlefield().get(self.object) {
+            Some(obj) if obj.face_down => match &obj.definition.morph_cost {
+                Some(cost) => cost.clone(),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let player = game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state");
+        let payment = match pay_mana_cost(player.life_total, &cost, &mut player.mana_pool, &[]) {
+            Ok(payment) => payment,
+            Err(_) => return,
+        };
+        player.life_total -= payment.life;
+
+        game_state
+            .battlefield_mut()
+            .get_mut(self.object)
+            .expect("Failed to find object on the battlefield")
+            .face_down = false;
+    }
+}
+
+/// The given player pays the suspend cost of the given card in their hand, and exiles it with
+/// its time counters
+///
+/// 702.62a [...] If you could begin to cast this card by putting it onto the stack from your
+///     hand, you may pay [cost] and exile it with N time counters on it. This action doesn't use
+///     the stack [...]
+///
+/// Exiling the card and putting its time counters on it are components of this action, so each can
+/// be observed individually. Quietly does nothing if the card isn't in the player's hand, doesn't
+/// have suspend, or the cost can't be paid without the player choosing how to pay for some of its
+/// symbols.
+#[derive(Clone, Debug)]
+pub struct SuspendCard {
+    player: PlayerId,
+    object: ObjectId,
+    exiled: CompositeAction,
+}
+
+impl SuspendCard {
+    pub fn new(player: PlayerId, object: ObjectId, game_state: &Mtg) -> Self {
+        let hand = game_state.player(player).hand;
+        let exile = game_state.shared_zones.exile;
+        let time_counters = game_state.zones[&hand]
+            .get(object)
+            .and_then(|obj| obj.definition.suspend.as_ref())
+            .map_or(0, |suspend| suspend.time_counters);
+
+        let components: Vec<Box<dyn MtgAction>> = vec![
+            Box::new(ChangeObjectZone {
+                obj_ref: ObjectReference::Concrete(ConcreteObject { zone: hand, object }),
+                new_loc: ZoneLocation {
+                    zone: exile,
+                    loc: AbstractZoneLocation::Undefined,
+                },
+            }),
+            // Added after the move, as counters don't survive a change of zone (122.2)
+            Box::new(AddCounter {
+                object: ConcreteObject {
+                    zone: exile,
+                    object,
+                },
+                kind: CounterKind::Time,
+                amount: time_counters,
+            }),
+        ];
+
+        Self {
+            player,
+            object,
+            exiled: CompositeAction {
+                tag: "suspend",
+                components,
+            },
+        }
+    }
+
+    pub fn player(&self) -> PlayerId {
+        self.player
+    }
+
+    pub fn object(&self) -> ObjectId {
+        self.object
+    }
+}
+
+impl BaseMtgAction for SuspendCard {
+    fn apply(&self, game_state: &mut Mtg) {
+        let hand = game_state.player(self.player).hand;
+        let cost = match game_state.zones[&hand].get(self.object) {
+            Some(obj) => match &obj.definition.suspend {
+                Some(suspend) => suspend.cost.clone(),
+                None => return,
+            },
+            None => return,
+        };
+
+        let player = game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state");
+        let payment = match pay_mana_cost(player.life_total, &cost, &mut player.mana_pool, &[]) {
+            Ok(payment) => payment,
+            Err(_) => return,
+        };
+        player.life_total -= payment.life;
+
+        self.exiled.apply(game_state);
+    }
+
+    fn components(&self) -> &[Box<dyn MtgAction>] {
+        &self.exiled.components
+    }
+}
+
+/// The given player rolls the planar die, paying the cost to do so from their mana pool
+///
+/// 901.9 Any time a player has priority during their own main phase and the stack is empty, that
+///     player may roll the planar die. [...]
+/// 901.9a If the die result is blank, nothing happens. If the die result is {CHAOS}, each chaos
+///     ability of each face-up plane card triggers. If the die result is {PW}, the player
+///     planeswalks.
+/// 901.9b Rolling the planar die costs {0} the first time it's done in a turn, {1} the second
+///     time, and so on.
+///
+/// The result is left in `Mtg::planar_die_result` for chaos abilities, and the player's
+/// planeswalk on a {PW}, to react to. Quietly does nothing if the cost can't be paid.
+#[derive(Clone, Debug)]
+pub struct RollPlanarDie {
+    pub player: PlayerId,
+}
+
+impl RollPlanarDie {
+    /// The cost for the given player to roll the planar die right now
+    pub fn cost(game_state: &Mtg, player: PlayerId) -> ManaCost {
+        let rolls = game_state.player(player).planar_die_rolls_this_turn;
+        ManaCost {
+            components: vec![ManaCostComponent::Base(
+                BaseManaCostComponent::ConcreteGeneric(rolls),
+            )],
+        }
+    }
+}
+
+impl BaseMtgAction for RollPlanarDie {
+    fn apply(&self, game_state: &mut Mtg) {
+        let cost = Self::cost(game_state, self.player);
+        let player = game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state");
+        if pay_mana_cost(player.life_total, &cost, &mut player.mana_pool, &[]).is_err() {
+            return;
+        }
+        player.planar_die_rolls_this_turn += 1;
+
+        game_state.planar_die_result = Some(PlanarDieFace::roll(&mut game_state.rng));
+    }
+}
+
+/// The given player planeswalks to the top card of their planar deck
+///
+/// To planeswalk means to put each face-up plane card and phenomenon card on the bottom of its
+/// owner's planar deck face down, then move the top card of your planar deck off that planar deck
+/// and turn it face up.
+///
+/// Which plane cards are put on the bottom is fixed when this action is created, with one
+/// `ChangeObjectZone` component per card moved so that each move can be observed individually. The
+/// top card of the planar deck is only found once the face-up cards have moved, so a player whose
+/// only plane is face up planeswalks back to it.
+#[derive(Clone, Debug)]
+pub struct Planeswalk {
+    player: PlayerId,
+    moved: CompositeAction,
+}
+
+impl Planeswalk {
+    pub fn new(player: PlayerId, game_state: &Mtg) -> Self {
+        let command = game_state.shared_zones.command;
+        let mut components = game_state
+            .face_up_planes()
+            .map(|plane| {
+                Box::new(ChangeObjectZone {
+                    obj_ref: ObjectReference::Concrete(ConcreteObject {
+                        zone: command,
+                        object: plane.id,
+                    }),
+                    new_loc: ZoneLocation {
+                        zone: game_state.player(plane.owner).planar_deck,
+                        loc: AbstractZoneLocation::Bottom,
+                    },
+                }) as Box<dyn MtgAction>
+            })
+            .collect::<Vec<_>>();
+        components.push(Box::new(ChangeObjectZone {
+            obj_ref: ObjectReference::Abstract(ZoneLocation {
+                zone: game_state.player(player).planar_deck,
+                loc: AbstractZoneLocation::Top,
+            }),
+            new_loc: ZoneLocation {
+                zone: command,
+                loc: AbstractZoneLocation::Undefined,
+            },
+        }));
+
+        Self {
+            player,
+            moved: CompositeAction {
+                tag: "planeswalk",
+                components,
+            },
+        }
+    }
+
+    pub fn player(&self) -> PlayerId {
+        self.player
+    }
+}
+
+impl BaseMtgAction for Planeswalk {
+    fn apply(&self, game_state: &mut Mtg) {
+        self.moved.apply(game_state);
+    }
+
+    fn components(&self) -> &[Box<dyn MtgAction>] {
+        &self.moved.components
+    }
+}
+
+/// Sets the current priority holder
+#[derive(Clone, Debug)]
+pub struct SetPriority {
+    pub new_priority: PlayerId,
+}
+
+impl BaseMtgAction for SetPriority {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.priority = Some(self.new_priority);
+    }
+}
+
+/// Clears the current priority holder
+#[derive(Clone, Debug)]
+pub struct PassPriority {
+    /// The player that is passing priority
+    pub player: PlayerId,
+}
+
+impl BaseMtgAction for PassPriority {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.priority = None;
+    }
+}
+
+/// Attempt to move the given object to a new zone
+///
+/// An undefined location in an ordered zone means the top of that zone, so that eg a graveyard
+/// keeps its cards in the order they were put there.
+///
+/// Quietly does nothing if the object cannot be found
+#[derive(Clone, Debug)]
+pub struct ChangeObjectZone {
+    pub obj_ref: ObjectReference,
+    pub new_loc: ZoneLocation,
+}
+
+impl BaseMtgAction for ChangeObjectZone {
+    fn apply(&self, game_state: &mut Mtg) {
+        let obj = match self.obj_ref {
+            ObjectReference::Concrete(concrete_obj) => {
+                if concrete_obj.zone == game_state.shared_zones.battlefield {
+                    game_state.record_last_known_information(concrete_obj.object);
+                }
+                let zone = game_state
+                    .zones
+                    .get_mut(&concrete_obj.zone)
+                    .expect("Failed to find zone in game state");
+
+                if zone.contains(concrete_obj.object) {
+                    zone.remove(concrete_obj.object)
+                } else {
+                    None
+                }
+            }
+            ObjectReference::Abstract(zone_loc) => {
+                let zone = game_state
+                    .zones
+                    .get_mut(&zone_loc.zone)
+                    .expect("Failed to find zone in game state");
+
+                zone.resolve_abstract_zone_location(zone_loc.loc)
+                    .and_then(|oid| zone.remove(oid))
+            }
+        };
+
+        if let Some(mut obj) = obj {
+            obj.leave_zone();
+            obj.summoning_sick = self.new_loc.zone == game_state.shared_zones.battlefield;
+            let zone = game_state
+                .zones
+                .get_mut(&self.new_loc.zone)
+                .expect("Failed to find zone in game state");
+            let loc = match self.new_loc.loc {
+                AbstractZoneLocation::Undefined if zone.is_ordered() => AbstractZoneLocation::Top,
+                loc => loc,
+            };
+            zone.insert(obj, loc)
+        }
+    }
+}
+
+/// Moves several objects to new zones as a single simultaneous event, eg "Return all creatures to
+/// their owners' hands."
+///
+/// Which objects move is fixed when this action is created, with one `ChangeObjectZone` component
+/// per object so that each move can be observed individually. Abstract references are all resolved
+/// against the given game state before anything moves, so two references to the top of the same
+/// library mean the same card, which only moves once.
+#[derive(Clone, Debug)]
+pub struct MoveObjects {
+    moves: Vec<(ObjectReference, ZoneLocation)>,
+    moved: CompositeAction,
+}
+
+impl MoveObjects {
+    pub fn new(moves: Vec<(ObjectReference, ZoneLocation)>, game_state: &Mtg) -> Self {
+        let mut moving = Vec::new();
+        let mut components = Vec::new();
+        for (obj_ref, new_loc) in &moves {
+            let concrete = match *obj_ref {
+                ObjectReference::Concrete(concrete_obj) => Some(concrete_obj),
+                ObjectReference::Abstract(zone_loc) => game_state.zones[&zone_loc.zone]
+                    .resolve_abstract_zone_location(zone_loc.loc)
+                    .map(|object| ConcreteObject {
+                        zone: zone_loc.zone,
+                        object,
+                    }),
+            };
+
+            match concrete {
+                Some(concrete_obj) if !moving.contains(&concrete_obj.object) => {
+                    moving.push(concrete_obj.object);
+                    components.push(Box::new(ChangeObjectZone {
+                        obj_ref: ObjectReference::Concrete(concrete_obj),
+                        new_loc: *new_loc,
+                    }) as Box<dyn MtgAction>);
+                }
+                _ => (),
+            }
+        }
+
+        Self {
+            moves,
+            moved: CompositeAction {
+                tag: "move_objects",
+                components,
+            },
+        }
+    }
+
+    /// The moves this action was created with, before they were resolved to concrete objects
+    pub fn moves(&self) -> &[(ObjectReference, ZoneLocation)] {
+        &self.moves
+    }
+}
+
+impl BaseMtgAction for MoveObjects {
+    fn apply(&self, game_state: &mut Mtg) {
+        self.moved.apply(game_state);
+    }
+
+    fn components(&self) -> &[Box<dyn MtgAction>] {
+        &self.moved.components
+    }
+}
+
+/// Removes the given object from the game entirely, eg a token that has left the battlefield
+///
+/// Quietly does nothing if the object cannot be found
+#[derive(Clone, Debug)]
+pub struct CeaseToExist {
+    pub object: ConcreteObject,
+}
+
+impl BaseMtgAction for CeaseToExist {
+    fn apply(&self, game_state: &mut Mtg) {
+        let zone = game_state
+            .zones
+            .get_mut(&self.object.zone)
+            .expect("Failed to find zone in game state");
+        if zone.contains(self.object.object) {
+            zone.remove(self.object.object);
+        }
+    }
+}
+
+/// The given player draws the top card of their library
+///
+/// Quietly does nothing if the library is empty
+#[derive(Clone, Debug)]
+pub struct DrawCard {
+    pub player: PlayerId,
+}
+
+impl BaseMtgAction for DrawCard {
+    fn apply(&self, game_state: &mut Mtg) {
+        let player = game_state.player(self.player);
+        ChangeObjectZone {
+            obj_ref: ObjectReference::Abstract(ZoneLocation {
+                zone: player.library,
+                loc: AbstractZoneLocation::Top,
+            }),
+            new_loc: ZoneLocation {
+                zone: player.hand,
+                loc: AbstractZoneLocation::Undefined,
+            },
+        }
+        .apply(game_state)
+    }
+}
+
+/// The number of cards in each player's opening hand (103.4)
+pub const STARTING_HAND_SIZE: usize = 7;
+
+/// The given player takes a mulligan, shuffling their hand into their library and drawing a new
+/// hand
+///
+/// Putting cards on the bottom of their library for each mulligan taken happens separately, once
+/// they decide to keep (103.5).
+#[derive(Clone, Debug)]
+pub struct TakeMulligan {
+    pub player: PlayerId,
+}
+
+impl BaseMtgAction for TakeMulligan {
+    fn apply(&self, game_state: &mut Mtg) {
+        let player = game_state.player(self.player);
+        let (hand, library) = (player.hand, player.library);
+
+        for object in game_state.zones[&hand].search(|_| true) {
+            ChangeObjectZone {
+                obj_ref: ObjectReference::Concrete(ConcreteObject { zone: hand, object }),
+                new_loc: ZoneLocation {
+                    zone: library,
+                    loc: AbstractZoneLocation::Top,
+                },
+            }
+            .apply(game_state);
+        }
+
+        ShuffleLibrary {
+            player: self.player,
+        }
+        .apply(game_state);
+
+        for _ in 0..STARTING_HAND_SIZE {
+            DrawCard {
+                player: self.player,
+            }
+            .apply(game_state);
+        }
+
+        game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state")
+            .mulligans_taken += 1;
+    }
+}
+
+/// The given player discards the given card from their hand, putting it into their graveyard
+///
+/// Quietly does nothing if the card isn't in the player's hand
+#[derive(Clone, Debug)]
+pub struct DiscardCard {
+    pub player: PlayerId,
+    pub object: ObjectId,
+}
+
+impl BaseMtgAction for DiscardCard {
+    fn apply(&self, game_state: &mut Mtg) {
+        let player = game_state.player(self.player);
+        ChangeObjectZone {
+            obj_ref: ObjectReference::Concrete(ConcreteObject {
+                zone: player.hand,
+                object: self.object,
+            }),
+            new_loc: ZoneLocation {
+                zone: player.graveyard,
+                loc: AbstractZoneLocation::Top,
+            },
+        }
+        .apply(game_state)
+    }
+}
+
+/// Records that the given player has played a land this turn
+///
+/// Emitted alongside the `ChangeObjectZone` that puts the land onto the battlefield.
+#[derive(Clone, Debug)]
+pub struct LandPlayed {
+    pub player: PlayerId,
+}
+
+impl BaseMtgAction for LandPlayed {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state")
+            .lands_played_this_turn += 1;
+    }
+}
+
+/// The given player casts the given card from the given zone, putting it on top of the stack
+///
+/// 601.2a The player announces that they are casting the spell, and it moves from where it is to
+///     the stack. It becomes the topmost object on the stack.
+/// 601.2c The player announces their choice of targets. [...]
+///
+/// The targets have already been chosen and checked by whoever emits this action. Paying the
+/// total cost (601.2h) isn't supported yet.
+///
+/// Quietly does nothing if the card isn't in the given zone
+#[derive(Clone, Debug)]
+pub struct CastSpell {
+    pub player: PlayerId,
+    pub object: ObjectId,
+
+    /// The zone the card is cast from, usually the player's hand
+    pub zone: ZoneId,
+
+    /// The value announced for X, if the spell has an {X} in its mana cost (601.2b)
+    pub x: Option<u32>,
+
+    /// The chosen targets, one for each of the card's target specs
+    pub targets: Vec<Target>,
+}
+
+impl BaseMtgAction for CastSpell {
+    fn apply(&self, game_state: &mut Mtg) {
+        let zone = game_state
+            .zones
+            .get_mut(&self.zone)
+            .expect("Failed to find zone in game state");
+        if !zone.contains(self.object) {
+            return;
+        }
+
+        let mut spell = zone
+            .remove(self.object)
+            .expect("Failed to remove object from zone");
+        spell.leave_zone();
+        spell.controller = self.player;
+        spell.x = self.x;
+        spell.targets = self.targets.clone();
+        spell.resolve_action = Some(Box::new(ResolveSpell {
+            object: self.object,
+        }));
+        game_state
+            .stack_mut()
+            .insert(spell, AbstractZoneLocation::Top);
+    }
+}
+
+/// Resolves the given instant or sorcery spell on the stack
+///
+/// 608.2b If the spell or ability specifies targets, it checks whether the targets are still
+///     legal. [...] If all its targets, for every instance of the word "target," are now
+///     illegal, the spell or ability doesn't resolve. It's removed from the stack and, if it's a
+///     spell, put into its owner's graveyard. Otherwise, the spell or ability will resolve
+///     normally. Illegal targets, if any, won't be affected by parts of a resolving spell's
+///     effect for which they're illegal.
+/// 608.2n As the final part of an instant or sorcery spell's resolution, the spell itself is put
+///     into its owner's graveyard.
+///
+/// Quietly does nothing if the spell is no longer on the stack
+#[derive(Clone, Debug)]
+pub struct ResolveSpell {
+    pub object: ObjectId,
+}
+
+impl BaseMtgAction for ResolveSpell {
+    fn apply(&self, game_state: &mut Mtg) {
+        let stack = game_state.shared_zones.stack;
+        let spell = match game_state.stack().get(self.object) {
+            Some(obj) => obj,
+            None => return,
+        };
+        let owner = spell.owner;
+        let effect = spell.characteristics().spell_effect;
+        let legal_targets = spell
+            .targets
+            .iter()
+            .zip(spell.characteristics().targets.iter())
+            .filter(|(target, spec)| spec.is_legal(game_state, **target))
+            .map(|(target, _)| *target)
+            .collect::<Vec<_>>();
+        if !spell.targets.is_empty() && legal_targets.is_empty() {
+            return CounterSpell {
+                object: self.object,
+            }
+            .apply(game_state);
+        }
+
+        match effect {
+            Some(SpellEffect::DamageTargets(amount)) => {
+                for target in legal_targets {
+                    match target {
+                        Target::Object(object) => DealDamage { object, amount }.apply(game_state),
+                        // 120.3a Damage dealt to a player by a source without infect causes
+                        //     that player to lose that much life.
+                        Target::Player(player) => GainLife {
+                            player,
+                            amount: -amount,
+                        }
+                        .apply(game_state),
+                    }
+                }
+            }
+            None => (),
+        }
+
+        ChangeObjectZone {
+            obj_ref: ObjectReference::Concrete(ConcreteObject {
+                zone: stack,
+                object: self.object,
+            }),
+            new_loc: ZoneLocation {
+                zone: game_state.player(owner).graveyard,
+                loc: AbstractZoneLocation::Top,
+            },
+        }
+        .apply(game_state)
+    }
+}
+
+/// Counters the given spell or ability, removing it from the stack without it resolving
+///
+/// 701.5a To counter a spell or ability means to cancel it, removing it from the stack. It
+///     doesn't resolve and none of its effects occur. A countered spell is put into its owner's
+///     graveyard.
+///
+/// Abilities on the stack aren't cards, so a countered ability just ceases to exist.
+///
+/// Quietly does nothing if the object is no longer on the stack
+#[derive(Clone, Debug)]
+pub struct CounterSpell {
+    pub object: ObjectId,
+}
+
+impl BaseMtgAction for CounterSpell {
+    fn apply(&self, game_state: &mut Mtg) {
+        let stack = game_state.shared_zones.stack;
+        let obj_ref = ConcreteObject {
+            zone: stack,
+            object: self.object,
+        };
+        let (owner, is_spell) = match game_state.stack().get(self.object) {
+            Some(obj) => (
+                obj.owner,
+                obj.resolve_action
+                    .as_ref()
+                    .is_some_and(|action| action.is::<ResolveSpell>()),
+            ),
+            None => return,
+        };
+
+        if is_spell {
+            ChangeObjectZone {
+                obj_ref: ObjectReference::Concrete(obj_ref),
+                new_loc: ZoneLocation {
+                    zone: game_state.player(owner).graveyard,
+                    loc: AbstractZoneLocation::Top,
+                },
+            }
+            .apply(game_state)
+        } else {
+            CeaseToExist { object: obj_ref }.apply(game_state)
+        }
+    }
+}
+
+/// The given player shuffles their library
+///
+/// 701.20a To shuffle a library or a face-down pile of cards, randomize the cards within it so that
+/// no player knows their order.
+#[derive(Clone, Debug)]
+pub struct ShuffleLibrary {
+    pub player: PlayerId,
+}
+
+impl BaseMtgAction for ShuffleLibrary {
+    fn apply(&self, game_state: &mut Mtg) {
+        let library = game_state.player(self.player).library;
+        let Mtg { zones, rng, .. } = game_state;
+        zones
+            .get_mut(&library)
+            .expect("Failed to find zone in game state")
+            .shuffle(rng);
+    }
+}
+
+/// The given player searches their library, putting the object they found (if any) into a new
+/// location and then shuffling their library
+///
+/// 701.19b If a player is instructed to search their library, they may look at every card in it,
+/// and must then shuffle it. To avoid leaking any information about the ordering of the library,
+/// the object being searched for should be selected from the results of `Zone::search`.
+#[derive(Clone, Debug)]
+pub struct SearchLibrary {
+    pub player: PlayerId,
+    pub found: Option<ObjectId>,
+    pub new_loc: ZoneLocation,
+}
+
+impl BaseMtgAction for SearchLibrary {
+    fn apply(&self, game_state: &mut Mtg) {
+        let library = game_state.player(self.player).library;
+
+        if let Some(found) = self.found {
+            ChangeObjectZone {
+                obj_ref: ObjectReference::Concrete(ConcreteObject {
+                    zone: library,
+                    object: found,
+                }),
+                new_loc: self.new_loc,
+            }
+            .apply(game_state);
+        }
+
+        ShuffleLibrary {
+            player: self.player,
+        }
+        .apply(game_state);
+    }
+}
+
+/// Registers a new continuous effect, which applies for as long as its source is on the
+/// battlefield
+#[derive(Clone, Debug)]
+pub struct AddContinuousEffect {
+    pub effect: ContinuousEffect,
+}
+
+impl BaseMtgAction for AddContinuousEffect {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.continuous_effects.push(self.effect.clone());
+    }
+}
+
+/// The given player gains the given amount of life
+///
+/// 119.3 If an effect causes a player to gain life or lose life, that player's life total is
+///     adjusted accordingly.
+#[derive(Clone, Debug)]
+pub struct GainLife {
+    pub player: PlayerId,
+    pub amount: i32,
+}
+
+impl BaseMtgAction for GainLife {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state
+            .players
+            .get_mut(&self.player)
+            .expect("Failed to find player in game state")
+            .life_total += self.amount;
+    }
+}
+
+/// Deals damage to the given permanent
+///
+/// Damage dealt to a planeswalker removes that many loyalty counters from it (120.3c), while damage
+/// dealt to any other permanent is marked on it (120.3e).
+///
+/// Quietly does nothing if the object is no longer on the battlefield
+#[derive(Clone, Debug)]
+pub struct DealDamage {
+    pub object: ObjectId,
+    pub amount: i32,
+}
+
+impl BaseMtgAction for DealDamage {
+    fn apply(&self, game_state: &mut Mtg) {
+        let obj = match game_state.battlefield_mut().get_mut(self.object) {
+            Some(obj) => obj,
+            None => return,
+        };
+
+        if obj.has_type(CardType::Planeswalker) {
+            let loyalty = obj.loyalty();
+            obj.counters
+                .insert(CounterKind::Loyalty, (loyalty - self.amount).max(0));
+        } else {
+            obj.damage_marked += self.amount;
+        }
+    }
+}
+
+/// Puts some number of counters of the given kind on an object
+///
+/// Quietly does nothing if the object cannot be found
+#[derive(Clone, Debug)]
+pub struct AddCounter {
+    pub object: ConcreteObject,
+    pub kind: CounterKind,
+    pub amount: i32,
+}
+
+impl BaseMtgAction for AddCounter {
+    fn apply(&self, game_state: &mut Mtg) {
+        let zone = game_state
+            .zones
+            .get_mut(&self.object.zone)
+            .expect("Failed to find zone in game state");
+        if let Some(obj) = zone.get_mut(self.object.object) {
+            let count = obj.counter_count(self.kind);
+            obj.counters.insert(self.kind, (count + self.amount).max(0));
+        }
+    }
+}
+
+/// Removes up to the given number of counters of the given kind from an object
+///
+/// The number of counters never drops below zero, and quietly does nothing if the object cannot
+/// be found
+#[derive(Clone, Debug)]
+pub struct RemoveCounter {
+    pub object: ConcreteObject,
+    pub kind: CounterKind,
+    pub amount: i32,
+}
+
+impl BaseMtgAction for RemoveCounter {
+    fn apply(&self, game_state: &mut Mtg) {
+        let zone = game_state
+            .zones
+            .get_mut(&self.object.zone)
+            .expect("Failed to find zone in game state");
+        if let Some(obj) = zone.get_mut(self.object.object) {
+            let count = obj.counter_count(self.kind);
+            obj.counters.insert(self.kind, (count - self.amount).max(0));
+        }
+    }
+}
+
+/// Forgets the last known information of the given objects, once nothing needs it any more
+///
+/// Quietly skips any object without last known information.
+#[derive(Clone, Debug)]
+pub struct ForgetLastKnownInformation {
+    pub objects: Vec<ObjectId>,
+}
+
+impl BaseMtgAction for ForgetLastKnownInformation {
+    fn apply(&self, game_state: &mut Mtg) {
+        for object in &self.objects {
+            game_state.last_known_information.remove(object);
+        }
+    }
+}
+
+/// Destroys the given permanent, moving it from the battlefield to its owner's graveyard
+///
+/// Quietly does nothing if the object is no longer on the battlefield
+#[derive(Clone, Debug)]
+pub struct Destroy {
+    pub object: ObjectId,
+}
+
+impl BaseMtgAction for Destroy {
+    fn apply(&self, game_state: &mut Mtg) {
+        if !game_state.battlefield().contains(self.object) {
+            return;
+        }
+
+        game_state.record_last_known_information(self.object);
+        let mut obj = game_state
+            .battlefield_mut()
+            .remove(self.object)
+            .expect("Failed to remove object from the battlefield");
+        obj.leave_zone();
+        let graveyard = game_state.player(obj.owner).graveyard;
+        game_state
+            .zones
+            .get_mut(&graveyard)
+            .expect("Failed to find zone in game state")
+            .insert(obj, AbstractZoneLocation::Top);
+    }
+}
+
+/// Destroys every permanent on the battlefield that matches a filter, as a single simultaneous
+/// event
+///
+/// The set of permanents is fixed when this action is created, with one `Destroy` component per
+/// matching permanent so that each death can be observed individually.
+#[derive(Clone, Debug)]
+pub struct DestroyAll {
+    pub filter: ObjectFilter,
+    destroyed: CompositeAction,
+}
+
+impl DestroyAll {
+    pub fn new(filter: ObjectFilter, game_state: &Mtg) -> Self {
+        let components = game_state
+            .battlefield()
+            .search(|obj| filter.matches(obj))
+            .into_iter()
+            .map(|object| Box::new(Destroy { object }) as Box<dyn MtgAction>)
+            .collect();
+
+        Self {
+            filter,
+            destroyed: CompositeAction {
+                tag: "destroy_all",
+                components,
+            },
+        }
+    }
+}
+
+impl BaseMtgAction for DestroyAll {
+    fn apply(&self, game_state: &mut Mtg) {
+        self.destroyed.apply(game_state);
+    }
+
+    fn components(&self) -> &[Box<dyn MtgAction>] {
+        &self.destroyed.components
+    }
+}
+
+/// Declares the given creature as attacking the given target, tapping it unless it has vigilance
+///
+/// 702.20b Attacking doesn't cause creatures with vigilance to tap.
+#[derive(Clone, Debug)]
+pub struct DeclareAttacker {
+    pub attacker: ObjectId,
+    pub target: AttackTarget,
+}
+
+impl BaseMtgAction for DeclareAttacker {
+    fn apply(&self, game_state: &mut Mtg) {
+        let vigilant = game_state
+            .battlefield()
+            .get(self.attacker)
+            .is_some_and(|obj| obj.has_keyword(game_state, KeywordAbility::Vigilance));
+        if !vigilant {
+            if let Some(obj) = game_state.battlefield_mut().get_mut(self.attacker) {
+                obj.tapped = true;
+            }
+        }
+
+        game_state.combat.attackers.push(Attacker {
+            object: self.attacker,
+            target: self.target,
+        });
+    }
+}
+
+/// Declares the given creature as blocking the given attacking creature
+#[derive(Clone, Debug)]
+pub struct DeclareBlocker {
+    pub blocker: ObjectId,
+    pub attacker: ObjectId,
+}
+
+impl BaseMtgAction for DeclareBlocker {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.combat.blockers.push(Blocker {
+            object: self.blocker,
+            attacker: self.attacker,
+        });
+    }
+}
+
+/// Withdraws a blocking creature's block while blockers are still being declared
+#[derive(Clone, Debug)]
+pub struct UndeclareBlocker {
+    pub blocker: ObjectId,
+}
+
+impl BaseMtgAction for UndeclareBlocker {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state
+            .combat
+            .blockers
+            .retain(|b| b.object != self.blocker);
+    }
+}
+
+/// The active player has finished declaring attackers
+#[derive(Clone, Debug)]
+pub struct FinishDeclaringAttackers {}
+
+impl BaseMtgAction for FinishDeclaringAttackers {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.combat.no_attackers_declared = game_state.combat.attackers.is_empty();
+    }
+}
+
+/// Makes the current combat damage step a first-strike one, adding a second combat damage step
+/// directly after it
+///
+/// 510.4 If at least one attacking or blocking creature has first strike or double strike as the
+///     combat damage step begins, the only creatures that assign combat damage in that step are
+///     those with first strike or double strike. After that step, instead of proceeding to the
+///     end of combat step, the phase gets a second combat damage step. The only creatures that
+///     assign combat damage in that step are the remaining attackers and blockers that had
+///     neither first strike nor double strike as the first combat damage step began, as well as
+///     the remaining attackers and blockers that currently have double strike. [...]
+#[derive(Clone, Debug)]
+pub struct BeginFirstStrikeDamage {
+    /// The attacking and blocking creatures that have first strike or double strike
+    pub first_strikers: Vec<ObjectId>,
+}
+
+impl BaseMtgAction for BeginFirstStrikeDamage {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.combat.first_strikers = Some(self.first_strikers.clone());
+
+        // As with InsertCombatPhase, the queue already holds the rest of the turn if it's in use
+        let current = game_state.step.step;
+        let queue = &mut game_state.step_queue;
+        if queue.is_empty() {
+            let remaining = current.remaining_in_phase();
+            let last = remaining.last().unwrap_or(&current);
+            let next = last.default_next();
+            queue.extend(remaining.iter().cloned());
+            queue.push_back(next);
+        }
+        queue.push_front(current);
+    }
+}
+
+/// Removes all creatures from combat
+#[derive(Clone, Debug)]
+pub struct EndCombat {}
+
+impl BaseMtgAction for EndCombat {
+    fn apply(&self, game_state: &mut Mtg) {
+        game_state.combat = CombatState::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::game::Game;
+
+    use crate::{
+        card::{make_card_universe, CardDefinition},
+        game::MtgGameBuilder,
+        mana::{BaseManaCostComponent, ManaConstraint, ManaCost, ManaCostComponent},
+        test_utils::{create_card, create_object},
+        zone::Zone,
+    };
+
+    fn library_order(zone: &Zone) -> Vec<ObjectId> {
+        (0..zone.len())
+            .map(|n| {
+                zone.resolve_abstract_zone_location(AbstractZoneLocation::NthFromBottom(n))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shuffle_library() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_seed(1234)
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let library = game.game_state.player(alice).library;
+        let cards = (0..6)
+            .map(|_| create_object(&mut game, alice, library, AbstractZoneLocation::Top))
+            .collect::<Vec<_>>();
+        assert_eq!(library_order(&game.game_state.zones[&library]), cards);
+
+        ShuffleLibrary { player: alice }.apply(&mut game.game_state);
+        let expected = [3, 5, 0, 2, 4, 1].map(|n| cards[n]);
+        assert_eq!(library_order(&game.game_state.zones[&library]), expected);
+
+        // Shuffling an empty library does nothing
+        let bobs_library = game.game_state.player(bob).library;
+        ShuffleLibrary { player: bob }.apply(&mut game.game_state);
+        assert!(game.game_state.zones[&bobs_library].is_empty());
+    }
+
+    #[test]
+    fn test_search_library_shuffles() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let library = game.game_state.player(alice).library;
+        let hand = game.game_state.player(alice).hand;
+        for _ in 0..20 {
+            create_object(&mut game, alice, library, AbstractZoneLocation::Top);
+        }
+
+        let pre_search = library_order(&game.game_state.zones[&library]);
+        let found = game.game_state.zones[&library].search(|obj| obj.id == pre_search[5]);
+        assert_eq!(found, vec![pre_search[5]]);
+
+        // The post-search ordering should be exactly what the game's RNG produces, with no trace
+        // of the pre-search ordering.
+        let mut expected = pre_search.clone();
+        expected.remove(5);
+        let mut unshuffled = expected.clone();
+        game.game_state.rng.clone().shuffle(&mut expected);
+
+        SearchLibrary {
+            player: alice,
+            found: Some(found[0]),
+            new_loc: ZoneLocation {
+                zone: hand,
+                loc: AbstractZoneLocation::Undefined,
+            },
+        }
+        .apply(&mut game.game_state);
+
+        let post_search = library_order(&game.game_state.zones[&library]);
+        assert_eq!(post_search, expected);
+        assert_ne!(post_search, unshuffled);
+        assert_eq!(game.game_state.zones[&hand].len(), 1);
+
+        unshuffled.sort_unstable();
+        let mut sorted_post_search = post_search;
+        sorted_post_search.sort_unstable();
+        assert_eq!(sorted_post_search, unshuffled);
+    }
+
+    #[test]
+    fn test_destroy_all_creatures() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+
+        let universe = make_card_universe();
+        let bears = universe.find_by_name("Grizzly Bears").unwrap();
+        let forest = universe.find_by_name("Forest").unwrap();
+
+        let creatures = vec![
+            create_card(
+                &mut game,
+                alice,
+                battlefield,
+                AbstractZoneLocation::Undefined,
+                bears.clone(),
+            ),
+            create_card(
+                &mut game,
+                alice,
+                battlefield,
+                AbstractZoneLocation::Undefined,
+                bears.clone(),
+            ),
+            create_card(
+                &mut game,
+                bob,
+                battlefield,
+                AbstractZoneLocation::Undefined,
+                bears.clone(),
+            ),
+        ];
+        let land = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            forest.clone(),
+        );
+
+        let wrath = DestroyAll::new(ObjectFilter::CardType(CardType::Creature), &game.game_state);
+        let destroyed = (&wrath as &dyn MtgAction)
+            .find_all::<Destroy>()
+            .iter()
+            .map(|d| d.object)
+            .collect::<Vec<_>>();
+        assert_eq!(destroyed, creatures);
+
+        wrath.apply(&mut game.game_state);
+
+        assert_eq!(game.game_state.battlefield().search(|_| true), vec![land]);
+        let alice_graveyard = &game.game_state.zones[&game.game_state.player(alice).graveyard];
+        let bob_graveyard = &game.game_state.zones[&game.game_state.player(bob).graveyard];
+        assert_eq!(alice_graveyard.search(|_| true), creatures[..2]);
+        assert_eq!(bob_graveyard.search(|_| true), creatures[2..]);
+    }
+
+    #[test]
+    fn test_counters() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let graveyard = game.game_state.player(alice).graveyard;
+        let id = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+        let object = ConcreteObject {
+            zone: battlefield,
+            object: id,
+        };
+
+        let counter_count = |game: &Game<Mtg>, zone, kind| {
+            game.game_state.zones[&zone]
+                .get(id)
+                .unwrap()
+                .counter_count(kind)
+        };
+
+        for _ in 0..2 {
+            AddCounter {
+                object,
+                kind: CounterKind::PlusOnePlusOne,
+                amount: 1,
+            }
+            .apply(&mut game.game_state);
+        }
+        AddCounter {
+            object,
+            kind: CounterKind::Charge,
+            amount: 1,
+        }
+        .apply(&mut game.game_state);
+        assert_eq!(
+            counter_count(&game, battlefield, CounterKind::PlusOnePlusOne),
+            2
+        );
+
+        // Removing more counters than there are leaves none rather than a negative number
+        RemoveCounter {
+            object,
+            kind: CounterKind::Charge,
+            amount: 3,
+        }
+        .apply(&mut game.game_state);
+        assert_eq!(counter_count(&game, battlefield, CounterKind::Charge), 0);
+
+        // 122.2 Counters don't survive the object changing zones
+        ChangeObjectZone {
+            obj_ref: ObjectReference::Concrete(object),
+            new_loc: ZoneLocation {
+                zone: graveyard,
+                loc: AbstractZoneLocation::Top,
+            },
+        }
+        .apply(&mut game.game_state);
+        assert_eq!(
+            counter_count(&game, graveyard, CounterKind::PlusOnePlusOne),
+            0
+        );
+    }
+
+    #[test]
+    fn test_cast_spell_with_x() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let hand = game.game_state.player(alice).hand;
+
+        // {X}{R}
+        let definition = CardDefinition {
+            name: "Fireball".to_string(),
+            mana_cost: ManaCost {
+                components: vec![
+                    ManaCostComponent::Base(BaseManaCostComponent::XGeneric),
+                    ManaCostComponent::Base(BaseManaCostComponent::Single(ManaConstraint::Color(
+                        Color::Red,
+                    ))),
+                ],
+            },
+            ..Default::default()
+        };
+        let spell = create_card(
+            &mut game,
+            alice,
+            hand,
+            AbstractZoneLocation::Undefined,
+            definition,
+        );
+        assert_eq!(
+            game.game_state.zones[&hand]
+                .get(spell)
+                .unwrap()
+                .converted_mana_cost(),
+            1
+        );
+
+        CastSpell {
+            player: alice,
+            object: spell,
+            zone: hand,
+            x: Some(3),
+            targets: Vec::new(),
+        }
+        .apply(&mut game.game_state);
+        let on_stack = game.game_state.stack().get(spell).unwrap();
+        assert_eq!(on_stack.converted_mana_cost(), 4);
+    }
+
+    #[test]
+    fn test_leaving_battlefield_resets_object() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let graveyard = game.game_state.player(alice).graveyard;
+        let bears = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            make_card_universe()
+                .find_by_name("Grizzly Bears")
+                .unwrap()
+                .clone(),
+        );
+
+        // Bob has stolen alice's bears, attacked with them, and they've been damaged
+        let obj = game.game_state.battlefield_mut().get_mut(bears).unwrap();
+        obj.controller = bob;
+        obj.tapped = true;
+        obj.damage_marked = 1;
+        obj.counters.insert(CounterKind::PlusOnePlusOne, 1);
+
+        ChangeObjectZone {
+            obj_ref: ObjectReference::Concrete(ConcreteObject {
+                zone: battlefield,
+                object: bears,
+            }),
+            new_loc: ZoneLocation {
+                zone: graveyard,
+                loc: AbstractZoneLocation::Top,
+            },
+        }
+        .apply(&mut game.game_state);
+
+        let obj = game.game_state.zones[&graveyard].get(bears).unwrap();
+        assert_eq!(obj.controller, alice);
+        assert!(!obj.tapped);
+        assert_eq!(obj.damage_marked, 0);
+        assert!(obj.counters.is_empty());
+    }
+
+    #[test]
+    fn test_graveyard_order() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let graveyard = game.game_state.player(alice).graveyard;
+        let bears = make_card_universe()
+            .find_by_name("Grizzly Bears")
+            .unwrap()
+            .clone();
+        let [first, second, third] = [(); 3].map(|_| {
+            create_card(
+                &mut game,
+                alice,
+                battlefield,
+                AbstractZoneLocation::Undefined,
+                bears.clone(),
+            )
+        });
+
+        Destroy { object: first }.apply(&mut game.game_state);
+        Destroy { object: second }.apply(&mut game.game_state);
+        let zone = &game.game_state.zones[&graveyard];
+        assert_eq!(zone.top().map(|o| o.id), Some(second));
+        assert_eq!(zone.nth_from_top(1).map(|o| o.id), Some(first));
+
+        // Without an explicit location, the graveyard is still added to on top
+        ChangeObjectZone {
+            obj_ref: ObjectReference::Concrete(ConcreteObject {
+                zone: battlefield,
+                object: third,
+            }),
+            new_loc: ZoneLocation {
+                zone: graveyard,
+                loc: AbstractZoneLocation::Undefined,
+            },
         }
+        .apply(&mut game.game_state);
+        assert_eq!(
+            game.game_state.zones[&graveyard].top().map(|o| o.id),
+            Some(third)
+        );
     }
 }
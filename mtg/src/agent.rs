@@ -0,0 +1,179 @@
+//! Simple agents for driving a game of magic without any human input
+
+use core::{actions::InputRequest, agent::Agent, game::Game, PlayerInput, PlayerInputPayload};
+
+use crate::{
+    game::Mtg,
+    player_inputs::{InputKind, MtgInput, MulliganInput, PriorityInput},
+};
+
+/// An agent that does as little as possible
+///
+/// It always passes priority, chooses to go first, keeps its opening hand, never attacks or blocks, and picks the cards
+/// with the lowest ObjectIds when it has to discard or put cards on the bottom of its library. Asked for a number, it
+/// picks the smallest one allowed.
+#[derive(Clone, Debug, Default)]
+pub struct AlwaysPassAgent {}
+
+impl Agent<Mtg> for AlwaysPassAgent {
+    fn decide(&mut self, game: &Game<Mtg>, request: &InputRequest<Mtg>) -> PlayerInput<Mtg> {
+        let input = match request.kind {
+            InputKind::Priority => MtgInput::PriorityInput(PriorityInput::PassPriority),
+            InputKind::DeclareAttacker
+            | InputKind::DeclareBlocker
+            | InputKind::PlayLand
+            | InputKind::FlipCreature
+            | InputKind::SuspendCard
+            | InputKind::EffectAction
+            | InputKind::CastSpell
+            | InputKind::ActivateAbility => MtgInput::Finished,
+            InputKind::ChooseStartingPlayer => MtgInput::PlayerId(request.from_player),
+            InputKind::Mulligan => MtgInput::MulliganInput(MulliganInput::Keep),
+            InputKind::Discard | InputKind::BottomCard => {
+                let hand = game.game_state.player(request.from_player).hand;
+                let card = game.game_state.zones[&hand]
+                    .iter()
+                    .next()
+                    .expect("Asked to discard from an empty hand");
+                MtgInput::ObjectId(card.id)
+            }
+            InputKind::OrderTriggers => {
+                let ability = game
+                    .game_state
+                    .pending_triggers
+                    .iter()
+                    .find(|a| a.controller == request.from_player)
+                    .expect("Asked to order triggers without any pending");
+                MtgInput::ObjectId(ability.id)
+            }
+            // Never asked while it never attacks or blocks, but answers with the first choice
+            // that could be legal in case some effect makes it
+            InputKind::ChooseAttackTarget(_) => {
+                MtgInput::PlayerId(game.game_state.turn_order.next(request.from_player))
+            }
+            InputKind::ChooseBlockedAttacker(_) => {
+                let attacker = game
+                    .game_state
+                    .combat
+                    .attackers
+                    .first()
+                    .expect("Asked to block without any attackers");
+                MtgInput::ObjectId(attacker.object)
+            }
+            // Stops casting the spell, as it never casts spells of its own accord
+            InputKind::ChooseTarget => MtgInput::Finished,
+            InputKind::ChooseNumber { min, .. } => MtgInput::Value(min),
+        };
+
+        PlayerInput {
+            source: request.from_player,
+            payload: PlayerInputPayload::DomainInput(input),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use core::{agent::Agent, game::AgentRunResult, ids::IdGenerator};
+
+    use super::*;
+    use crate::{
+        game::MtgGameBuilder,
+        steps::{Step, SubStep},
+        test_utils::create_object,
+        zone::AbstractZoneLocation,
+        ObjectId,
+    };
+
+    #[test]
+    fn test_run_turn_with_agents() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_starting_life_total(20)
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        // Alice has to discard down to seven cards at the end of her turn
+        let hand = game.game_state.player(alice).hand;
+        for _ in 0..8 {
+            create_object(&mut game, alice, hand, AbstractZoneLocation::Undefined);
+        }
+
+        let mut agents: HashMap<_, Box<dyn Agent<Mtg>>> = HashMap::new();
+        agents.insert(alice, Box::new(AlwaysPassAgent::default()));
+        agents.insert(bob, Box::new(AlwaysPassAgent::default()));
+
+        let result = game.run_with_agents(agents, |state| {
+            state.step.active_player == bob && state.step.step == Step::PreCombatMain
+        });
+
+        assert!(matches!(result, AgentRunResult::Stopped));
+        assert_eq!(game.game_state.turn_number, 2);
+        assert_eq!(game.game_state.zones[&hand].len(), 7);
+        assert_eq!(game.game_state.priority, Some(bob));
+    }
+
+    #[test]
+    fn test_answers_choices() {
+        let game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        let mut agent = AlwaysPassAgent::default();
+        let mut decide = |kind| {
+            let request = InputRequest {
+                from_player: alice,
+                kind,
+            };
+            let input = agent.decide(&game, &request);
+            assert_eq!(input.source, alice);
+            let input = *input.payload.as_domain_input().unwrap();
+            assert!(kind.accepts(&input));
+            input
+        };
+
+        assert!(matches!(
+            decide(InputKind::ChooseTarget),
+            MtgInput::Finished
+        ));
+        assert!(matches!(
+            decide(InputKind::ChooseNumber { min: 2, max: 5 }),
+            MtgInput::Value(2)
+        ));
+        let attacker = IdGenerator::<ObjectId>::new().next_id();
+        assert!(matches!(
+            decide(InputKind::ChooseAttackTarget(attacker)),
+            MtgInput::PlayerId(p) if p == bob
+        ));
+    }
+
+    #[test]
+    fn test_missing_agent() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        let mut agents: HashMap<_, Box<dyn Agent<Mtg>>> = HashMap::new();
+        agents.insert(alice, Box::new(AlwaysPassAgent::default()));
+
+        let result = game.run_with_agents(agents, |_| false);
+        assert!(matches!(result, AgentRunResult::NoAgent(p) if p == bob));
+    }
+}
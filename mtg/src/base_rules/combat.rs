@@ -1,31 +1,349 @@
 use core::{
     actions::{Action, ActionPayload, EngineAction, InputRequest},
-    ids::ObserverId,
+    ids::{ObjectId, ObserverId, PlayerId},
     ActionSink, BaseObserver, PlayerInput,
 };
 
 use crate::{
-    action::{AdvanceStep, MtgActionDowncast},
+    action::{
+        AdvanceStep, BeginFirstStrikeDamage, DealDamage, DeclareAttacker, DeclareBlocker,
+        EndCombat, FinishDeclaringAttackers, GainLife, MtgAction, MtgActionDowncast,
+        UndeclareBlocker,
+    },
+    card::{CardType, HasType, KeywordAbility},
+    combat::AttackTarget,
     game::Mtg,
-    player_inputs::MtgInput,
+    player_inputs::{InputKind, MtgInput},
     steps::{CombatStep, Step, SubStep},
+    Object,
 };
 
 #[derive(Clone, Copy, Debug)]
-#[allow(dead_code)]
+#[allow(clippy::enum_variant_names)]
 enum ExpectedInput {
     /// Expect the player to nominate the next object to be an attacker, or that they have finished
     /// declaring attackers
     NextAttackerOrFinished,
 
     /// Expect the player to nominate what the most recently declared attacker is attacking
-    NextAttackee,
+    NextAttackee(ObjectId),
+
+    /// Expect the given defending player to nominate the next object to be a blocker, or that they
+    /// have finished declaring blockers
+    NextBlockerOrFinished(PlayerId),
+
+    /// Expect the given defending player to nominate which attacker the most recently nominated
+    /// blocker is blocking
+    NextBlockee(PlayerId, ObjectId),
+}
+
+/// Is the given attack target the given player, or a planeswalker they control
+fn is_attacking_player(game_state: &Mtg, target: AttackTarget, player: PlayerId) -> bool {
+    match target {
+        AttackTarget::Player(p) => p == player,
+        AttackTarget::Planeswalker(pw) => game_state
+            .battlefield()
+            .get(pw)
+            .is_some_and(|o| o.controller == player),
+    }
+}
+
+/// The players being attacked (directly or via their planeswalkers), in turn order starting from
+/// the active player
+fn defending_players(game_state: &Mtg) -> Vec<PlayerId> {
+    let is_defending = |player: PlayerId| {
+        game_state
+            .combat
+            .attackers
+            .iter()
+            .any(|a| is_attacking_player(game_state, a.target, player))
+    };
+
+    let active_player = game_state.step.active_player;
+    (1..=game_state.turn_order.len())
+        .map(|n| game_state.turn_order.nth_after(active_player, n))
+        .filter(|&p| is_defending(p))
+        .collect()
+}
+
+/// Everything that creatures controlled by the active player could legally attack
+///
+/// 508.1b The active player announces which player or planeswalker each of the chosen creatures is
+///     attacking. Each attacking creature attacks exactly one of those.
+fn legal_attack_targets(game_state: &Mtg) -> Vec<AttackTarget> {
+    let active_player = game_state.step.active_player;
+    let defenders = game_state
+        .turn_order
+        .players()
+        .filter(|&p| p != active_player && game_state.player(p).can_be_attacked)
+        .map(AttackTarget::Player);
+    let planeswalkers = game_state
+        .battlefield()
+        .iter()
+        .filter(|o| o.controller != active_player && o.has_type(CardType::Planeswalker))
+        .map(|o| AttackTarget::Planeswalker(o.id));
+
+    defenders.chain(planeswalkers).collect()
+}
+
+/// The first creature that is required to attack, can attack, and hasn't been declared as an
+/// attacker
+///
+/// 508.1d The active player checks each creature they control to see whether it's affected by any
+///     requirements. A requirement that can't be met, eg because there is nothing the creature
+///     can legally attack, is ignored.
+fn unmet_attack_requirement(game_state: &Mtg) -> Option<ObjectId> {
+    if legal_attack_targets(game_state).is_empty() {
+        return None;
+    }
+
+    game_state
+        .battlefield()
+        .find(|o| {
+            o.must_attack
+                && can_attack(game_state, o).is_ok()
+                && !game_state.combat.is_attacking(o.id)
+        })
+        .map(|o| o.id)
+}
+
+/// Checks that the given permanent is able to be declared as an attacker, returning why not if it
+/// isn't
+///
+/// 508.1a The active player chooses which creatures that they control, if any, will attack. The
+///     chosen creatures must be untapped, and each one must either have haste or have been
+///     controlled by the active player continuously since the turn began.
+fn can_attack(game_state: &Mtg, obj: &Object) -> Result<(), String> {
+    let active_player = game_state.step.active_player;
+    if obj.controller != active_player || obj.tapped {
+        return Err(format!(
+            "{:?} is not an untapped permanent controlled by {}",
+            obj.id, active_player
+        ));
+    }
+    if !obj.has_type(CardType::Creature) {
+        return Err(format!("{:?} is not a creature", obj.id));
+    }
+    if obj.summoning_sick && !obj.has_keyword(game_state, KeywordAbility::Haste) {
+        return Err(format!(
+            "{:?} hasn't been controlled by {} since the turn began",
+            obj.id, active_player
+        ));
+    }
+
+    Ok(())
+}
+
+/// The attacking and blocking creatures that are still on the battlefield
+fn combatants(game_state: &Mtg) -> Vec<&Object> {
+    let combat = &game_state.combat;
+    combat
+        .attackers
+        .iter()
+        .map(|a| a.object)
+        .chain(combat.blockers.iter().map(|b| b.object))
+        .filter_map(|id| game_state.battlefield().get(id))
+        .collect()
+}
+
+/// The creatures that deal combat damage in the combat damage step that is beginning
+///
+/// Emits `BeginFirstStrikeDamage` if this is the first combat damage step of the phase and any of
+/// the combatants have first strike or double strike (510.4).
+fn combat_damage_dealers(game_state: &Mtg, sink: &mut dyn ActionSink<Mtg>) -> Vec<ObjectId> {
+    let combatants = combatants(game_state);
+    if let Some(first_strikers) = &game_state.combat.first_strikers {
+        return combatants
+            .iter()
+            .filter(|o| {
+                !first_strikers.contains(&o.id)
+                    || o.has_keyword(game_state, KeywordAbility::DoubleStrike)
+            })
+            .map(|o| o.id)
+            .collect();
+    }
+
+    let first_strikers = combatants
+        .iter()
+        .filter(|o| o.deals_first_strike_damage(game_state))
+        .map(|o| o.id)
+        .collect::<Vec<_>>();
+    if first_strikers.is_empty() {
+        return combatants.iter().map(|o| o.id).collect();
+    }
+
+    sink.emit_single(ActionPayload::DomainAction(
+        Box::new(BeginFirstStrikeDamage {
+            first_strikers: first_strikers.clone(),
+        }) as Box<dyn MtgAction>,
+    ));
+    first_strikers
+}
+
+/// Damage dealt by an attacking creature to whatever it's attacking
+fn damage_attack_target(target: AttackTarget, amount: i32) -> Box<dyn MtgAction> {
+    match target {
+        // 120.3a Damage dealt to a player by a source without infect causes that player to lose
+        //     that much life.
+        AttackTarget::Player(player) => Box::new(GainLife {
+            player,
+            amount: -amount,
+        }),
+        AttackTarget::Planeswalker(object) => Box::new(DealDamage { object, amount }),
+    }
+}
+
+/// The combat damage dealt by the given attacking and blocking creatures
+///
+/// 510.1a Each attacking creature and each blocking creature assigns combat damage equal to its
+///     power. Creatures that would assign 0 or less damage this way don't assign combat damage at
+///     all.
+///
+/// An attacker blocked by several creatures assigns lethal damage to each of them in the order
+/// they were declared before moving on to the next, taking into account damage already marked on
+/// them. Any excess goes to the last one, or to whatever the attacker is attacking if it has
+/// trample (702.19b). An attacker stays blocked even if its blockers have all left combat (509.1h),
+/// in which case it deals no damage unless it has trample (702.19c).
+fn combat_damage(game_state: &Mtg, dealers: &[ObjectId]) -> Vec<Box<dyn MtgAction>> {
+    let combat = &game_state.combat;
+    let battlefield = game_state.battlefield();
+    let power = |id| game_state.effective_power_toughness(id).0;
+    let mut damage: Vec<Box<dyn MtgAction>> = Vec::new();
+
+    for attacker in combat
+        .attackers
+        .iter()
+        .filter(|a| dealers.contains(&a.object))
+    {
+        let mut remaining = power(attacker.object);
+        if remaining <= 0 {
+            continue;
+        }
+
+        let blockers = combat
+            .blockers
+            .iter()
+            .filter(|b| b.attacker == attacker.object)
+            .collect::<Vec<_>>();
+        if blockers.is_empty() {
+            damage.push(damage_attack_target(attacker.target, remaining));
+            continue;
+        }
+
+        let trample = battlefield
+            .get(attacker.object)
+            .is_some_and(|o| o.has_keyword(game_state, KeywordAbility::Trample));
+        let blockers = blockers
+            .iter()
+            .filter_map(|b| battlefield.get(b.object))
+            .collect::<Vec<_>>();
+        for (n, blocker) in blockers.iter().enumerate() {
+            let lethal =
+                (game_state.effective_power_toughness(blocker.id).1 - blocker.damage_marked).max(0);
+            let amount = if n == blockers.len() - 1 && !trample {
+                remaining
+            } else {
+                remaining.min(lethal)
+            };
+            if amount > 0 {
+                damage.push(Box::new(DealDamage {
+                    object: blocker.id,
+                    amount,
+                }));
+            }
+            remaining -= amount;
+        }
+
+        if trample && remaining > 0 {
+            damage.push(damage_attack_target(attacker.target, remaining));
+        }
+    }
+
+    for blocker in combat
+        .blockers
+        .iter()
+        .filter(|b| dealers.contains(&b.object))
+    {
+        let amount = power(blocker.object);
+        if amount > 0 && battlefield.contains(blocker.attacker) {
+            damage.push(Box::new(DealDamage {
+                object: blocker.attacker,
+                amount,
+            }));
+        }
+    }
+
+    damage
+}
+
+/// Can the given creature block the given attacker, considering evasion abilities
+///
+/// 702.9b A creature with flying can't be blocked except by creatures with flying and/or reach.
+///     [...]
+fn can_block(game_state: &Mtg, blocker: &Object, attacker: &Object) -> bool {
+    let has_keyword = |obj: &Object, keyword| obj.has_keyword(game_state, keyword);
+    !has_keyword(attacker, KeywordAbility::Flying)
+        || has_keyword(blocker, KeywordAbility::Flying)
+        || has_keyword(blocker, KeywordAbility::Reach)
+}
+
+/// The first attacker with menace that the given defending player has blocked with only one
+/// creature
+///
+/// 702.110b A creature with menace can't be blocked except by two or more creatures.
+fn unmet_menace(game_state: &Mtg, defender: PlayerId) -> Option<ObjectId> {
+    let combat = &game_state.combat;
+    let battlefield = game_state.battlefield();
+    combat
+        .attackers
+        .iter()
+        .filter(|a| {
+            battlefield
+                .get(a.object)
+                .is_some_and(|o| o.has_keyword(game_state, KeywordAbility::Menace))
+        })
+        .find(|a| {
+            let blockers = combat.blockers.iter().filter(|b| {
+                b.attacker == a.object
+                    && battlefield
+                        .get(b.object)
+                        .is_some_and(|o| o.controller == defender)
+            });
+            blockers.count() == 1
+        })
+        .map(|a| a.object)
+}
+
+fn request_input(player: PlayerId, kind: InputKind) -> ActionPayload<Mtg> {
+    ActionPayload::EngineAction(EngineAction::RequestInput(InputRequest {
+        from_player: player,
+        kind,
+    }))
+}
+
+/// Replaces the current input session with one requesting a different kind of input
+fn rerequest_input(
+    player: PlayerId,
+    kind: InputKind,
+    emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+) {
+    emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+    emit_action(request_input(player, kind));
 }
 
 #[derive(Clone, Debug)]
 pub struct CombatManager {
     id: Option<ObserverId>,
     current_input_request: Option<ExpectedInput>,
+
+    /// Defending players that have yet to be asked to declare blockers
+    remaining_defenders: Vec<PlayerId>,
+}
+
+impl Default for CombatManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CombatManager {
@@ -33,6 +351,7 @@ impl CombatManager {
         Self {
             id: None,
             current_input_request: None,
+            remaining_defenders: Vec::new(),
         }
     }
 }
@@ -52,72 +371,914 @@ impl BaseObserver<Mtg> for CombatManager {
         game_state: &Mtg,
         sink: &mut dyn ActionSink<Mtg>,
     ) {
-        match &action.payload {
-            ActionPayload::DomainAction(da) => {
-                if let Some(da) = da.as_t::<AdvanceStep>() {
-                    if let Step::Combat(CombatStep::DeclareAttackers) = da.new_step {
-                        if let SubStep::InProgress = da.new_substep {
-                            // This is the beginning of the declare attackers step
+        if let ActionPayload::DomainAction(da) = &action.payload {
+            if let Some(da) = da.as_t::<AdvanceStep>() {
+                match (da.new_step, da.new_substep) {
+                    (Step::Combat(CombatStep::DeclareAttackers), SubStep::InProgress) => {
+                        // This is the beginning of the declare attackers step. If the active
+                        // player has left the game (800.4i), nothing attacks.
+                        let active_player = game_state.step.active_player;
+                        if !game_state.turn_order.contains(active_player) {
+                            sink.emit_single(ActionPayload::DomainAction(Box::new(
+                                FinishDeclaringAttackers {},
+                            )
+                                as Box<dyn MtgAction>));
+                            return;
+                        }
+                        self.current_input_request = Some(ExpectedInput::NextAttackerOrFinished);
+                        sink.emit_single(request_input(active_player, InputKind::DeclareAttacker))
+                    }
+                    (Step::Combat(CombatStep::DeclareBlockers), SubStep::InProgress) => {
+                        // 509.1 First, the defending player declares blockers.
+                        self.remaining_defenders = defending_players(game_state);
+                        if !self.remaining_defenders.is_empty() {
+                            let defender = self.remaining_defenders.remove(0);
                             self.current_input_request =
-                                Some(ExpectedInput::NextAttackerOrFinished);
-                            sink.emit_single(ActionPayload::EngineAction(
-                                EngineAction::RequestInput(InputRequest {
-                                    from_player: game_state.step.active_player,
-                                    input_type: format!(
-                                        "{} to declare attackers",
-                                        game_state.step.active_player
-                                    ),
-                                }),
-                            ))
+                                Some(ExpectedInput::NextBlockerOrFinished(defender));
+                            sink.emit_single(request_input(defender, InputKind::DeclareBlocker));
                         }
                     }
+                    (Step::Combat(CombatStep::CombatDamage), SubStep::InProgress) => {
+                        // 510.2 Second, all combat damage that's been assigned is dealt
+                        //     simultaneously. This turn-based action doesn't use the stack.
+                        let dealers = combat_damage_dealers(game_state, sink);
+                        let damage = combat_damage(game_state, &dealers);
+                        if !damage.is_empty() {
+                            sink.emit_composite(
+                                "combat_damage",
+                                damage
+                                    .into_iter()
+                                    .map(ActionPayload::DomainAction)
+                                    .collect(),
+                            );
+                        }
+                    }
+                    (Step::Combat(CombatStep::EndOfCombat), SubStep::Ending) => {
+                        // 506.4 A permanent that's removed from combat stops being an attacking,
+                        //     blocking, blocked, and/or unblocked creature.
+                        sink.emit_single(ActionPayload::DomainAction(
+                            Box::new(EndCombat {}) as Box<dyn MtgAction>
+                        ));
+                    }
+                    _ => (),
                 }
             }
-            _ => (),
         }
     }
 
     fn consume_input(
         &mut self,
         input: &PlayerInput<Mtg>,
-        _game_state: &Mtg,
+        game_state: &Mtg,
         emit_action: &mut dyn FnMut(core::actions::ActionPayload<Mtg>),
-    ) {
+    ) -> Result<(), String> {
         let expected = self
             .current_input_request
             .expect("Received input when not expecting one");
 
+        let input = input
+            .payload
+            .as_domain_input()
+            .expect("Expected a domain input");
+
         match expected {
-            ExpectedInput::NextAttackerOrFinished => {
-                let input = input
-                    .payload
-                    .as_domain_input()
-                    .expect("Expected a domain input");
-
-                match input {
-                    MtgInput::Finished => {
-                        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+            ExpectedInput::NextAttackerOrFinished => match input {
+                MtgInput::Finished => {
+                    if let Some(obj_id) = unmet_attack_requirement(game_state) {
+                        return Err(format!("{:?} must attack if able", obj_id));
                     }
-                    MtgInput::ObjectId(_obj_id) => todo!("Implement declaring attackers"),
+
+                    self.current_input_request = None;
+                    emit_action(ActionPayload::DomainAction(
+                        Box::new(FinishDeclaringAttackers {}) as Box<dyn MtgAction>,
+                    ));
+                    emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+                }
+                MtgInput::ObjectId(obj_id) => {
+                    match game_state.battlefield().get(*obj_id) {
+                        Some(obj) => can_attack(game_state, obj)?,
+                        None => return Err(format!("{:?} is not on the battlefield", obj_id)),
+                    }
+                    if game_state.combat.is_attacking(*obj_id) {
+                        return Err(format!("{:?} is already attacking", obj_id));
+                    }
+
+                    self.current_input_request = Some(ExpectedInput::NextAttackee(*obj_id));
+                    rerequest_input(
+                        game_state.step.active_player,
+                        InputKind::ChooseAttackTarget(*obj_id),
+                        emit_action,
+                    );
+                }
+                _ => panic!("Received bad input"),
+            },
+            ExpectedInput::NextAttackee(attacker) => {
+                let target = match input {
+                    MtgInput::ObjectId(obj_id) => AttackTarget::Planeswalker(*obj_id),
+                    MtgInput::PlayerId(player_id) => AttackTarget::Player(*player_id),
                     _ => panic!("Received bad input"),
+                };
+
+                if !legal_attack_targets(game_state).contains(&target) {
+                    return Err(format!("{:?} can't be attacked", target));
                 }
+
+                emit_action(ActionPayload::DomainAction(Box::new(DeclareAttacker {
+                    attacker,
+                    target,
+                })
+                    as Box<dyn MtgAction>));
+                self.current_input_request = Some(ExpectedInput::NextAttackerOrFinished);
+                rerequest_input(
+                    game_state.step.active_player,
+                    InputKind::DeclareAttacker,
+                    emit_action,
+                );
             }
-            ExpectedInput::NextAttackee => {
-                let input = input
-                    .payload
-                    .as_domain_input()
-                    .expect("Expected a domain input");
-
-                match input {
-                    MtgInput::ObjectId(_obj_id) => {
-                        todo!("Implement declaring object attack target")
+            ExpectedInput::NextBlockerOrFinished(defender) => match input {
+                MtgInput::Finished => {
+                    if let Some(obj_id) = unmet_menace(game_state, defender) {
+                        return Err(format!(
+                            "{:?} has menace, so can't be blocked by just one creature",
+                            obj_id
+                        ));
+                    }
+
+                    emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+                    if self.remaining_defenders.is_empty() {
+                        self.current_input_request = None;
+                    } else {
+                        let next_defender = self.remaining_defenders.remove(0);
+                        self.current_input_request =
+                            Some(ExpectedInput::NextBlockerOrFinished(next_defender));
+                        emit_action(request_input(next_defender, InputKind::DeclareBlocker));
+                    }
+                }
+                MtgInput::ObjectId(obj_id) => {
+                    // 509.1a The defending player chooses which creatures they control, if any,
+                    //     will block. The chosen creatures must be untapped. [...]
+                    let valid = game_state.battlefield().get(*obj_id).is_some_and(|o| {
+                        o.controller == defender && !o.tapped && o.has_type(CardType::Creature)
+                    });
+                    if !valid {
+                        return Err(format!(
+                            "{:?} is not an untapped creature controlled by {}",
+                            obj_id, defender
+                        ));
                     }
-                    MtgInput::PlayerId(_player_id) => {
-                        todo!("Implement declaring player attack target")
+                    if game_state.combat.is_blocking(*obj_id) {
+                        // Nominating a blocker again withdraws its block, so that a declaration
+                        // that can't be finished (eg a lone blocker of a creature with menace)
+                        // can be corrected
+                        emit_action(ActionPayload::DomainAction(Box::new(UndeclareBlocker {
+                            blocker: *obj_id,
+                        })
+                            as Box<dyn MtgAction>));
+                        rerequest_input(defender, InputKind::DeclareBlocker, emit_action);
+                        return Ok(());
                     }
+
+                    let battlefield = game_state.battlefield();
+                    let blocker = battlefield.get(*obj_id).unwrap();
+                    let can_block_any = game_state.combat.attackers.iter().any(|a| {
+                        is_attacking_player(game_state, a.target, defender)
+                            && battlefield
+                                .get(a.object)
+                                .is_some_and(|attacker| can_block(game_state, blocker, attacker))
+                    });
+                    if !can_block_any {
+                        return Err(format!("{:?} can't block any of the attackers", obj_id));
+                    }
+
+                    self.current_input_request =
+                        Some(ExpectedInput::NextBlockee(defender, *obj_id));
+                    rerequest_input(
+                        defender,
+                        InputKind::ChooseBlockedAttacker(*obj_id),
+                        emit_action,
+                    );
+                }
+                _ => panic!("Received bad input"),
+            },
+            ExpectedInput::NextBlockee(defender, blocker) => {
+                let attacker = match input {
+                    MtgInput::ObjectId(obj_id) => *obj_id,
                     _ => panic!("Received bad input"),
+                };
+
+                // 509.1a [...] For each of the chosen creatures, the defending player chooses one
+                //     creature for it to block that's attacking that player or a planeswalker they
+                //     control.
+                let attacking_defender = game_state.combat.attackers.iter().any(|a| {
+                    a.object == attacker && is_attacking_player(game_state, a.target, defender)
+                });
+                if !attacking_defender {
+                    return Err(format!(
+                        "{:?} is not attacking {} or a planeswalker they control",
+                        attacker, defender
+                    ));
+                }
+
+                let battlefield = game_state.battlefield();
+                let legal = match (battlefield.get(blocker), battlefield.get(attacker)) {
+                    (Some(b), Some(a)) => can_block(game_state, b, a),
+                    _ => false,
+                };
+                if !legal {
+                    return Err(format!("{:?} can't block {:?}", blocker, attacker));
                 }
+
+                emit_action(ActionPayload::DomainAction(Box::new(DeclareBlocker {
+                    blocker,
+                    attacker,
+                })
+                    as Box<dyn MtgAction>));
+                self.current_input_request = Some(ExpectedInput::NextBlockerOrFinished(defender));
+                rerequest_input(defender, InputKind::DeclareBlocker, emit_action);
             }
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::game::{Game, InputError};
+
+    use crate::{
+        action::{BaseMtgAction, Destroy},
+        card::{CardDefinition, CardType, CardTypeLine, KeywordAbility},
+        combat::{AttackTarget, Attacker, Blocker},
+        game::{Mtg, MtgGameBuilder},
+        player_inputs::{InputKind, MtgInput, PriorityInput},
+        steps::{CombatStep, Step, SubStep},
+        test_utils::{create_card, create_object, input, input_kind, pass_priority, try_input},
+        zone::AbstractZoneLocation,
+        ObjectId, PlayerId,
+    };
+
+    fn start_of_combat() -> Game<Mtg> {
+        MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step(
+                "alice",
+                Step::Combat(CombatStep::StartOfCombat),
+                SubStep::InProgress,
+            )
+            .with_initial_priority("alice")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_declare_attacker() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let creature = create_creature(&mut game, alice, 2, 2, vec![]);
+
+        game.tick_until_player_input();
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::DeclareAttackers)
+        );
+        assert_eq!(game.expecting_input_from(), Some(alice));
+        assert_eq!(input_kind(&game), Some(InputKind::DeclareAttacker));
+
+        input(&mut game, alice, MtgInput::ObjectId(creature));
+        assert_eq!(
+            input_kind(&game),
+            Some(InputKind::ChooseAttackTarget(creature))
+        );
+        input(&mut game, alice, MtgInput::PlayerId(bob));
+        assert_eq!(input_kind(&game), Some(InputKind::DeclareAttacker));
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+
+        assert_eq!(
+            game.game_state.combat.attackers,
+            vec![Attacker {
+                object: creature,
+                target: AttackTarget::Player(bob),
+            }]
+        );
+        assert_eq!(
+            game.game_state.battlefield().search(|o| o.tapped),
+            vec![creature]
+        );
+    }
+
+    #[test]
+    fn test_vigilance() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let vigilant = create_creature(&mut game, alice, 2, 2, vec![KeywordAbility::Vigilance]);
+        let other = create_creature(&mut game, alice, 2, 2, vec![]);
+
+        // Only the creature without vigilance taps to attack
+        attack(&mut game, &[vigilant, other]);
+        assert_eq!(game.game_state.combat.attackers.len(), 2);
+        assert_eq!(
+            game.game_state.battlefield().search(|o| o.tapped),
+            vec![other]
+        );
+    }
+
+    #[test]
+    fn test_declare_blocker() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 2, 2, vec![]);
+        let blocker = create_creature(&mut game, bob, 2, 2, vec![]);
+
+        game.tick_until_player_input();
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        input(&mut game, alice, MtgInput::ObjectId(attacker));
+        input(&mut game, alice, MtgInput::PlayerId(bob));
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::DeclareBlockers)
+        );
+        assert_eq!(game.expecting_input_from(), Some(bob));
+
+        // Bob can't pass priority while declaring blockers
+        assert!(matches!(
+            try_input(
+                &mut game,
+                bob,
+                MtgInput::PriorityInput(PriorityInput::PassPriority)
+            ),
+            Err(InputError::Rejected(_))
+        ));
+
+        // Bob can't block with Alice's tapped attacker
+        assert!(matches!(
+            try_input(&mut game, bob, MtgInput::ObjectId(attacker)),
+            Err(InputError::Rejected(_))
+        ));
+
+        input(&mut game, bob, MtgInput::ObjectId(blocker));
+        assert_eq!(
+            input_kind(&game),
+            Some(InputKind::ChooseBlockedAttacker(blocker))
+        );
+        input(&mut game, bob, MtgInput::ObjectId(attacker));
+        input(&mut game, bob, MtgInput::Finished);
+        game.tick_until_player_input();
+
+        assert_eq!(
+            game.game_state.combat.blockers,
+            vec![Blocker {
+                object: blocker,
+                attacker,
+            }]
+        );
+        assert_eq!(game.expecting_input_from(), Some(alice));
+    }
+
+    #[test]
+    fn test_block_only_own_attackers() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_player("carol")
+            .with_initial_step(
+                "alice",
+                Step::Combat(CombatStep::StartOfCombat),
+                SubStep::InProgress,
+            )
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let [alice, bob, carol] =
+            ["alice", "bob", "carol"].map(|name| game.game_state.find_player(name).unwrap());
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let attacking_bob = create_creature(&mut game, alice, 2, 2, vec![]);
+        let attacking_carol = create_creature(&mut game, alice, 2, 2, vec![]);
+        let blocker = create_creature(&mut game, bob, 2, 2, vec![]);
+        let artifact = create_object(&mut game, bob, battlefield, AbstractZoneLocation::Undefined);
+
+        game.tick_until_player_input();
+        for player in [alice, bob, carol] {
+            pass_priority(&mut game, player);
+        }
+        input(&mut game, alice, MtgInput::ObjectId(attacking_bob));
+        input(&mut game, alice, MtgInput::PlayerId(bob));
+        input(&mut game, alice, MtgInput::ObjectId(attacking_carol));
+        input(&mut game, alice, MtgInput::PlayerId(carol));
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+        for player in [alice, bob, carol] {
+            pass_priority(&mut game, player);
+        }
+        assert_eq!(game.expecting_input_from(), Some(bob));
+
+        // Only creatures can block
+        assert!(matches!(
+            try_input(&mut game, bob, MtgInput::ObjectId(artifact)),
+            Err(InputError::Rejected(_))
+        ));
+
+        // Bob can't block the creature attacking carol
+        input(&mut game, bob, MtgInput::ObjectId(blocker));
+        assert!(matches!(
+            try_input(&mut game, bob, MtgInput::ObjectId(attacking_carol)),
+            Err(InputError::Rejected(_))
+        ));
+        input(&mut game, bob, MtgInput::ObjectId(attacking_bob));
+        input(&mut game, bob, MtgInput::Finished);
+        assert_eq!(
+            game.game_state.combat.blockers,
+            vec![Blocker {
+                object: blocker,
+                attacker: attacking_bob,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_no_attackers_skips_blockers_and_damage() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+        create_object(&mut game, bob, battlefield, AbstractZoneLocation::Undefined);
+
+        game.tick_until_player_input();
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+        assert!(game.game_state.combat.no_attackers_declared);
+
+        // Players still get priority in the declare attackers step
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::DeclareAttackers)
+        );
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::EndOfCombat)
+        );
+        assert_eq!(game.expecting_input_from(), Some(alice));
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+
+        // The flag doesn't outlive the combat phase
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(game.game_state.step.step, Step::PostCombatMain);
+        assert!(!game.game_state.combat.no_attackers_declared);
+    }
+
+    /// Puts a creature with the given power, toughness and keywords onto the battlefield
+    fn create_creature(
+        game: &mut Game<Mtg>,
+        controller: PlayerId,
+        power: i32,
+        toughness: i32,
+        keywords: Vec<KeywordAbility>,
+    ) -> ObjectId {
+        let battlefield = game.game_state.shared_zones.battlefield;
+        create_card(
+            game,
+            controller,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            CardDefinition {
+                type_line: CardTypeLine {
+                    card_types: vec![CardType::Creature],
+                    ..Default::default()
+                },
+                power: Some(power),
+                toughness: Some(toughness),
+                keywords,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Plays through to the declare blockers step with alice attacking bob with the given
+    /// creatures, leaving bob to declare blockers
+    fn attack(game: &mut Game<Mtg>, attackers: &[ObjectId]) {
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        game.tick_until_player_input();
+        pass_priority(game, alice);
+        pass_priority(game, bob);
+        for &attacker in attackers {
+            input(game, alice, MtgInput::ObjectId(attacker));
+            input(game, alice, MtgInput::PlayerId(bob));
+        }
+        input(game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+        pass_priority(game, alice);
+        pass_priority(game, bob);
+        assert_eq!(input_kind(game), Some(InputKind::DeclareBlocker));
+    }
+
+    /// As `attack`, with bob then blocking the attacker with the given blockers
+    fn attack_and_block(game: &mut Game<Mtg>, attacker: ObjectId, blockers: &[ObjectId]) {
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        attack(game, &[attacker]);
+        for &blocker in blockers {
+            input(game, bob, MtgInput::ObjectId(blocker));
+            input(game, bob, MtgInput::ObjectId(attacker));
+        }
+        input(game, bob, MtgInput::Finished);
+        game.tick_until_player_input();
+    }
+
+    /// As `attack_and_block`, then plays on to the first combat damage step
+    fn attack_until_damage(game: &mut Game<Mtg>, attacker: ObjectId, blockers: &[ObjectId]) {
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        attack_and_block(game, attacker, blockers);
+        pass_priority(game, alice);
+        pass_priority(game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::CombatDamage)
+        );
+    }
+
+    #[test]
+    fn test_combat_damage() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 3, 3, vec![]);
+        let first = create_creature(&mut game, bob, 1, 2, vec![]);
+        let second = create_creature(&mut game, bob, 1, 4, vec![]);
+
+        // Lethal damage to the first blocker, and the rest to the second
+        attack_until_damage(&mut game, attacker, &[first, second]);
+        let battlefield = game.game_state.battlefield();
+        assert!(!battlefield.contains(first));
+        assert_eq!(battlefield.get(second).unwrap().damage_marked, 1);
+        assert_eq!(battlefield.get(attacker).unwrap().damage_marked, 2);
+
+        // Without first strike there's only the one combat damage step
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::EndOfCombat)
+        );
+    }
+
+    #[test]
+    fn test_trample() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 5, 5, vec![KeywordAbility::Trample]);
+        let blocker = create_creature(&mut game, bob, 2, 2, vec![]);
+
+        attack_until_damage(&mut game, attacker, &[blocker]);
+        assert!(!game.game_state.battlefield().contains(blocker));
+        assert_eq!(game.game_state.player(bob).life_total, 17);
+    }
+
+    #[test]
+    fn test_trample_over_multiple_blockers() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 6, 6, vec![KeywordAbility::Trample]);
+        let first = create_creature(&mut game, bob, 0, 2, vec![]);
+        let second = create_creature(&mut game, bob, 0, 3, vec![]);
+        game.game_state
+            .battlefield_mut()
+            .get_mut(second)
+            .unwrap()
+            .damage_marked = 1;
+
+        // Damage already marked on the second blocker counts towards lethal damage
+        attack_until_damage(&mut game, attacker, &[first, second]);
+        let battlefield = game.game_state.battlefield();
+        assert!(!battlefield.contains(first));
+        assert!(!battlefield.contains(second));
+        assert_eq!(game.game_state.player(bob).life_total, 18);
+    }
+
+    #[test]
+    fn test_trample_without_blockers_remaining() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 3, 3, vec![KeywordAbility::Trample]);
+        let blocker = create_creature(&mut game, bob, 2, 2, vec![]);
+
+        attack_and_block(&mut game, attacker, &[blocker]);
+        Destroy { object: blocker }.apply(&mut game.game_state);
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+
+        // With nothing left blocking it, all of the damage goes to bob
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::CombatDamage)
+        );
+        assert_eq!(game.game_state.player(bob).life_total, 17);
+    }
+
+    #[test]
+    fn test_flying_blocks() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 2, 2, vec![KeywordAbility::Flying]);
+        let ground = create_creature(&mut game, bob, 2, 2, vec![]);
+        let reach = create_creature(&mut game, bob, 1, 3, vec![KeywordAbility::Reach]);
+
+        // With only a flyer attacking, there's nothing the ground creature can block
+        attack(&mut game, &[attacker]);
+        assert!(matches!(
+            try_input(&mut game, bob, MtgInput::ObjectId(ground)),
+            Err(InputError::Rejected(_))
+        ));
+
+        input(&mut game, bob, MtgInput::ObjectId(reach));
+        input(&mut game, bob, MtgInput::ObjectId(attacker));
+        input(&mut game, bob, MtgInput::Finished);
+        assert_eq!(
+            game.game_state.combat.blockers,
+            vec![Blocker {
+                object: reach,
+                attacker,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_block_flyer_with_ground_creature() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let flyer = create_creature(&mut game, alice, 2, 2, vec![KeywordAbility::Flying]);
+        let other = create_creature(&mut game, alice, 2, 2, vec![]);
+        let blocker = create_creature(&mut game, bob, 2, 2, vec![]);
+
+        attack(&mut game, &[flyer, other]);
+        input(&mut game, bob, MtgInput::ObjectId(blocker));
+        assert!(matches!(
+            try_input(&mut game, bob, MtgInput::ObjectId(flyer)),
+            Err(InputError::Rejected(_))
+        ));
+
+        // The blocker can still block the other attacker instead
+        input(&mut game, bob, MtgInput::ObjectId(other));
+        assert_eq!(
+            game.game_state.combat.blockers,
+            vec![Blocker {
+                object: blocker,
+                attacker: other,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_menace() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 3, 3, vec![KeywordAbility::Menace]);
+        let first = create_creature(&mut game, bob, 2, 2, vec![]);
+        let second = create_creature(&mut game, bob, 2, 2, vec![]);
+
+        // A single blocker isn't enough
+        attack(&mut game, &[attacker]);
+        input(&mut game, bob, MtgInput::ObjectId(first));
+        input(&mut game, bob, MtgInput::ObjectId(attacker));
+        assert!(matches!(
+            try_input(&mut game, bob, MtgInput::Finished),
+            Err(InputError::Rejected(_))
+        ));
+
+        input(&mut game, bob, MtgInput::ObjectId(second));
+        input(&mut game, bob, MtgInput::ObjectId(attacker));
+        input(&mut game, bob, MtgInput::Finished);
+        game.tick_until_player_input();
+        assert_eq!(game.game_state.combat.blockers.len(), 2);
+        assert_eq!(game.expecting_input_from(), Some(alice));
+    }
+
+    #[test]
+    fn test_withdraw_menace_block() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 3, 3, vec![KeywordAbility::Menace]);
+        let blocker = create_creature(&mut game, bob, 2, 2, vec![]);
+
+        // With no second creature to block with, bob has to take back the lone block
+        attack(&mut game, &[attacker]);
+        input(&mut game, bob, MtgInput::ObjectId(blocker));
+        input(&mut game, bob, MtgInput::ObjectId(attacker));
+        assert!(try_input(&mut game, bob, MtgInput::Finished).is_err());
+
+        input(&mut game, bob, MtgInput::ObjectId(blocker));
+        assert!(game.game_state.combat.blockers.is_empty());
+        assert_eq!(input_kind(&game), Some(InputKind::DeclareBlocker));
+        input(&mut game, bob, MtgInput::Finished);
+        game.tick_until_player_input();
+        assert_eq!(game.expecting_input_from(), Some(alice));
+    }
+
+    #[test]
+    fn test_first_strike() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 2, 2, vec![KeywordAbility::FirstStrike]);
+        let blocker = create_creature(&mut game, bob, 2, 2, vec![]);
+
+        // The first strike damage kills the blocker before it can deal damage back
+        attack_until_damage(&mut game, attacker, &[blocker]);
+        assert!(!game.game_state.battlefield().contains(blocker));
+        assert_eq!(game.game_state.combat.first_strikers, Some(vec![attacker]));
+
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::CombatDamage)
+        );
+        assert_eq!(
+            game.game_state
+                .battlefield()
+                .get(attacker)
+                .unwrap()
+                .damage_marked,
+            0
+        );
+
+        // The attacker stays blocked, so deals no damage to bob in either step
+        assert_eq!(game.game_state.player(bob).life_total, 20);
+
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::EndOfCombat)
+        );
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(game.game_state.step.step, Step::PostCombatMain);
+        assert!(game.game_state.step_queue.is_empty());
+    }
+
+    #[test]
+    fn test_double_strike() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let attacker = create_creature(&mut game, alice, 3, 3, vec![KeywordAbility::DoubleStrike]);
+
+        attack_until_damage(&mut game, attacker, &[]);
+        assert_eq!(game.game_state.player(bob).life_total, 17);
+
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::CombatDamage)
+        );
+        assert_eq!(game.game_state.player(bob).life_total, 14);
+    }
+
+    #[test]
+    fn test_summoning_sickness() {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let sick = create_creature(&mut game, alice, 2, 2, vec![]);
+        let hasty = create_creature(&mut game, alice, 2, 2, vec![KeywordAbility::Haste]);
+        let not_creature = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+        for id in [sick, hasty] {
+            game.game_state
+                .battlefield_mut()
+                .get_mut(id)
+                .unwrap()
+                .summoning_sick = true;
+        }
+
+        game.tick_until_player_input();
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        for id in [sick, not_creature] {
+            assert!(matches!(
+                try_input(&mut game, alice, MtgInput::ObjectId(id)),
+                Err(InputError::Rejected(_))
+            ));
+        }
+
+        // Haste lets a creature attack the turn it comes under its controller's control
+        input(&mut game, alice, MtgInput::ObjectId(hasty));
+        input(&mut game, alice, MtgInput::PlayerId(bob));
+        input(&mut game, alice, MtgInput::Finished);
+        assert_eq!(
+            game.game_state.combat.attackers,
+            vec![Attacker {
+                object: hasty,
+                target: AttackTarget::Player(bob),
+            }]
+        );
+    }
+
+    /// A game in alice's declare attackers step, where alice controls a creature that must attack
+    fn must_attack_game() -> (Game<Mtg>, ObjectId) {
+        let mut game = start_of_combat();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let creature = create_creature(&mut game, alice, 2, 2, vec![]);
+        game.game_state
+            .battlefield_mut()
+            .get_mut(creature)
+            .unwrap()
+            .must_attack = true;
+
+        game.tick_until_player_input();
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(input_kind(&game), Some(InputKind::DeclareAttacker));
+
+        (game, creature)
+    }
+
+    #[test]
+    fn test_must_attack() {
+        let (mut game, creature) = must_attack_game();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::Finished),
+            Err(InputError::Rejected(_))
+        ));
+
+        input(&mut game, alice, MtgInput::ObjectId(creature));
+        input(&mut game, alice, MtgInput::PlayerId(bob));
+
+        // A creature attacks at most one player or planeswalker
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::ObjectId(creature)),
+            Err(InputError::Rejected(_))
+        ));
+
+        input(&mut game, alice, MtgInput::Finished);
+        assert_eq!(game.game_state.combat.attackers.len(), 1);
+    }
+
+    #[test]
+    fn test_must_attack_without_legal_target() {
+        let (mut game, _) = must_attack_game();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        game.game_state
+            .players
+            .get_mut(&bob)
+            .unwrap()
+            .can_be_attacked = false;
+
+        // With nothing to attack, the requirement to attack is ignored
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+
+        assert!(game.game_state.combat.attackers.is_empty());
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::DeclareAttackers)
+        );
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
     }
 }
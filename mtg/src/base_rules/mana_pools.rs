@@ -0,0 +1,95 @@
+//! Observer that empties mana pools as steps and phases end
+//!
+//! See section 500.4 of the comprehensive rules
+
+use core::{
+    actions::{Action, ActionPayload},
+    ActionSink, BaseObserver,
+};
+
+use crate::{
+    action::{AdvanceStep, ClearManaPool, MtgAction, MtgActionDowncast},
+    game::Mtg,
+    steps::SubStep,
+};
+
+/// Empties every player's mana pool as each step ends
+///
+/// 500.4 When a step or phase ends, any unused mana left in a player's mana pool empties. This
+///     game action doesn't use the stack.
+///
+/// Every phase ends along with its last step, so watching for steps ending covers both.
+#[derive(Clone, Debug)]
+pub struct ManaPoolEmptying {}
+
+impl BaseObserver<Mtg> for ManaPoolEmptying {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let is_step_ending = match &action.payload {
+            ActionPayload::DomainAction(da) => da
+                .as_t::<AdvanceStep>()
+                .is_some_and(|a| a.new_substep == SubStep::Ending),
+            _ => false,
+        };
+        if !is_step_ending {
+            return;
+        }
+
+        for player in game_state.turn_order.players() {
+            if !game_state.player(player).mana_pool.is_empty() {
+                sink.emit_single(ActionPayload::DomainAction(
+                    Box::new(ClearManaPool { player }) as Box<dyn MtgAction>,
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        action::{AddMana, BaseMtgAction},
+        game::MtgGameBuilder,
+        mana::Color,
+        steps::{Step, SubStep},
+        test_utils::pass_priority,
+    };
+
+    #[test]
+    fn test_mana_empties_at_end_of_step() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        game.tick_until_player_input();
+
+        for (player, color) in [(alice, Some(Color::Green)), (bob, None)] {
+            AddMana {
+                player,
+                color,
+                amount: 2,
+                source: None,
+            }
+            .apply(&mut game.game_state);
+        }
+
+        // Mana stays in the pool while the step carries on
+        pass_priority(&mut game, alice);
+        assert_eq!(game.game_state.step.step, Step::PreCombatMain);
+        assert_eq!(game.game_state.player(alice).mana_pool.mana.len(), 2);
+
+        pass_priority(&mut game, bob);
+        assert_ne!(game.game_state.step.step, Step::PreCombatMain);
+        assert!(game.game_state.player(alice).mana_pool.is_empty());
+        assert!(game.game_state.player(bob).mana_pool.is_empty());
+    }
+}
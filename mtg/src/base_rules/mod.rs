@@ -1,16 +1,39 @@
 use core::game::Game;
 
 pub mod combat;
+pub mod mana_pools;
+pub mod mulligan;
+pub mod planechase;
 pub mod progression;
+pub mod starting_player;
 pub mod state_actions;
+pub mod suspend;
+pub mod triggers;
+pub mod turn_actions;
 
 use crate::game::Mtg;
 use combat::CombatManager;
+use mana_pools::ManaPoolEmptying;
+use mulligan::Mulligans;
+use planechase::Planeswalking;
 use progression::StepsAndPriority;
+use starting_player::StartingPlayerChoice;
 use state_actions::StateBasedActions;
+use suspend::SuspendedCards;
+use triggers::{DelayedTriggers, LastKnownInformationExpiry, TriggeredAbilities};
+use turn_actions::TurnBasedActions;
 
 pub fn attach(game: &mut Game<Mtg>) {
     game.attach_observer(Box::new(StateBasedActions {}));
+    game.attach_observer(Box::new(TriggeredAbilities::new()));
+    game.attach_observer(Box::new(DelayedTriggers {}));
+    game.attach_observer(Box::new(LastKnownInformationExpiry {}));
     game.attach_observer(Box::new(StepsAndPriority::new()));
     game.attach_observer(Box::new(CombatManager::new()));
+    game.attach_observer(Box::new(TurnBasedActions::new()));
+    game.attach_observer(Box::new(ManaPoolEmptying {}));
+    game.attach_observer(Box::new(SuspendedCards {}));
+    game.attach_observer(Box::new(Planeswalking {}));
+    game.attach_observer(Box::new(StartingPlayerChoice {}));
+    game.attach_observer(Box::new(Mulligans::new()));
 }
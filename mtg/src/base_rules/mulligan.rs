@@ -0,0 +1,299 @@
+//! Observer that draws each player's opening hand and implements the London mulligan
+//!
+//! See section 103.5 of the comprehensive rules
+
+use std::collections::VecDeque;
+
+use core::{
+    actions::{Action, ActionPayload, EngineAction, InputRequest},
+    ids::PlayerId,
+    ActionSink, BaseObserver, PlayerInput,
+};
+
+use crate::{
+    action::{
+        AdvanceStep, ChangeObjectZone, DrawCard, MtgAction, MtgActionDowncast, ShuffleLibrary,
+        TakeMulligan, STARTING_HAND_SIZE,
+    },
+    game::Mtg,
+    player_inputs::{InputKind, MtgInput, MulliganInput},
+    steps::{StartingStep, Step, SubStep},
+    zone::{AbstractZoneLocation, ZoneLocation},
+    ConcreteObject, ObjectReference,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct Mulligans {
+    /// Players yet to declare whether they will take a mulligan this round, in turn order
+    undecided: VecDeque<PlayerId>,
+
+    /// Players who have declared that they will take a mulligan this round
+    mulliganing: Vec<PlayerId>,
+
+    /// A player who has kept their hand, and the number of cards they have left to put on the
+    /// bottom of their library
+    bottoming: Option<(PlayerId, usize)>,
+}
+
+impl Mulligans {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ends the current input session and asks for the next decision, if there is one
+    ///
+    /// 103.5 Once each player has made a declaration, all players who decided to take mulligans do
+    ///     so at the same time. [...] This process is then repeated until no player takes a
+    ///     mulligan.
+    fn request_next(&mut self, emit_action: &mut dyn FnMut(ActionPayload<Mtg>)) {
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+
+        if self.undecided.is_empty() && self.bottoming.is_none() {
+            for player in self.mulliganing.drain(..) {
+                emit_action(ActionPayload::DomainAction(
+                    Box::new(TakeMulligan { player }) as Box<dyn MtgAction>,
+                ));
+                self.undecided.push_back(player);
+            }
+        }
+
+        let request = match (self.bottoming, self.undecided.front()) {
+            (Some((player, _)), _) => InputRequest {
+                from_player: player,
+                kind: InputKind::BottomCard,
+            },
+            (None, Some(&player)) => InputRequest {
+                from_player: player,
+                kind: InputKind::Mulligan,
+            },
+            (None, None) => return,
+        };
+        emit_action(ActionPayload::EngineAction(EngineAction::RequestInput(
+            request,
+        )));
+    }
+}
+
+impl BaseObserver<Mtg> for Mulligans {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let is_hand_draw_starting = match &action.payload {
+            ActionPayload::DomainAction(da) => da.as_t::<AdvanceStep>().is_some_and(|a| {
+                a.new_step == Step::Starting(StartingStep::InitialHandDraw)
+                    && a.new_substep == SubStep::InProgress
+            }),
+            _ => false,
+        };
+        if !is_hand_draw_starting {
+            return;
+        }
+
+        // 103.3 Each player shuffles their deck so that the cards are in a random order.
+        // 103.4 Each player draws a number of cards equal to their starting hand size, which is
+        //     normally seven.
+        for player in game_state.turn_order.players() {
+            sink.emit_single(ActionPayload::DomainAction(
+                Box::new(ShuffleLibrary { player }) as Box<dyn MtgAction>,
+            ));
+            for _ in 0..STARTING_HAND_SIZE {
+                sink.emit_single(ActionPayload::DomainAction(
+                    Box::new(DrawCard { player }) as Box<dyn MtgAction>
+                ));
+            }
+        }
+
+        // 103.5 First, the starting player declares whether they will take a mulligan. Then each
+        //     other player in turn order does the same.
+        self.undecided = game_state.turn_order.players().collect();
+        self.mulliganing.clear();
+        self.bottoming = None;
+        sink.emit_single(ActionPayload::EngineAction(EngineAction::RequestInput(
+            InputRequest {
+                from_player: self.undecided[0],
+                kind: InputKind::Mulligan,
+            },
+        )));
+    }
+
+    fn consume_input(
+        &mut self,
+        input: &PlayerInput<Mtg>,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        let input_payload = input
+            .payload
+            .as_domain_input()
+            .expect("Expected a domain input");
+
+        if let Some((player, remaining)) = self.bottoming {
+            assert_eq!(player, input.source);
+            let object = match input_payload {
+                MtgInput::ObjectId(object) => *object,
+                _ => unreachable!("The engine has already checked that this is an object input"),
+            };
+
+            let player = game_state.player(player);
+            if !game_state.zones[&player.hand].contains(object) {
+                return Err(format!("{:?} is not in {}'s hand", object, player.name));
+            }
+
+            emit_action(ActionPayload::DomainAction(Box::new(ChangeObjectZone {
+                obj_ref: ObjectReference::Concrete(ConcreteObject {
+                    zone: player.hand,
+                    object,
+                }),
+                new_loc: ZoneLocation {
+                    zone: player.library,
+                    loc: AbstractZoneLocation::Bottom,
+                },
+            })
+                as Box<dyn MtgAction>));
+
+            self.bottoming = match remaining - 1 {
+                0 => None,
+                n => Some((player.id, n)),
+            };
+        } else {
+            let player = self
+                .undecided
+                .pop_front()
+                .expect("Received input when not expecting one");
+            assert_eq!(player, input.source);
+
+            match input_payload {
+                MtgInput::MulliganInput(MulliganInput::Mulligan) => self.mulliganing.push(player),
+                MtgInput::MulliganInput(MulliganInput::Keep) => {
+                    // Once a player keeps an opening hand, they put a card on the bottom of their
+                    // library for each mulligan they took.
+                    let hand_size = game_state.zones[&game_state.player(player).hand].len();
+                    let to_bottom = game_state.player(player).mulligans_taken as usize;
+                    self.bottoming = match to_bottom.min(hand_size) {
+                        0 => None,
+                        n => Some((player, n)),
+                    };
+                }
+                _ => unreachable!("The engine has already checked that this is a mulligan input"),
+            }
+        }
+
+        self.request_next(emit_action);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::game::{Game, InputError};
+
+    use crate::{
+        card::make_card_universe,
+        game::{Mtg, MtgGameBuilder},
+        player_inputs::{InputKind, MtgInput, MulliganInput},
+        steps::{StartingStep, Step},
+        test_utils::{input, input_kind, try_input},
+        zone::AbstractZoneLocation,
+    };
+
+    fn game_at_hand_draw() -> Game<Mtg> {
+        let deck = vec!["Forest".to_string(); 40];
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_turn_order(&["alice", "bob"])
+            .with_card_universe(make_card_universe())
+            .with_deck("alice", deck.clone())
+            .with_deck("bob", deck)
+            .build()
+            .unwrap();
+        game.tick_until_player_input();
+        game
+    }
+
+    #[test]
+    fn test_everyone_keeps() {
+        let mut game = game_at_hand_draw();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Starting(StartingStep::InitialHandDraw)
+        );
+        assert_eq!(game.expecting_input_from(), Some(alice));
+        assert_eq!(input_kind(&game), Some(InputKind::Mulligan));
+
+        input(
+            &mut game,
+            alice,
+            MtgInput::MulliganInput(MulliganInput::Keep),
+        );
+        assert_eq!(game.expecting_input_from(), Some(bob));
+        input(&mut game, bob, MtgInput::MulliganInput(MulliganInput::Keep));
+        game.tick_until_player_input();
+
+        let state = &game.game_state;
+        assert!(!matches!(state.step.step, Step::Starting(_)));
+        assert_eq!(state.turn_number, 1);
+        for player in [alice, bob] {
+            assert_eq!(state.zones[&state.player(player).hand].len(), 7);
+            assert_eq!(state.zones[&state.player(player).library].len(), 33);
+        }
+    }
+
+    #[test]
+    fn test_mulligan_then_keep() {
+        let mut game = game_at_hand_draw();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let library = game.game_state.player(alice).library;
+        let first_hand = game.game_state.zones[&hand].search(|_| true);
+
+        input(
+            &mut game,
+            alice,
+            MtgInput::MulliganInput(MulliganInput::Mulligan),
+        );
+        input(&mut game, bob, MtgInput::MulliganInput(MulliganInput::Keep));
+
+        // Alice draws a fresh hand of seven, and is asked again
+        assert_eq!(game.game_state.player(alice).mulligans_taken, 1);
+        assert_eq!(game.game_state.zones[&hand].len(), 7);
+        assert_ne!(game.game_state.zones[&hand].search(|_| true), first_hand);
+        assert_eq!(game.expecting_input_from(), Some(alice));
+        assert_eq!(input_kind(&game), Some(InputKind::Mulligan));
+        input(
+            &mut game,
+            alice,
+            MtgInput::MulliganInput(MulliganInput::Keep),
+        );
+
+        // Then puts one card on the bottom, which must come from her own hand
+        assert_eq!(input_kind(&game), Some(InputKind::BottomCard));
+        let bobs_card =
+            game.game_state.zones[&game.game_state.player(bob).hand].search(|_| true)[0];
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::ObjectId(bobs_card)),
+            Err(InputError::Rejected(_))
+        ));
+        let bottomed = game.game_state.zones[&hand].search(|_| true)[0];
+        input(&mut game, alice, MtgInput::ObjectId(bottomed));
+        game.tick_until_player_input();
+
+        let state = &game.game_state;
+        assert!(!matches!(state.step.step, Step::Starting(_)));
+        assert_eq!(state.zones[&hand].len(), 6);
+        assert_eq!(state.zones[&library].len(), 34);
+        assert_eq!(
+            state.zones[&library].resolve_abstract_zone_location(AbstractZoneLocation::Bottom),
+            Some(bottomed)
+        );
+        assert_eq!(state.player(bob).mulligans_taken, 0);
+        assert_eq!(state.zones[&state.player(bob).hand].len(), 7);
+    }
+}
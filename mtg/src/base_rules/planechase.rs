@@ -0,0 +1,45 @@
+//! Observer that makes a player planeswalk when they roll {PW} on the planar die
+//!
+//! See section 901 of the comprehensive rules
+
+use core::{
+    actions::{Action, ActionPayload},
+    ActionSink, BaseObserver,
+};
+
+use crate::{
+    action::{MtgAction, MtgActionDowncast, Planeswalk, RollPlanarDie},
+    game::Mtg,
+    planechase::PlanarDieFace,
+};
+
+/// Makes a player planeswalk when they roll {PW}
+///
+/// 901.9a [...] If the die result is {PW}, the player planeswalks.
+#[derive(Clone, Debug)]
+pub struct Planeswalking {}
+
+impl BaseObserver<Mtg> for Planeswalking {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let da = match &action.payload {
+            ActionPayload::DomainAction(da) => da,
+            _ => return,
+        };
+        if game_state.planar_die_result != Some(PlanarDieFace::Planeswalk) {
+            return;
+        }
+
+        for roll in da.find_all::<RollPlanarDie>() {
+            sink.emit_single(ActionPayload::DomainAction(Box::new(Planeswalk::new(
+                roll.player,
+                game_state,
+            ))
+                as Box<dyn MtgAction>));
+        }
+    }
+}
@@ -7,34 +7,122 @@
 
 use core::{
     actions::{Action, ActionPayload, EngineAction, InputRequest},
-    ids::{ObserverId, PlayerId},
-    ActionSink, BaseObserver, PlayerInput,
+    ids::{AbilityId, ObjectId, ObserverId, PlayerId},
+    ActionSink, BaseObserver, ObserverInterests, PlayerInput,
 };
 
+use super::{state_actions::state_based_actions_pending, triggers::triggers_pending};
 use crate::{
-    action::{AdvanceStep, MtgAction, MtgActionDowncast, PassPriority, SetPriority},
+    action::{
+        ActivateAbility, AdvanceStep, CastSpell, ChangeObjectZone, CompositeAction, CounterSpell,
+        LandPlayed, MtgAction, MtgActionDowncast, PassPriority, RemovePlayer, RollPlanarDie,
+        SetPriority, SuspendCard, TakeEffectAction, Tap, TurnFaceUp,
+    },
+    card::{CardType, HasType},
     game::Mtg,
-    player_inputs::PriorityInput,
+    mana::{pay_mana_cost, CostError, ManaCost},
+    player_inputs::{InputKind, MtgInput, PriorityInput, SpecialAction},
     steps::{BeginningStep, CombatStep, EndStep, GameStep, Step, SubStep},
+    target::Target,
+    zone::{AbstractZoneLocation, ZoneLocation},
+    ConcreteObject, ObjectReference,
 };
 
 /// Does the given step normally involve a round of priority
-#[allow(dead_code)]
 fn step_has_priority(step: &Step) -> bool {
-    match step {
-        Step::Beginning(BeginningStep::Untap) => false,
-        Step::End(EndStep::Cleanup) => false,
-        _ => true,
+    !matches!(
+        step,
+        Step::Beginning(BeginningStep::Untap) | Step::End(EndStep::Cleanup)
+    )
+}
+
+/// Every step of a turn under the default ordering, and whether players normally receive priority
+/// during it
+///
+/// Begins with the untap step. After the last step (cleanup) the next player in turn order
+/// becomes the active player and the sequence starts again.
+pub fn turn_structure() -> Vec<(Step, bool)> {
+    let first = Step::Beginning(BeginningStep::Untap);
+    let mut structure = Vec::new();
+    let mut step = first;
+    loop {
+        structure.push((step, step_has_priority(&step)));
+        step = step.default_next();
+        if step == first {
+            return structure;
+        }
+    }
+}
+
+/// Is the game about to give some player priority, or ask the player holding priority for input
+///
+/// True whenever the game quiesces part way through a step, as opposed to while it is ending.
+pub(crate) fn player_would_receive_priority(game_state: &Mtg) -> bool {
+    game_state.priority.is_some() || game_state.step.substep.is_in_progress()
+}
+
+/// The player who acts in place of the given active player, which is them unless they have left
+/// the game
+///
+/// 800.4i If a player leaves the game during their turn, that turn continues to its completion
+///     without an active player. If the active player would receive priority, instead the next
+///     player in turn order receives priority, or the top object on the stack resolves, or the
+///     phase or step ends, whichever is appropriate.
+pub(crate) fn acting_for(game_state: &Mtg, active_player: PlayerId) -> PlayerId {
+    if game_state.turn_order.contains(active_player) {
+        active_player
+    } else {
+        game_state.turn_order.next(active_player)
+    }
+}
+
+/// Replaces the current input session with one requesting a different kind of input
+fn rerequest_input(
+    player: PlayerId,
+    kind: InputKind,
+    emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+) {
+    emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+    emit_action(ActionPayload::EngineAction(EngineAction::RequestInput(
+        InputRequest {
+            from_player: player,
+            kind,
+        },
+    )));
+}
+
+/// The player who takes the turn after the current one
+///
+/// 500.7 If multiple extra turns are created, the most recently created turn will be taken first.
+/// 500.11 [...] If an effect causes a player to skip a turn, they simply don't take it, and the
+///     player who would take the following turn does so instead.
+fn next_turn_player(game_state: &Mtg) -> PlayerId {
+    if let Some(&player) = game_state.extra_turns.last() {
+        return player;
+    }
+
+    let previous = game_state.last_regular_turn;
+    let mut skipped = game_state.skipped_turns.clone();
+    let mut player = game_state.turn_order.next(previous);
+    while let Some(index) = skipped.iter().position(|&p| p == player) {
+        skipped.remove(index);
+        player = game_state.turn_order.next(player);
     }
+    player
+}
+
+/// Should the given step be passed over without happening, if it's next to begin this turn
+fn is_skipped(game_state: &Mtg, step: Step) -> bool {
+    let skips_blocks_and_damage = matches!(
+        step,
+        Step::Combat(CombatStep::DeclareBlockers) | Step::Combat(CombatStep::CombatDamage)
+    );
+    game_state.skipped_phases.contains(&step.phase())
+        || (skips_blocks_and_damage && game_state.combat.no_attackers_declared)
 }
 
 /// The next next step under the default ordering, and whether the active player should advance
 fn next_step(game_state: &Mtg) -> GameStep {
-    use BeginningStep::*;
-    use CombatStep::*;
-    use EndStep::*;
-    use Step::*;
-
     // If the current step is in progress, the next thing to do is end it
     if game_state.step.substep.is_in_progress() {
         return GameStep {
@@ -45,36 +133,34 @@ fn next_step(game_state: &Mtg) -> GameStep {
     }
     assert!(game_state.step.substep.is_ending());
 
-    let next_step = match game_state.step.step {
-        Beginning(Untap) => Beginning(Upkeep),
-        Beginning(Upkeep) => Beginning(Draw),
-        Beginning(Draw) => PreCombatMain,
-        PreCombatMain => Combat(StartOfCombat),
-        Combat(StartOfCombat) => Combat(DeclareAttackers),
-        Combat(DeclareAttackers) => Combat(DeclareBlockers),
-        Combat(DeclareBlockers) => Combat(CombatDamage),
-        Combat(CombatDamage) => Combat(EndOfCombat),
-        Combat(EndOfCombat) => PostCombatMain,
-        PostCombatMain => End(EndOfTurn),
-        End(EndOfTurn) => End(Cleanup),
-        End(Cleanup) => Beginning(Untap),
-        Starting(_) => panic!("default_next_step being used on special starting steps"),
-    };
-
-    let next_active_player = if game_state.step.step == End(Cleanup) {
-        game_state
-            .turn_order
-            .get(&game_state.step.active_player)
-            .cloned()
-            .expect("Don't know which player comes after the active player")
-    } else {
-        game_state.step.active_player
-    };
+    // Steps inserted by effects happen before the default ordering resumes. Any steps that are
+    // being skipped are passed over, up until the turn ends.
+    let mut queued = game_state.step_queue.iter().cloned();
+    let mut current = game_state.step.step;
+    loop {
+        let next_step = queued.next().unwrap_or_else(|| current.default_next());
+        let next_active_player = match current {
+            // Nothing carries over to the next turn, so its first step always happens
+            Step::End(EndStep::Cleanup) => next_turn_player(game_state),
+            // The first turn of the game belongs to whoever ended up at the front of the turn
+            // order
+            Step::Starting(_) => game_state
+                .turn_order
+                .players()
+                .next()
+                .expect("Every player has been removed"),
+            _ if is_skipped(game_state, next_step) => {
+                current = next_step;
+                continue;
+            }
+            _ => game_state.step.active_player,
+        };
 
-    GameStep {
-        active_player: next_active_player,
-        step: next_step,
-        substep: SubStep::InProgress,
+        return GameStep {
+            active_player: next_active_player,
+            step: next_step,
+            substep: SubStep::InProgress,
+        };
     }
 }
 
@@ -82,6 +168,316 @@ fn next_step(game_state: &Mtg) -> GameStep {
 enum ExpectedInput {
     /// The given player has priority, and is being asked what they would like to do
     Priority(PlayerId),
+
+    /// The given player has chosen to play a land, and is being asked which one
+    PlayLand(PlayerId),
+
+    /// The given player has chosen to turn a face-down creature face up, and is being asked which
+    /// one
+    FlipCreature(PlayerId),
+
+    /// The given player has chosen to take an effect action, and is being asked which one
+    EffectAction(PlayerId),
+
+    /// The given player has chosen to suspend a card, and is being asked which one
+    SuspendCard(PlayerId),
+
+    /// The given player has chosen to cast a spell, and is being asked which one
+    CastSpell(PlayerId),
+
+    /// The given player is casting the given spell, and is being asked for its next target
+    ChooseTarget(PlayerId, ObjectId),
+
+    /// The given player has chosen to activate an ability, and is being asked which one
+    ActivateAbility(PlayerId),
+}
+
+impl ExpectedInput {
+    /// The player being asked for input
+    fn player(&self) -> PlayerId {
+        match *self {
+            ExpectedInput::Priority(p)
+            | ExpectedInput::PlayLand(p)
+            | ExpectedInput::FlipCreature(p)
+            | ExpectedInput::EffectAction(p)
+            | ExpectedInput::SuspendCard(p)
+            | ExpectedInput::CastSpell(p)
+            | ExpectedInput::ChooseTarget(p, _)
+            | ExpectedInput::ActivateAbility(p) => p,
+        }
+    }
+}
+
+/// Is it a main phase of the given player's turn, with nothing on the stack
+///
+/// This is when sorceries can be cast (307.1) and lands can be played (305.1).
+fn is_sorcery_timing(game_state: &Mtg, player: PlayerId) -> bool {
+    game_state.step.active_player == player
+        && game_state.step.is_main_phase()
+        && game_state.stack().is_empty()
+}
+
+/// Can the given player play a land right now, ignoring which land they would play
+///
+/// 305.1 A player who has priority may play a land card from their hand during a main phase of
+///     their turn when the stack is empty.
+/// 305.2 A player can normally play one land during their turn.
+fn can_play_land(game_state: &Mtg, player: PlayerId) -> Result<(), String> {
+    if !is_sorcery_timing(game_state, player) {
+        return Err(format!(
+            "{} can only play a land during a main phase of their own turn while the stack is \
+             empty",
+            player
+        ));
+    }
+
+    let player = game_state.player(player);
+    if player.lands_played_this_turn >= player.lands_per_turn {
+        return Err(format!(
+            "{} has already played {} land(s) this turn",
+            player.name, player.lands_played_this_turn
+        ));
+    }
+
+    Ok(())
+}
+
+/// The player who exercised their priority by performing the given action (or one of its
+/// components), if any
+fn priority_used_by(action: &dyn MtgAction) -> Option<PlayerId> {
+    if let Some(a) = action.as_t::<CastSpell>() {
+        Some(a.player)
+    } else if let Some(a) = action.as_t::<ActivateAbility>() {
+        Some(a.player)
+    } else if let Some(a) = action.as_t::<LandPlayed>() {
+        Some(a.player)
+    } else if let Some(a) = action.as_t::<TurnFaceUp>() {
+        Some(a.player)
+    } else if let Some(a) = action.as_t::<SuspendCard>() {
+        Some(a.player())
+    } else if let Some(a) = action.as_t::<RollPlanarDie>() {
+        Some(a.player)
+    } else if let Some(a) = action.as_t::<TakeEffectAction>() {
+        Some(a.player())
+    } else {
+        action
+            .components()
+            .iter()
+            .find_map(|component| priority_used_by(&**component))
+    }
+}
+
+/// Can the given player turn the given face-down permanent face up right now
+///
+/// 702.37e Any time you have priority, you may turn a face-down permanent you control with a morph
+///     ability face up.
+fn can_turn_face_up(game_state: &Mtg, player: PlayerId, object: ObjectId) -> Result<(), String> {
+    let obj = match game_state.battlefield().get(object) {
+        Some(obj) if obj.face_down => obj,
+        _ => return Err(format!("{:?} is not a face-down permanent", object)),
+    };
+    if obj.controller != player {
+        return Err(format!("{} doesn't control {:?}", player, object));
+    }
+    let cost = match &obj.definition.morph_cost {
+        Some(cost) => cost,
+        None => return Err(format!("{:?} doesn't have morph", object)),
+    };
+
+    can_pay_mana_cost(game_state, player, cost, object)
+}
+
+/// Can the given player pay the given cost for the given object from their mana pool right now
+///
+/// Only costs that can be paid without choosing how to pay for hybrid or Phyrexian symbols are
+/// supported so far.
+fn can_pay_mana_cost(
+    game_state: &Mtg,
+    player: PlayerId,
+    cost: &ManaCost,
+    object: ObjectId,
+) -> Result<(), String> {
+    let player = game_state.player(player);
+    let mut pool = player.mana_pool.clone();
+    match pay_mana_cost(player.life_total, cost, &mut pool, &[]) {
+        Ok(_) => Ok(()),
+        Err(CostError::ChoiceNeeded(_)) | Err(CostError::InvalidChoice(_)) => Err(format!(
+            "Choosing how to pay the cost of {:?} isn't supported yet",
+            object
+        )),
+        Err(e) => Err(format!(
+            "{} can't pay the cost of {:?}: {:?}",
+            player.name, object, e
+        )),
+    }
+}
+
+/// Can the given player suspend the given card from their hand right now
+///
+/// 702.62a [...] If you could begin to cast this card by putting it onto the stack from your
+///     hand, you may pay [cost] and exile it with N time counters on it.
+fn can_suspend(game_state: &Mtg, player: PlayerId, object: ObjectId) -> Result<(), String> {
+    can_cast_spell(game_state, player, object)?;
+
+    let hand = game_state.player(player).hand;
+    match &game_state.zones[&hand]
+        .get(object)
+        .unwrap()
+        .definition
+        .suspend
+    {
+        Some(suspend) => can_pay_mana_cost(game_state, player, &suspend.cost, object),
+        None => Err(format!("{:?} doesn't have suspend", object)),
+    }
+}
+
+/// Can the given player take the given effect action right now
+///
+/// 116.2c Some effects allow a player to take an action at a later time [...]
+fn can_take_effect_action(
+    game_state: &Mtg,
+    player: PlayerId,
+    action: AbilityId,
+) -> Result<(), String> {
+    let effect_action = game_state
+        .effect_actions
+        .iter()
+        .find(|a| a.id == action && a.player == player);
+    match effect_action {
+        Some(effect_action) => {
+            let cost = &effect_action.cost;
+            let player = game_state.player(player);
+            let mut pool = player.mana_pool.clone();
+            pay_mana_cost(player.life_total, cost, &mut pool, &[])
+                .map(|_| ())
+                .map_err(|e| format!("{} can't pay for {:?}: {:?}", player.name, action, e))
+        }
+        None => Err(format!(
+            "{:?} is not an effect action available to {}",
+            action, player
+        )),
+    }
+}
+
+/// Can the given player roll the planar die right now
+///
+/// 901.9 Any time a player has priority during their own main phase and the stack is empty, that
+///     player may roll the planar die.
+fn can_roll_planar_die(game_state: &Mtg, player: PlayerId) -> Result<(), String> {
+    if game_state.face_up_planes().next().is_none() {
+        return Err("The planar die is only rolled in a Planechase game".to_string());
+    }
+    if !is_sorcery_timing(game_state, player) {
+        return Err(format!(
+            "{} can only roll the planar die during a main phase of their own turn while the \
+             stack is empty",
+            player
+        ));
+    }
+
+    let cost = RollPlanarDie::cost(game_state, player);
+    let player = game_state.player(player);
+    let mut pool = player.mana_pool.clone();
+    pay_mana_cost(player.life_total, &cost, &mut pool, &[])
+        .map(|_| ())
+        .map_err(|e| {
+            format!(
+                "{} can't pay to roll the planar die again: {:?}",
+                player.name, e
+            )
+        })
+}
+
+/// Can the given player cast the given object from their hand right now
+///
+/// Only instants and sorceries are supported so far.
+/// 117.1a A player may cast an instant spell any time they have priority. A player may cast a
+///     noninstant spell during their main phase any time they have priority and the stack is
+///     empty.
+fn can_cast_spell(game_state: &Mtg, player: PlayerId, object: ObjectId) -> Result<(), String> {
+    let hand = game_state.player(player).hand;
+    let definition = match game_state.zones[&hand].get(object) {
+        Some(obj) => &obj.definition,
+        None => return Err(format!("{:?} is not in {}'s hand", object, player)),
+    };
+
+    if definition.has_type(CardType::Sorcery) {
+        if !is_sorcery_timing(game_state, player) {
+            return Err(format!(
+                "{} can only cast a sorcery during a main phase of their own turn while the stack \
+                 is empty",
+                player
+            ));
+        }
+    } else if !definition.has_type(CardType::Instant) {
+        return Err(format!("Casting {:?} isn't supported yet", definition.name));
+    }
+
+    // Targets are chosen as part of casting the spell (601.2c), so it can't be cast while there's
+    // nothing it could target
+    match definition
+        .targets
+        .iter()
+        .find(|spec| !spec.has_legal_target(game_state))
+    {
+        Some(spec) => Err(format!(
+            "{:?} can't be cast as there's no legal target for {:?}",
+            definition.name, spec
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The actions that activate the given ability and pay its costs, if the given player can do so
+/// right now
+///
+/// Only tap costs are supported so far.
+/// 602.2 To activate an ability is to put it onto the stack and pay its costs, so that it will
+///     eventually resolve and have its effect. Only an object's controller (or its owner, if it
+///     doesn't have a controller) can activate its activated ability unless the object
+///     specifically says otherwise.
+fn activate_ability(
+    game_state: &Mtg,
+    player: PlayerId,
+    ability: AbilityId,
+) -> Result<Box<dyn MtgAction>, String> {
+    let (source, ability) = match game_state.find_ability(ability) {
+        Some(found) => found,
+        None => return Err(format!("{:?} is not an ability of any permanent", ability)),
+    };
+    if source.controller != player {
+        return Err(format!("{} doesn't control {:?}", player, source.id));
+    }
+    if !ability.cost.mana.components.is_empty() {
+        return Err(format!(
+            "Paying the mana cost of {:?} isn't supported yet",
+            ability.id
+        ));
+    }
+
+    let mut components: Vec<Box<dyn MtgAction>> = Vec::new();
+    if ability.cost.tap {
+        if source.tapped {
+            return Err(format!("{:?} is already tapped", source.id));
+        }
+        components.push(Box::new(Tap { object: source.id }));
+    }
+
+    // 605.3b An activated mana ability doesn't go on the stack [...] it resolves immediately
+    //     after it is activated.
+    if ability.is_mana_ability {
+        components.push(ability.effect.clone());
+    } else {
+        components.push(Box::new(ActivateAbility {
+            player,
+            ability: ability.id,
+        }));
+    }
+
+    Ok(Box::new(CompositeAction {
+        tag: "activate_ability",
+        components,
+    }))
 }
 
 #[derive(Clone, Debug)]
@@ -102,6 +498,19 @@ pub struct StepsAndPriority {
     /// Actions to be emitted through the normal queuing mechanism after the EndInput action is
     /// observed.
     post_input_actions: Vec<ActionPayload<Mtg>>,
+
+    /// The targets chosen so far for the spell currently being cast
+    chosen_targets: Vec<Target>,
+
+    /// Whether state-based actions were performed or triggered abilities waiting during the
+    /// current cleanup step, so that players receive priority and another cleanup step follows
+    cleanup_interrupted: bool,
+}
+
+impl Default for StepsAndPriority {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StepsAndPriority {
@@ -112,6 +521,8 @@ impl StepsAndPriority {
             next_priority: None,
             current_input_request: None,
             post_input_actions: Vec::new(),
+            chosen_targets: Vec::new(),
+            cleanup_interrupted: false,
         }
     }
 
@@ -119,9 +530,9 @@ impl StepsAndPriority {
         &mut self,
         source: PlayerId,
         input: &PriorityInput,
-        _game_state: &Mtg,
+        game_state: &Mtg,
         emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
-    ) {
+    ) -> Result<(), String> {
         match input {
             PriorityInput::PassPriority => {
                 self.post_input_actions
@@ -130,10 +541,329 @@ impl StepsAndPriority {
                     ));
                 emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
             }
-            PriorityInput::CastSpell => todo!(),
-            PriorityInput::ActivateAbility => todo!(),
+            PriorityInput::SpecialAction(SpecialAction::PlayLand) => {
+                can_play_land(game_state, source)?;
+
+                self.current_input_request = Some(ExpectedInput::PlayLand(source));
+                rerequest_input(source, InputKind::PlayLand, emit_action);
+            }
+            PriorityInput::SpecialAction(SpecialAction::FlipCreature) => {
+                self.current_input_request = Some(ExpectedInput::FlipCreature(source));
+                rerequest_input(source, InputKind::FlipCreature, emit_action);
+            }
+            PriorityInput::SpecialAction(SpecialAction::EffectAction) => {
+                if !game_state.effect_actions.iter().any(|a| a.player == source) {
+                    return Err(format!(
+                        "There are no effect actions available to {}",
+                        source
+                    ));
+                }
+
+                self.current_input_request = Some(ExpectedInput::EffectAction(source));
+                rerequest_input(source, InputKind::EffectAction, emit_action);
+            }
+            PriorityInput::SpecialAction(SpecialAction::RollPlanarDie) => {
+                can_roll_planar_die(game_state, source)?;
+
+                // 116.2g Rolling the planar die is a special action, so it doesn't use the stack
+                //     and the player receives priority again afterwards (117.3c).
+                self.post_input_actions
+                    .push(ActionPayload::DomainAction(
+                        Box::new(RollPlanarDie { player: source }) as Box<dyn MtgAction>,
+                    ));
+                emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+            }
+            PriorityInput::SpecialAction(SpecialAction::SuspendCard) => {
+                self.current_input_request = Some(ExpectedInput::SuspendCard(source));
+                rerequest_input(source, InputKind::SuspendCard, emit_action);
+            }
+            PriorityInput::CastSpell => {
+                self.current_input_request = Some(ExpectedInput::CastSpell(source));
+                rerequest_input(source, InputKind::CastSpell, emit_action);
+            }
+            PriorityInput::ActivateAbility => {
+                self.current_input_request = Some(ExpectedInput::ActivateAbility(source));
+                rerequest_input(source, InputKind::ActivateAbility, emit_action);
+            }
             PriorityInput::SpecialAction(_) => todo!(),
         }
+
+        Ok(())
+    }
+
+    fn handle_cast_spell_input(
+        &mut self,
+        source: PlayerId,
+        input: &MtgInput,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        match input {
+            MtgInput::Finished => (),
+            MtgInput::ObjectId(spell) => {
+                can_cast_spell(game_state, source, *spell)?;
+                self.chosen_targets.clear();
+                return self.request_target_or_cast(source, *spell, game_state, emit_action);
+            }
+            _ => unreachable!("The engine has already checked that this is a spell input"),
+        }
+
+        self.current_input_request = None;
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
+    }
+
+    fn handle_choose_target_input(
+        &mut self,
+        source: PlayerId,
+        spell: ObjectId,
+        input: &MtgInput,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        let target = match input {
+            MtgInput::ObjectId(object) => Target::Object(*object),
+            MtgInput::PlayerId(player) => Target::Player(*player),
+            MtgInput::Finished => {
+                // 723.1 If a player [...] starts to take an action but can't legally complete it,
+                //     the entire action is reversed and any payments already made are canceled.
+                //     [...] If the action was casting a spell, the spell returns to the zone it
+                //     came from.
+                // The spell hasn't left its owner's hand yet, so forgetting the targets chosen so
+                // far is enough to undo the cast. The player keeps priority.
+                self.chosen_targets.clear();
+                self.current_input_request = Some(ExpectedInput::Priority(source));
+                rerequest_input(source, InputKind::Priority, emit_action);
+                return Ok(());
+            }
+            _ => unreachable!("The engine has already checked that this is a target input"),
+        };
+
+        let hand = game_state.player(source).hand;
+        let definition = &game_state.zones[&hand]
+            .get(spell)
+            .expect("The spell being cast has left its owner's hand")
+            .definition;
+        let spec = &definition.targets[self.chosen_targets.len()];
+        if !spec.is_legal(game_state, target) {
+            return Err(format!("{:?} is not a legal target for {:?}", target, spec));
+        }
+
+        self.chosen_targets.push(target);
+        self.request_target_or_cast(source, spell, game_state, emit_action)
+    }
+
+    /// Ask for the spell's next target, or cast it if every one of its targets has been chosen
+    ///
+    /// 601.2c The player announces their choice of targets. [...]
+    ///
+    /// Targets are chosen before the spell moves to the stack rather than after, which makes no
+    /// difference while only permanents and players can be targeted.
+    fn request_target_or_cast(
+        &mut self,
+        source: PlayerId,
+        spell: ObjectId,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        let hand = game_state.player(source).hand;
+        let required = game_state.zones[&hand]
+            .get(spell)
+            .expect("The spell being cast has left its owner's hand")
+            .definition
+            .targets
+            .len();
+        if self.chosen_targets.len() < required {
+            self.current_input_request = Some(ExpectedInput::ChooseTarget(source, spell));
+            rerequest_input(source, InputKind::ChooseTarget, emit_action);
+            return Ok(());
+        }
+
+        self.post_input_actions
+            .push(ActionPayload::DomainAction(Box::new(CastSpell {
+                player: source,
+                object: spell,
+                zone: hand,
+                // Choosing a value for X isn't supported yet
+                x: None,
+                targets: std::mem::take(&mut self.chosen_targets),
+            })
+                as Box<dyn MtgAction>));
+        self.current_input_request = None;
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
+    }
+
+    fn handle_activate_ability_input(
+        &mut self,
+        source: PlayerId,
+        input: &MtgInput,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        match input {
+            MtgInput::Finished => (),
+            MtgInput::AbilityId(ability) => {
+                let activation = activate_ability(game_state, source, *ability)?;
+                let is_mana_ability = game_state
+                    .find_ability(*ability)
+                    .is_some_and(|(_, ability)| ability.is_mana_ability);
+
+                if is_mana_ability {
+                    // 605.3a A player may activate an activated mana ability whenever they have
+                    //     priority [...]
+                    // 605.3b [...] it resolves immediately after it is activated.
+                    // The ability doesn't exercise the player's priority, so they're asked again
+                    // straight away without disturbing the current round of passes.
+                    emit_action(ActionPayload::DomainAction(activation));
+                    self.current_input_request = Some(ExpectedInput::Priority(source));
+                    rerequest_input(source, InputKind::Priority, emit_action);
+                    return Ok(());
+                }
+
+                self.post_input_actions
+                    .push(ActionPayload::DomainAction(activation));
+            }
+            _ => unreachable!("The engine has already checked that this is an ability input"),
+        }
+
+        self.current_input_request = None;
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
+    }
+
+    fn handle_play_land_input(
+        &mut self,
+        source: PlayerId,
+        input: &MtgInput,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        match input {
+            MtgInput::Finished => (),
+            MtgInput::ObjectId(land) => {
+                let hand = game_state.player(source).hand;
+                let is_land_in_hand = game_state.zones[&hand]
+                    .get(*land)
+                    .is_some_and(|obj| obj.definition.has_type(CardType::Land));
+                if !is_land_in_hand {
+                    return Err(format!(
+                        "{:?} is not a land card in {}'s hand",
+                        land, source
+                    ));
+                }
+
+                // 116.2a Playing a land is a special action, so it doesn't use the stack and the
+                //     player receives priority again afterwards (117.3c).
+                let play_land = CompositeAction {
+                    tag: "play_land",
+                    components: vec![
+                        Box::new(ChangeObjectZone {
+                            obj_ref: ObjectReference::Concrete(ConcreteObject {
+                                zone: hand,
+                                object: *land,
+                            }),
+                            new_loc: ZoneLocation {
+                                zone: game_state.shared_zones.battlefield,
+                                loc: AbstractZoneLocation::Undefined,
+                            },
+                        }),
+                        Box::new(LandPlayed { player: source }),
+                    ],
+                };
+                self.post_input_actions.push(ActionPayload::DomainAction(
+                    Box::new(play_land) as Box<dyn MtgAction>
+                ));
+            }
+            _ => unreachable!("The engine has already checked that this is a land input"),
+        }
+
+        self.current_input_request = None;
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
+    }
+
+    fn handle_flip_creature_input(
+        &mut self,
+        source: PlayerId,
+        input: &MtgInput,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        match input {
+            MtgInput::Finished => (),
+            MtgInput::ObjectId(object) => {
+                can_turn_face_up(game_state, source, *object)?;
+
+                // 116.2b Turning a face-down creature face up is a special action, so it doesn't
+                //     use the stack and the player receives priority again afterwards (117.3c).
+                self.post_input_actions
+                    .push(ActionPayload::DomainAction(Box::new(TurnFaceUp {
+                        player: source,
+                        object: *object,
+                    })
+                        as Box<dyn MtgAction>));
+            }
+            _ => unreachable!("The engine has already checked that this is an object input"),
+        }
+
+        self.current_input_request = None;
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
+    }
+
+    fn handle_effect_action_input(
+        &mut self,
+        source: PlayerId,
+        input: &MtgInput,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        match input {
+            MtgInput::Finished => (),
+            MtgInput::AbilityId(action) => {
+                can_take_effect_action(game_state, source, *action)?;
+
+                // 116.2c Taking an effect action is a special action, so it doesn't use the stack
+                //     and the player receives priority again afterwards (117.3c).
+                self.post_input_actions
+                    .push(ActionPayload::DomainAction(Box::new(TakeEffectAction::new(
+                        source, *action, game_state,
+                    ))
+                        as Box<dyn MtgAction>));
+            }
+            _ => unreachable!("The engine has already checked that this is an ability input"),
+        }
+
+        self.current_input_request = None;
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
+    }
+
+    fn handle_suspend_card_input(
+        &mut self,
+        source: PlayerId,
+        input: &MtgInput,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        match input {
+            MtgInput::Finished => (),
+            MtgInput::ObjectId(object) => {
+                can_suspend(game_state, source, *object)?;
+
+                // 116.2f Suspending a card is a special action, so it doesn't use the stack and
+                //     the player receives priority again afterwards (117.3c).
+                self.post_input_actions.push(ActionPayload::DomainAction(
+                    Box::new(SuspendCard::new(source, *object, game_state)) as Box<dyn MtgAction>,
+                ));
+            }
+            _ => unreachable!("The engine has already checked that this is an object input"),
+        }
+
+        self.current_input_request = None;
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
     }
 }
 
@@ -142,6 +872,33 @@ impl BaseObserver<Mtg> for StepsAndPriority {
         self.id = Some(id)
     }
 
+    fn interests(&self) -> ObserverInterests {
+        ObserverInterests {
+            pending: true,
+            ..ObserverInterests::ACTIONS
+        }
+    }
+
+    fn observe_pending(&mut self, action: &Action<Mtg>, game_state: &Mtg) {
+        let removal = match &action.payload {
+            ActionPayload::DomainAction(da) => da.as_t::<RemovePlayer>(),
+            _ => None,
+        };
+        let (removal, holder) = match (removal, game_state.priority.or(self.next_priority)) {
+            (Some(removal), Some(holder)) => (removal, holder),
+            _ => return,
+        };
+
+        // The players who have passed in succession so far are the ones directly before whoever
+        // is holding priority. A pass made by a player who leaves the game no longer counts.
+        let players = game_state.turn_order.len();
+        let passed = (1..=self.passing_counter.min(players - 1))
+            .any(|n| game_state.turn_order.nth_after(holder, players - n) == removal.player);
+        if passed {
+            self.passing_counter -= 1;
+        }
+    }
+
     fn observe_action(
         &mut self,
         action: &Action<Mtg>,
@@ -159,20 +916,63 @@ impl BaseObserver<Mtg> for StepsAndPriority {
                 //   - Attempt to give the appropriate player priority
                 //     - Except during the untap step, and (most) cleanup step(s)
                 //   - Advance to the next step/substep
+                //
+                // State-based actions are always performed and triggered abilities put on the stack
+                // before a player receives priority, so wait for those to calm down first.
+                if state_based_actions_pending(game_state) || triggers_pending(game_state) {
+                    if game_state.step.step == Step::End(EndStep::Cleanup) {
+                        self.cleanup_interrupted = true;
+                    }
+                    return;
+                }
 
                 if let Some(priority_player) = game_state.priority {
                     let input_req = InputRequest {
                         from_player: priority_player,
-                        input_type: format!(
-                            "Requesting priority input. Expecting MtgInput::PriorityInput(_)"
-                        ),
+                        kind: InputKind::Priority,
                     };
                     sink.emit_single(ActionPayload::EngineAction(EngineAction::RequestInput(
                         input_req.clone(),
                     )));
                     self.current_input_request = Some(ExpectedInput::Priority(priority_player));
                 } else {
-                    if game_state.step.substep == SubStep::Ending {
+                    if let (Step::Starting(_), SubStep::InProgress) =
+                        (game_state.step.step, game_state.step.substep)
+                    {
+                        // Nobody receives priority before the first turn begins, so a starting
+                        // step ends as soon as its own business is done
+                        let action = Box::new(AdvanceStep {
+                            new_step: game_state.step.step,
+                            new_substep: SubStep::Ending,
+                            new_active_player: game_state.step.active_player,
+                        }) as Box<dyn MtgAction>;
+                        sink.emit_single(ActionPayload::DomainAction(action));
+                    } else if game_state.step.step == Step::End(EndStep::Cleanup)
+                        && game_state.step.substep == SubStep::InProgress
+                        && !self.cleanup_interrupted
+                    {
+                        // 514.3 Normally, no player receives priority during the cleanup step, so
+                        //     no spells can be cast and no abilities can be activated.
+                        let action = Box::new(AdvanceStep {
+                            new_step: game_state.step.step,
+                            new_substep: SubStep::Ending,
+                            new_active_player: game_state.step.active_player,
+                        }) as Box<dyn MtgAction>;
+                        sink.emit_single(ActionPayload::DomainAction(action));
+                    } else if game_state.step.step == Step::End(EndStep::Cleanup)
+                        && game_state.step.substep == SubStep::Ending
+                        && self.cleanup_interrupted
+                    {
+                        // 514.3a [...] Once the stack is empty and all players pass in succession,
+                        //     another cleanup step begins.
+                        self.cleanup_interrupted = false;
+                        let action = Box::new(AdvanceStep {
+                            new_step: game_state.step.step,
+                            new_substep: SubStep::InProgress,
+                            new_active_player: game_state.step.active_player,
+                        }) as Box<dyn MtgAction>;
+                        sink.emit_single(ActionPayload::DomainAction(action));
+                    } else if game_state.step.substep == SubStep::Ending {
                         // There are no more things happening at the end of the current step, it is
                         // time to progress to the next step
                         let next_step = next_step(game_state);
@@ -184,6 +984,12 @@ impl BaseObserver<Mtg> for StepsAndPriority {
                         sink.emit_single(ActionPayload::DomainAction(action));
                     } else {
                         // There should be a player ready to receive priority
+                        //
+                        // 514.3a At this point, the game checks to see if any state-based actions
+                        //     would be performed and/or any triggered abilities are waiting to be
+                        //     put onto the stack [...] If so, those state-based actions are
+                        //     performed, then those triggered abilities are put on the stack, then
+                        //     the active player gets priority.
                         let set_prio_action = Box::new(SetPriority {
                             new_priority: self
                                 .next_priority
@@ -194,22 +1000,46 @@ impl BaseObserver<Mtg> for StepsAndPriority {
                 }
             }
             ActionPayload::EngineAction(EngineAction::EndInput) if action.source == self_id => {
-                for action in self.post_input_actions.drain(..) {
-                    sink.emit_single(action);
+                // The deferred actions belong to whichever player gave the input that ended
+                sink.emit_many_controlled(
+                    std::mem::take(&mut self.post_input_actions),
+                    action.controller,
+                );
+            }
+            ActionPayload::DomainAction(da) if da.is::<RemovePlayer>() => {
+                let removed = da.as_t::<RemovePlayer>().unwrap().player;
+                if self.next_priority == Some(removed) {
+                    self.next_priority = Some(game_state.turn_order.next(removed));
+                }
+
+                // Nobody is left to answer a request made of the removed player. If they held
+                // priority, it has already passed to the next player, who is asked for their input
+                // once the game quiesces.
+                if self.current_input_request.map(|e| e.player()) == Some(removed) {
+                    self.current_input_request = None;
+                    self.post_input_actions.clear();
+                    self.chosen_targets.clear();
+                    sink.emit_single(ActionPayload::EngineAction(EngineAction::EndInput));
                 }
             }
             ActionPayload::DomainAction(da) if da.is::<PassPriority>() => {
                 let action = da.as_t::<PassPriority>().unwrap();
                 self.passing_counter += 1;
-                if self.passing_counter == game_state.players.len() {
+                // If the player holding priority left the game after everyone else had passed,
+                // the count can overshoot
+                if self.passing_counter >= game_state.turn_order.len() {
                     // All players have passed priority in succession
 
                     // Whatever happens here, the passing counter is reset.
                     self.passing_counter = 0;
 
-                    if game_state.stack().len() > 0 {
+                    if !game_state.stack().is_empty() {
                         // There is something on the stack to resolve. Resolve that thing and grant
                         // the active player priority.
+                        //
+                        // 405.5 When all players pass in succession, the top (last-added) spell or
+                        //     ability on the stack resolves. [...] Then the active player receives
+                        //     priority.
                         let resolve_action = game_state
                             .stack()
                             .top()
@@ -219,7 +1049,8 @@ impl BaseObserver<Mtg> for StepsAndPriority {
                             .expect("Top of stack has no resolve action");
 
                         sink.emit_single(ActionPayload::DomainAction(resolve_action));
-                        self.next_priority = Some(game_state.step.active_player);
+                        self.next_priority =
+                            Some(acting_for(game_state, game_state.step.active_player));
                     } else {
                         // There is nothing on the stack to resolve. Begin ending this step.
                         let advance_step_ending = Box::new(AdvanceStep {
@@ -239,20 +1070,33 @@ impl BaseObserver<Mtg> for StepsAndPriority {
                     // Not all players have passed in succession yet, so work out which player
                     // should receive priority next. Don't actually emit a SetPriority action just
                     // yet though, instead wait for the next appropriate NoActions event.
-                    let next_priority = game_state
-                        .turn_order
-                        .get(&action.player)
-                        .cloned()
-                        .expect("Don't know which player comes next in the turn order");
+                    let next_priority = game_state.turn_order.next(action.player);
                     self.next_priority = Some(next_priority);
                 }
             }
+            ActionPayload::DomainAction(da) if priority_used_by(&**da).is_some() => {
+                // 117.3c If a player has priority when they cast a spell, activate an ability, or
+                //     take a special action, that player receives priority afterward.
+                // Every player then needs to pass in succession again before anything happens.
+                self.passing_counter = 0;
+                self.next_priority = priority_used_by(&**da);
+            }
             ActionPayload::DomainAction(da) if da.is::<AdvanceStep>() => {
                 let advance_step_action = da.as_t::<AdvanceStep>().unwrap();
                 if advance_step_action.new_substep == SubStep::InProgress {
-                    self.next_priority = Some(advance_step_action.new_active_player);
+                    self.next_priority = Some(acting_for(
+                        game_state,
+                        advance_step_action.new_active_player,
+                    ));
                 }
             }
+            ActionPayload::DomainAction(da) if !da.find_all::<CounterSpell>().is_empty() => {
+                // Passes only count towards resolving the object that was on top of the stack
+                // when they were made. Something leaving the stack without resolving means every
+                // player has to pass again before whatever is now on top resolves, or the step
+                // ends if the stack is now empty.
+                self.passing_counter = 0;
+            }
             _ => (),
         }
     }
@@ -262,7 +1106,7 @@ impl BaseObserver<Mtg> for StepsAndPriority {
         input: &PlayerInput<Mtg>,
         game_state: &Mtg,
         emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
-    ) {
+    ) -> Result<(), String> {
         let expected = self
             .current_input_request
             .expect("Received input when not expecting one");
@@ -272,7 +1116,7 @@ impl BaseObserver<Mtg> for StepsAndPriority {
                 // The engine should have already validated that the input came from the correct player
                 assert_eq!(p, input.source);
 
-                // TODO: don't panic when the wrong input is provided
+                // The engine has already checked that this is a priority input
                 let prio_input = input
                     .payload
                     .as_domain_input()
@@ -280,8 +1124,1408 @@ impl BaseObserver<Mtg> for StepsAndPriority {
                     .as_priority_input()
                     .expect("Expected a priority input");
 
-                self.handle_priority_input(input.source, prio_input, game_state, emit_action);
+                self.handle_priority_input(input.source, prio_input, game_state, emit_action)
+            }
+            ExpectedInput::PlayLand(p) => {
+                assert_eq!(p, input.source);
+
+                let input = input
+                    .payload
+                    .as_domain_input()
+                    .expect("Expected a domain input");
+                self.handle_play_land_input(p, input, game_state, emit_action)
+            }
+            ExpectedInput::FlipCreature(p) => {
+                assert_eq!(p, input.source);
+
+                let input = input
+                    .payload
+                    .as_domain_input()
+                    .expect("Expected a domain input");
+                self.handle_flip_creature_input(p, input, game_state, emit_action)
+            }
+            ExpectedInput::EffectAction(p) => {
+                assert_eq!(p, input.source);
+
+                let input = input
+                    .payload
+                    .as_domain_input()
+                    .expect("Expected a domain input");
+                self.handle_effect_action_input(p, input, game_state, emit_action)
+            }
+            ExpectedInput::SuspendCard(p) => {
+                assert_eq!(p, input.source);
+
+                let input = input
+                    .payload
+                    .as_domain_input()
+                    .expect("Expected a domain input");
+                self.handle_suspend_card_input(p, input, game_state, emit_action)
+            }
+            ExpectedInput::CastSpell(p) => {
+                assert_eq!(p, input.source);
+
+                let input = input
+                    .payload
+                    .as_domain_input()
+                    .expect("Expected a domain input");
+                self.handle_cast_spell_input(p, input, game_state, emit_action)
+            }
+            ExpectedInput::ChooseTarget(p, spell) => {
+                assert_eq!(p, input.source);
+
+                let input = input
+                    .payload
+                    .as_domain_input()
+                    .expect("Expected a domain input");
+                self.handle_choose_target_input(p, spell, input, game_state, emit_action)
+            }
+            ExpectedInput::ActivateAbility(p) => {
+                assert_eq!(p, input.source);
+
+                let input = input
+                    .payload
+                    .as_domain_input()
+                    .expect("Expected a domain input");
+                self.handle_activate_ability_input(p, input, game_state, emit_action)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        actions::ActionPayload,
+        game::{Game, InputError, TickResult},
+        Controller,
+    };
+
+    use super::turn_structure;
+    use crate::{
+        ability::{ActivatedAbility, ActivationCost},
+        action::{
+            AddMana, BaseMtgAction, CounterSpell, DealDamage, GainLife, InsertCombatPhase,
+            MtgAction, MtgActionDowncast, PassPriority, RemovePlayer, SkipPhase, SkipTurn,
+            TakeExtraTurn, Tap,
+        },
+        card::{
+            make_card_universe, CardDefinition, CardType, CardTypeLine, CreatureType, HasType,
+            SpellEffect, SubType,
+        },
+        game::{Mtg, MtgGameBuilder},
+        mana::{BaseManaCostComponent, Color, Mana, ManaConstraint, ManaCost, ManaCostComponent},
+        player_inputs::{InputKind, MtgInput, PriorityInput, SpecialAction},
+        steps::{BeginningStep, CombatStep, EndStep, Phase, Step, SubStep},
+        target::{Target, TargetSpec},
+        test_utils::{create_card, create_object, input, input_kind, pass_priority, try_input},
+        zone::AbstractZoneLocation,
+        AbilityId, ObjectFilter, ObjectId, PlayerId,
+    };
+
+    /// Plays through the turn without anyone doing anything, recording each step that begins
+    /// until the given step is reached
+    fn steps_until(game: &mut Game<Mtg>, last: Step) -> Vec<Step> {
+        let mut steps = vec![game.game_state.step.step];
+        while game.game_state.step.step != last {
+            let player = game.expecting_input_from().unwrap();
+            match input_kind(game).unwrap() {
+                InputKind::Priority => pass_priority(game, player),
+                InputKind::DeclareAttacker | InputKind::DeclareBlocker => {
+                    input(game, player, MtgInput::Finished);
+                    game.tick_until_player_input();
+                }
+                kind => panic!("Unexpected input request: {}", kind),
+            }
+
+            if *steps.last().unwrap() != game.game_state.step.step {
+                steps.push(game.game_state.step.step);
             }
         }
+
+        steps
+    }
+
+    #[test]
+    fn test_additional_combat_phase() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        game.tick_until_player_input();
+
+        InsertCombatPhase {
+            with_main_phase: false,
+        }
+        .apply(&mut game.game_state);
+
+        // Nobody attacks, so neither combat has declare blockers or combat damage steps
+        let combat = [
+            Step::Combat(CombatStep::StartOfCombat),
+            Step::Combat(CombatStep::DeclareAttackers),
+            Step::Combat(CombatStep::EndOfCombat),
+        ];
+        let mut expected = vec![Step::PreCombatMain];
+        expected.extend(combat.iter().cloned());
+        expected.extend(combat.iter().cloned());
+        expected.push(Step::PostCombatMain);
+
+        assert_eq!(steps_until(&mut game, Step::PostCombatMain), expected);
+        assert!(game.game_state.step_queue.is_empty());
+    }
+
+    #[test]
+    fn test_additional_combat_phase_mid_combat() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step(
+                "alice",
+                Step::Combat(CombatStep::CombatDamage),
+                SubStep::InProgress,
+            )
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        game.tick_until_player_input();
+
+        InsertCombatPhase {
+            with_main_phase: true,
+        }
+        .apply(&mut game.game_state);
+
+        let mut expected = vec![
+            Step::Combat(CombatStep::CombatDamage),
+            Step::Combat(CombatStep::EndOfCombat),
+        ];
+        expected.extend([
+            Step::Combat(CombatStep::StartOfCombat),
+            Step::Combat(CombatStep::DeclareAttackers),
+            Step::Combat(CombatStep::EndOfCombat),
+        ]);
+        expected.push(Step::PostCombatMain);
+
+        assert_eq!(steps_until(&mut game, Step::PostCombatMain), expected);
+
+        // The additional main phase is followed by the regular one
+        let bob = game.game_state.find_player("bob").unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(game.game_state.step.step, Step::PostCombatMain);
+        assert!(game.game_state.step_queue.is_empty());
+    }
+
+    /// A game in alice's first main phase, with two forests and a bear in her hand
+    fn game_with_lands_in_hand() -> (Game<Mtg>, Vec<ObjectId>) {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let hand = game.game_state.player(alice).hand;
+
+        let universe = make_card_universe();
+        let cards = ["Forest", "Forest", "Grizzly Bears"]
+            .iter()
+            .map(|name| {
+                let definition = universe.find_by_name(name).unwrap().clone();
+                create_card(
+                    &mut game,
+                    alice,
+                    hand,
+                    AbstractZoneLocation::Undefined,
+                    definition,
+                )
+            })
+            .collect();
+
+        game.tick_until_player_input();
+        (game, cards)
+    }
+
+    fn play_land(game: &mut Game<Mtg>, player: PlayerId) -> Result<(), InputError> {
+        try_input(
+            game,
+            player,
+            MtgInput::PriorityInput(PriorityInput::SpecialAction(SpecialAction::PlayLand)),
+        )
+    }
+
+    #[test]
+    fn test_play_land() {
+        let (mut game, cards) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+
+        play_land(&mut game, alice).unwrap();
+        assert_eq!(input_kind(&game), Some(InputKind::PlayLand));
+
+        // Only lands can be played
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::ObjectId(cards[2])),
+            Err(InputError::Rejected(_))
+        ));
+
+        input(&mut game, alice, MtgInput::ObjectId(cards[0]));
+        game.tick_until_player_input();
+
+        let state = &game.game_state;
+        assert!(state.battlefield().contains(cards[0]));
+        assert_eq!(state.zones[&state.player(alice).hand].len(), 2);
+        assert_eq!(state.player(alice).lands_played_this_turn, 1);
+
+        // Playing a land doesn't pass priority
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+        assert_eq!(game.game_state.priority, Some(alice));
+    }
+
+    #[test]
+    fn test_flip_creature() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+
+        // A 3/1 bear with "Morph {G}"
+        let definition = CardDefinition {
+            name: "Hidden Bear".to_string(),
+            type_line: CardTypeLine {
+                card_types: vec![CardType::Creature],
+                sub_types: vec![SubType::Creature(CreatureType::Bear)],
+                ..Default::default()
+            },
+            power: Some(3),
+            toughness: Some(1),
+            morph_cost: Some(ManaCost {
+                components: vec![ManaCostComponent::Base(BaseManaCostComponent::Single(
+                    ManaConstraint::Color(Color::Green),
+                ))],
+            }),
+            ..Default::default()
+        };
+        let bear = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            definition.clone(),
+        );
+        game.game_state
+            .battlefield_mut()
+            .get_mut(bear)
+            .unwrap()
+            .face_down = true;
+
+        // Face down, it's a nameless 2/2 creature with no subtypes
+        let obj = game.game_state.battlefield().get(bear).unwrap();
+        assert_eq!((obj.power(), obj.toughness()), (Some(2), Some(2)));
+        assert!(obj.has_type(CardType::Creature));
+        assert!(!obj.has_type(CreatureType::Bear));
+        assert_eq!(obj.characteristics().name, "");
+
+        let flip = |game: &mut Game<Mtg>, player| {
+            try_input(
+                game,
+                player,
+                MtgInput::PriorityInput(PriorityInput::SpecialAction(SpecialAction::FlipCreature)),
+            )
+        };
+
+        // The morph cost can't be paid yet
+        flip(&mut game, alice).unwrap();
+        assert_eq!(input_kind(&game), Some(InputKind::FlipCreature));
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::ObjectId(bear)),
+            Err(InputError::Rejected(_))
+        ));
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+
+        // Only the permanent's controller can turn it face up
+        pass_priority(&mut game, alice);
+        flip(&mut game, bob).unwrap();
+        assert!(matches!(
+            try_input(&mut game, bob, MtgInput::ObjectId(bear)),
+            Err(InputError::Rejected(_))
+        ));
+        input(&mut game, bob, MtgInput::Finished);
+        game.tick_until_player_input();
+
+        // Any time they have priority, not just at sorcery speed
+        pass_priority(&mut game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::StartOfCombat)
+        );
+        AddMana {
+            player: alice,
+            color: Some(Color::Green),
+            amount: 1,
+            source: None,
+        }
+        .apply(&mut game.game_state);
+
+        flip(&mut game, alice).unwrap();
+        input(&mut game, alice, MtgInput::ObjectId(bear));
+        game.tick_until_player_input();
+
+        // Its characteristics revert to the printed card, and the cost has been paid
+        let state = &game.game_state;
+        let obj = state.battlefield().get(bear).unwrap();
+        assert!(!obj.face_down);
+        assert_eq!(*obj.characteristics(), definition);
+        assert!(obj.has_type(CreatureType::Bear));
+        assert_eq!(state.effective_power_toughness(bear), (3, 1));
+        assert!(state.player(alice).mana_pool.is_empty());
+
+        // Turning a creature face up doesn't use the stack or pass priority
+        assert!(state.stack().is_empty());
+        assert_eq!(state.priority, Some(alice));
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+    }
+
+    #[test]
+    fn test_pass_priority_controller() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        game.tick_until_player_input();
+        let alice = game.game_state.find_player("alice").unwrap();
+
+        input(
+            &mut game,
+            alice,
+            MtgInput::PriorityInput(PriorityInput::PassPriority),
+        );
+        let mut controllers = Vec::new();
+        while let TickResult::Ticked(action) = game.tick() {
+            if let ActionPayload::DomainAction(da) = &action.payload {
+                controllers.push((da.is::<PassPriority>(), action.controller));
+            }
+        }
+
+        // Passing priority is done by the player, while handing priority on is done by the game
+        assert_eq!(
+            controllers,
+            vec![(true, Controller::Player(alice)), (false, Controller::Game)]
+        );
+    }
+
+    #[test]
+    fn test_second_land_rejected() {
+        let (mut game, cards) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+
+        play_land(&mut game, alice).unwrap();
+        input(&mut game, alice, MtgInput::ObjectId(cards[0]));
+        game.tick_until_player_input();
+
+        assert!(matches!(
+            play_land(&mut game, alice),
+            Err(InputError::Rejected(_))
+        ));
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+        assert!(!game.game_state.battlefield().contains(cards[1]));
+    }
+
+    fn cast_spell(game: &mut Game<Mtg>, player: PlayerId, spell: ObjectId) {
+        input(
+            game,
+            player,
+            MtgInput::PriorityInput(PriorityInput::CastSpell),
+        );
+        assert_eq!(input_kind(game), Some(InputKind::CastSpell));
+        input(game, player, MtgInput::ObjectId(spell));
+        game.tick_until_player_input();
+    }
+
+    /// Puts a permanent with a single "{T}: [effect]" ability onto the battlefield under the given
+    /// player's control
+    fn tap_ability_permanent(
+        game: &mut Game<Mtg>,
+        player: PlayerId,
+        effect: Box<dyn MtgAction>,
+        is_mana_ability: bool,
+    ) -> (ObjectId, AbilityId) {
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let permanent = create_object(game, player, battlefield, AbstractZoneLocation::Undefined);
+        let ability = game.game_state.ability_id_gen.next_id();
+        game.game_state
+            .battlefield_mut()
+            .get_mut(permanent)
+            .unwrap()
+            .abilities
+            .push(ActivatedAbility {
+                id: ability,
+                cost: ActivationCost {
+                    tap: true,
+                    ..Default::default()
+                },
+                effect,
+                is_mana_ability,
+            });
+        (permanent, ability)
+    }
+
+    fn try_activate(
+        game: &mut Game<Mtg>,
+        player: PlayerId,
+        ability: AbilityId,
+    ) -> Result<(), InputError> {
+        input(
+            game,
+            player,
+            MtgInput::PriorityInput(PriorityInput::ActivateAbility),
+        );
+        assert_eq!(input_kind(game), Some(InputKind::ActivateAbility));
+        let res = try_input(game, player, MtgInput::AbilityId(ability));
+        if res.is_err() {
+            input(game, player, MtgInput::Finished);
+        }
+        game.tick_until_player_input();
+        res
+    }
+
+    #[test]
+    fn test_activate_mana_ability() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        // "{T}: Add {G}"
+        let add_green = Box::new(AddMana {
+            player: alice,
+            color: Some(Color::Green),
+            amount: 1,
+            source: None,
+        });
+        let (forest, ability) = tap_ability_permanent(&mut game, alice, add_green, true);
+
+        // Only the permanent's controller can activate its abilities
+        pass_priority(&mut game, alice);
+        assert!(matches!(
+            try_activate(&mut game, bob, ability),
+            Err(InputError::Rejected(_))
+        ));
+        pass_priority(&mut game, bob);
+        game.tick_until_player_input();
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::StartOfCombat)
+        );
+
+        try_activate(&mut game, alice, ability).unwrap();
+        let state = &game.game_state;
+        assert!(state.stack().is_empty());
+        assert!(state.battlefield().get(forest).unwrap().tapped);
+        assert_eq!(
+            state.player(alice).mana_pool.mana,
+            vec![Mana {
+                color: Some(Color::Green),
+                snow: false,
+            }]
+        );
+        assert_eq!(state.priority, Some(alice));
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+
+        // The forest is now tapped, so can't pay the cost again
+        assert!(matches!(
+            try_activate(&mut game, alice, ability),
+            Err(InputError::Rejected(_))
+        ));
+        assert_eq!(game.game_state.player(alice).mana_pool.mana.len(), 1);
+    }
+
+    #[test]
+    fn test_mana_ability_keeps_priority_round() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let add_red = Box::new(AddMana {
+            player: bob,
+            color: Some(Color::Red),
+            amount: 1,
+            source: None,
+        });
+        let (_, ability) = tap_ability_permanent(&mut game, bob, add_red, true);
+
+        pass_priority(&mut game, alice);
+        input(
+            &mut game,
+            bob,
+            MtgInput::PriorityInput(PriorityInput::ActivateAbility),
+        );
+        input(&mut game, bob, MtgInput::AbilityId(ability));
+
+        // The mana is added without the game needing to tick, and Bob is straight back to holding
+        // priority
+        assert_eq!(
+            game.game_state
+                .player(bob)
+                .mana_pool
+                .total_of(Some(Color::Red)),
+            1
+        );
+        assert_eq!(game.expecting_input_from(), Some(bob));
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+
+        // Alice's pass still counts, so Bob passing ends the round
+        pass_priority(&mut game, bob);
+        game.tick_until_player_input();
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::StartOfCombat)
+        );
+    }
+
+    #[test]
+    fn test_activated_ability_uses_stack() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let life_total = game.game_state.player(alice).life_total;
+
+        // "{T}: You gain 1 life"
+        let gain_life = Box::new(GainLife {
+            player: alice,
+            amount: 1,
+        });
+        let (_, ability) = tap_ability_permanent(&mut game, alice, gain_life, false);
+
+        try_activate(&mut game, alice, ability).unwrap();
+        assert_eq!(game.game_state.stack().len(), 1);
+        assert_eq!(game.game_state.priority, Some(alice));
+
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert!(game.game_state.stack().is_empty());
+        assert_eq!(game.game_state.player(alice).life_total, life_total + 1);
+        assert_eq!(game.game_state.priority, Some(alice));
+    }
+
+    #[test]
+    fn test_cast_and_resolve_spell() {
+        let (mut game, cards) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let instant = create_card(
+            &mut game,
+            alice,
+            hand,
+            AbstractZoneLocation::Undefined,
+            CardDefinition {
+                name: "Test Instant".to_string(),
+                type_line: CardTypeLine {
+                    card_types: vec![CardType::Instant],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        // Lands can't be cast
+        input(
+            &mut game,
+            alice,
+            MtgInput::PriorityInput(PriorityInput::CastSpell),
+        );
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::ObjectId(cards[0])),
+            Err(InputError::Rejected(_))
+        ));
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+
+        cast_spell(&mut game, alice, instant);
+        assert_eq!(game.game_state.stack().search(|_| true), vec![instant]);
+        assert_eq!(game.game_state.priority, Some(alice));
+
+        // Once every player passes in succession, the spell resolves and the active player
+        // receives priority again
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+
+        let state = &game.game_state;
+        assert!(state.stack().is_empty());
+        assert!(state.zones[&state.player(alice).graveyard].contains(instant));
+        assert_eq!(state.step.step, Step::PreCombatMain);
+        assert_eq!(state.priority, Some(alice));
+    }
+
+    #[test]
+    fn test_stack_resolves_last_in_first_out() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let graveyard = game.game_state.player(alice).graveyard;
+        let [first, second] = ["First Instant", "Second Instant"].map(|name| {
+            create_card(
+                &mut game,
+                alice,
+                hand,
+                AbstractZoneLocation::Undefined,
+                CardDefinition {
+                    name: name.to_string(),
+                    type_line: CardTypeLine {
+                        card_types: vec![CardType::Instant],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            )
+        });
+
+        cast_spell(&mut game, alice, first);
+        cast_spell(&mut game, alice, second);
+        assert_eq!(game.game_state.stack().top().unwrap().id, second);
+
+        // Only the most recently cast spell resolves once everyone passes
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        let state = &game.game_state;
+        assert_eq!(state.stack().search(|_| true), vec![first]);
+        assert!(state.zones[&graveyard].contains(second));
+        assert_eq!(state.priority, Some(alice));
+
+        // Players get priority again before the next spell resolves
+        pass_priority(&mut game, alice);
+        assert_eq!(game.game_state.stack().search(|_| true), vec![first]);
+        pass_priority(&mut game, bob);
+        let state = &game.game_state;
+        assert!(state.stack().is_empty());
+        assert!(state.zones[&graveyard].contains(first));
+        assert_eq!(state.step.step, Step::PreCombatMain);
+        assert_eq!(state.priority, Some(alice));
+    }
+
+    #[test]
+    fn test_cast_resets_priority_round() {
+        let (mut game, [alice, bob, carol]) = three_player_game();
+        let hand = game.game_state.player(bob).hand;
+        let instant = create_card(
+            &mut game,
+            bob,
+            hand,
+            AbstractZoneLocation::Undefined,
+            CardDefinition {
+                name: "Test Instant".to_string(),
+                type_line: CardTypeLine {
+                    card_types: vec![CardType::Instant],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        // Bob casts a spell part way through the round, after Alice has passed
+        pass_priority(&mut game, alice);
+        cast_spell(&mut game, bob, instant);
+        assert_eq!(game.game_state.priority, Some(bob));
+
+        // Alice's earlier pass no longer counts, so the spell waits for her to pass again
+        pass_priority(&mut game, bob);
+        pass_priority(&mut game, carol);
+        assert_eq!(game.game_state.stack().search(|_| true), vec![instant]);
+        assert_eq!(game.game_state.priority, Some(alice));
+
+        pass_priority(&mut game, alice);
+        assert!(game.game_state.stack().is_empty());
+        assert_eq!(game.game_state.priority, Some(alice));
+    }
+
+    /// An instant reading "Shock deals 2 damage to target creature."
+    fn shock_definition() -> CardDefinition {
+        CardDefinition {
+            name: "Shock".to_string(),
+            type_line: CardTypeLine {
+                card_types: vec![CardType::Instant],
+                ..Default::default()
+            },
+            targets: vec![TargetSpec::Permanent(ObjectFilter::CardType(
+                CardType::Creature,
+            ))],
+            spell_effect: Some(SpellEffect::DamageTargets(2)),
+            ..Default::default()
+        }
+    }
+
+    /// Alice has a Shock in hand, and bob controls a Grizzly Bears
+    fn game_with_shock_and_bears() -> (Game<Mtg>, ObjectId, ObjectId) {
+        let (mut game, cards) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let shock = create_card(
+            &mut game,
+            alice,
+            hand,
+            AbstractZoneLocation::Undefined,
+            shock_definition(),
+        );
+        let bears = game.game_state.zones[&hand].get(cards[2]).unwrap();
+        let definition = (*bears.definition).clone();
+        let bears = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            definition,
+        );
+        (game, shock, bears)
+    }
+
+    #[test]
+    fn test_cast_targeted_spell() {
+        let (mut game, shock, bears) = game_with_shock_and_bears();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let creature_in_hand =
+            game.game_state.zones[&hand].search(|obj| obj.has_type(CardType::Creature))[0];
+
+        cast_spell(&mut game, alice, shock);
+        assert_eq!(input_kind(&game), Some(InputKind::ChooseTarget));
+
+        // Neither a player nor a creature card in hand is a legal target for Shock
+        for target in [
+            MtgInput::PlayerId(bob),
+            MtgInput::ObjectId(creature_in_hand),
+        ] {
+            assert!(matches!(
+                try_input(&mut game, alice, target),
+                Err(InputError::Rejected(_))
+            ));
+        }
+        input(&mut game, alice, MtgInput::ObjectId(bears));
+        game.tick_until_player_input();
+
+        let on_stack = game.game_state.stack().get(shock).unwrap();
+        assert_eq!(on_stack.targets, vec![Target::Object(bears)]);
+        assert_eq!(game.game_state.priority, Some(alice));
+
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+
+        // The bears were dealt lethal damage, and died as a state-based action
+        let state = &game.game_state;
+        assert!(state.stack().is_empty());
+        assert!(state.zones[&state.player(alice).graveyard].contains(shock));
+        assert!(state.zones[&state.player(bob).graveyard].contains(bears));
+    }
+
+    #[test]
+    fn test_cancel_targeted_spell() {
+        let (mut game, shock, bears) = game_with_shock_and_bears();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+
+        // Alice changes her mind instead of choosing a target, and still holds priority
+        cast_spell(&mut game, alice, shock);
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+        assert!(game.game_state.stack().is_empty());
+        assert!(game.game_state.zones[&hand].contains(shock));
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+        assert_eq!(game.expecting_input_from(), Some(alice));
+
+        // Nothing was left over from the cancelled cast
+        cast_spell(&mut game, alice, shock);
+        input(&mut game, alice, MtgInput::ObjectId(bears));
+        game.tick_until_player_input();
+        let on_stack = game.game_state.stack().get(shock).unwrap();
+        assert_eq!(on_stack.targets, vec![Target::Object(bears)]);
+        pass_priority(&mut game, alice);
+        assert_eq!(game.expecting_input_from(), Some(bob));
+    }
+
+    #[test]
+    fn test_spell_without_legal_targets() {
+        let (mut game, shock, bears) = game_with_shock_and_bears();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        cast_spell(&mut game, alice, shock);
+        input(&mut game, alice, MtgInput::ObjectId(bears));
+        game.tick_until_player_input();
+
+        // The bears stop being targetable while Shock is on the stack, so it doesn't resolve
+        game.game_state
+            .battlefield_mut()
+            .get_mut(bears)
+            .unwrap()
+            .can_be_targeted = false;
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+
+        let state = &game.game_state;
+        assert!(state.stack().is_empty());
+        assert!(state.zones[&state.player(alice).graveyard].contains(shock));
+        assert_eq!(state.battlefield().get(bears).unwrap().damage_marked, 0);
+
+        // Another Shock can't even be cast, as there's nothing left for it to target
+        let hand = game.game_state.player(alice).hand;
+        let another = create_card(
+            &mut game,
+            alice,
+            hand,
+            AbstractZoneLocation::Undefined,
+            shock_definition(),
+        );
+        input(
+            &mut game,
+            alice,
+            MtgInput::PriorityInput(PriorityInput::CastSpell),
+        );
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::ObjectId(another)),
+            Err(InputError::Rejected(_))
+        ));
+    }
+
+    #[test]
+    fn test_counter_spell() {
+        let (mut game, shock, bears) = game_with_shock_and_bears();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        // "{T}: Counter [Shock]"
+        let counter = Box::new(CounterSpell { object: shock });
+        let (_, ability) = tap_ability_permanent(&mut game, bob, counter, false);
+
+        cast_spell(&mut game, alice, shock);
+        input(&mut game, alice, MtgInput::ObjectId(bears));
+        game.tick_until_player_input();
+
+        // Bob responds, and his ability resolves first
+        pass_priority(&mut game, alice);
+        try_activate(&mut game, bob, ability).unwrap();
+        assert_eq!(game.game_state.stack().len(), 2);
+        pass_priority(&mut game, bob);
+        pass_priority(&mut game, alice);
+
+        // Shock left the stack without resolving, so the bears weren't dealt any damage
+        let state = &game.game_state;
+        assert!(state.stack().is_empty());
+        assert!(state.zones[&state.player(alice).graveyard].contains(shock));
+        assert_eq!(state.battlefield().get(bears).unwrap().damage_marked, 0);
+        assert_eq!(state.step.step, Step::PreCombatMain);
+        assert_eq!(state.priority, Some(alice));
+
+        // With nothing left on the stack, passing moves on to the next step
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_ne!(game.game_state.step.step, Step::PreCombatMain);
+        assert_eq!(
+            game.game_state
+                .battlefield()
+                .get(bears)
+                .unwrap()
+                .damage_marked,
+            0
+        );
+    }
+
+    /// Plays through to the start of the next turn, returning the player whose turn it is
+    ///
+    /// Nobody receives priority during the cleanup step, so the end step is the last stop.
+    fn next_turn(game: &mut Game<Mtg>) -> PlayerId {
+        steps_until(game, Step::End(EndStep::EndOfTurn));
+        steps_until(game, Step::Beginning(BeginningStep::Untap));
+        game.game_state.step.active_player
+    }
+
+    fn three_player_game() -> (Game<Mtg>, [PlayerId; 3]) {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_player("carol")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        game.tick_until_player_input();
+        let players =
+            ["alice", "bob", "carol"].map(|name| game.game_state.find_player(name).unwrap());
+        (game, players)
+    }
+
+    #[test]
+    fn test_four_player_priority_and_turns() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_player("carol")
+            .with_player("dave")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        game.tick_until_player_input();
+        let [alice, bob, carol, dave] = ["alice", "bob", "carol", "dave"]
+            .map(|name| game.game_state.find_player(name).unwrap());
+
+        // Priority goes around the table, and the step only ends once all four have passed
+        for (player, next) in [(alice, bob), (bob, carol), (carol, dave)] {
+            assert_eq!(game.game_state.priority, Some(player));
+            pass_priority(&mut game, player);
+            assert_eq!(game.game_state.step.step, Step::PreCombatMain);
+            assert_eq!(game.game_state.priority, Some(next));
+        }
+        pass_priority(&mut game, dave);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::StartOfCombat)
+        );
+        assert_eq!(game.game_state.priority, Some(alice));
+
+        // On bob's turn, priority starts with him and wraps around past dave to alice
+        assert_eq!(next_turn(&mut game), bob);
+        let upkeep = Step::Beginning(BeginningStep::Upkeep);
+        steps_until(&mut game, upkeep);
+        for (player, next) in [(bob, carol), (carol, dave), (dave, alice)] {
+            assert_eq!(game.game_state.priority, Some(player));
+            pass_priority(&mut game, player);
+            assert_eq!(game.game_state.step.step, upkeep);
+            assert_eq!(game.game_state.priority, Some(next));
+        }
+        pass_priority(&mut game, alice);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Beginning(BeginningStep::Draw)
+        );
+        assert_eq!(game.game_state.priority, Some(bob));
+
+        // Each player takes a turn in order, then it's back to alice
+        for player in [carol, dave, alice] {
+            assert_eq!(next_turn(&mut game), player);
+        }
+        assert_eq!(game.game_state.turn_number, 5);
+    }
+
+    fn remove_player(game: &mut Game<Mtg>, player: PlayerId) {
+        let action = Box::new(RemovePlayer { player }) as Box<dyn MtgAction>;
+        game.perform_action(ActionPayload::DomainAction(action));
+        game.tick_until_player_input();
+    }
+
+    #[test]
+    fn test_remove_player() {
+        let (mut game, [alice, bob, carol]) = three_player_game();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = make_card_universe()
+            .find_by_name("Grizzly Bears")
+            .unwrap()
+            .clone();
+        let bobs_bears = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            bears.clone(),
+        );
+        let stolen_bears = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            bears,
+        );
+        game.game_state
+            .battlefield_mut()
+            .get_mut(stolen_bears)
+            .unwrap()
+            .controller = bob;
+
+        // Alice and bob have both passed when bob leaves the game, so carol passing finishes the
+        // round
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        remove_player(&mut game, bob);
+        assert!(!game.game_state.turn_order.contains(bob));
+        assert!(!game.game_state.players.contains_key(&bob));
+        assert!(!game.game_state.battlefield().contains(bobs_bears));
+        let stolen = game.game_state.battlefield().get(stolen_bears).unwrap();
+        assert_eq!(stolen.controller, alice);
+
+        pass_priority(&mut game, carol);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::StartOfCombat)
+        );
+
+        // The remaining two pass back and forth
+        assert_eq!(game.game_state.priority, Some(alice));
+        pass_priority(&mut game, alice);
+        assert_eq!(game.game_state.priority, Some(carol));
+        pass_priority(&mut game, carol);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::DeclareAttackers)
+        );
+
+        // Bob's seat is skipped when the turn passes
+        assert_eq!(next_turn(&mut game), carol);
+        assert_eq!(next_turn(&mut game), alice);
+    }
+
+    #[test]
+    fn test_remove_priority_holder() {
+        let (mut game, [alice, bob, carol]) = three_player_game();
+
+        // Priority passes straight on to carol, and alice's pass still counts
+        pass_priority(&mut game, alice);
+        assert_eq!(game.expecting_input_from(), Some(bob));
+        remove_player(&mut game, bob);
+        assert_eq!(game.game_state.priority, Some(carol));
+        assert_eq!(game.expecting_input_from(), Some(carol));
+        pass_priority(&mut game, carol);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::StartOfCombat)
+        );
+        assert_eq!(game.expecting_input_from(), Some(alice));
+    }
+
+    #[test]
+    fn test_removed_player_pass_no_longer_counts() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_player("carol")
+            .with_player("dave")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        game.tick_until_player_input();
+        let [alice, bob, carol, dave] = ["alice", "bob", "carol", "dave"]
+            .map(|name| game.game_state.find_player(name).unwrap());
+
+        // Bob leaves after passing, so it's only alice and carol that have passed once dave
+        // receives priority
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        remove_player(&mut game, bob);
+        pass_priority(&mut game, carol);
+        assert_eq!(game.game_state.step.step, Step::PreCombatMain);
+        assert_eq!(game.expecting_input_from(), Some(dave));
+
+        pass_priority(&mut game, dave);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::StartOfCombat)
+        );
+    }
+
+    #[test]
+    fn test_remove_active_player() {
+        let (mut game, [alice, bob, carol]) = three_player_game();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = make_card_universe()
+            .find_by_name("Grizzly Bears")
+            .unwrap()
+            .clone();
+        let alices_bears = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            bears,
+        );
+
+        remove_player(&mut game, alice);
+        assert!(game.game_state.battlefield().get(alices_bears).is_none());
+
+        // The rest of alice's turn still happens, with bob receiving priority in their place
+        assert_eq!(game.expecting_input_from(), Some(bob));
+        let steps = steps_until(&mut game, Step::End(EndStep::EndOfTurn));
+        assert!(steps.contains(&Step::Combat(CombatStep::DeclareAttackers)));
+        assert_eq!(game.game_state.step.active_player, alice);
+
+        assert_eq!(next_turn(&mut game), bob);
+        assert_eq!(next_turn(&mut game), carol);
+        assert_eq!(next_turn(&mut game), bob);
+    }
+
+    #[test]
+    fn test_turn_state_resets() {
+        let (mut game, cards) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let forest = cards[0];
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            make_card_universe()
+                .find_by_name("Grizzly Bears")
+                .unwrap()
+                .clone(),
+        );
+
+        play_land(&mut game, alice).unwrap();
+        input(&mut game, alice, MtgInput::ObjectId(forest));
+        game.tick_until_player_input();
+        assert!(
+            game.game_state
+                .battlefield()
+                .get(forest)
+                .unwrap()
+                .summoning_sick
+        );
+        Tap { object: forest }.apply(&mut game.game_state);
+        DealDamage {
+            object: bears,
+            amount: 1,
+        }
+        .apply(&mut game.game_state);
+
+        steps_until(&mut game, Step::End(EndStep::EndOfTurn));
+        let state = &game.game_state;
+        assert_eq!(state.player(alice).lands_played_this_turn, 1);
+        assert_eq!(state.battlefield().get(bears).unwrap().damage_marked, 1);
+
+        // Damage wears off during alice's cleanup step, and bob's turn starts with nobody having
+        // played a land. Only bob's own permanents untap.
+        assert_eq!(next_turn(&mut game), bob);
+        let state = &game.game_state;
+        assert_eq!(state.player(alice).lands_played_this_turn, 0);
+        assert_eq!(state.battlefield().get(bears).unwrap().damage_marked, 0);
+        assert!(state.battlefield().get(forest).unwrap().tapped);
+        assert!(state.battlefield().get(forest).unwrap().summoning_sick);
+
+        // Alice has controlled the forest continuously since her turn began
+        assert_eq!(next_turn(&mut game), alice);
+        let forest = game.game_state.battlefield().get(forest).unwrap();
+        assert!(!forest.tapped);
+        assert!(!forest.summoning_sick);
+    }
+
+    #[test]
+    fn test_skip_phases() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        // "Skip your combat phase this turn"
+        SkipPhase {
+            phase: Phase::Combat,
+        }
+        .apply(&mut game.game_state);
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(game.game_state.step.step, Step::PostCombatMain);
+        assert_eq!(game.game_state.priority, Some(alice));
+
+        // Skipping the end phase skips the cleanup step where the turn would normally pass on,
+        // but the next turn still begins
+        SkipPhase { phase: Phase::End }.apply(&mut game.game_state);
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Beginning(BeginningStep::Untap)
+        );
+        assert_eq!(game.game_state.step.active_player, bob);
+        assert_eq!(game.game_state.turn_number, 2);
+        assert!(game.game_state.skipped_phases.is_empty());
+
+        // Bob's turn is played out in full
+        let steps = steps_until(&mut game, Step::End(EndStep::EndOfTurn));
+        assert!(steps.contains(&Step::Combat(CombatStep::StartOfCombat)));
+    }
+
+    #[test]
+    fn test_skip_phase_after_inserted_phase() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let bob = game.game_state.find_player("bob").unwrap();
+        steps_until(&mut game, Step::PostCombatMain);
+
+        // The end phase that would follow the additional combat phase is skipped, so the turn
+        // ends without the step queue being used up
+        InsertCombatPhase {
+            with_main_phase: false,
+        }
+        .apply(&mut game.game_state);
+        SkipPhase { phase: Phase::End }.apply(&mut game.game_state);
+        steps_until(&mut game, Step::Beginning(BeginningStep::Untap));
+        assert_eq!(game.game_state.step.active_player, bob);
+        assert!(game.game_state.step_queue.is_empty());
+
+        // Bob's turn still starts from the beginning
+        let steps = steps_until(&mut game, Step::PreCombatMain);
+        assert_eq!(
+            steps,
+            vec![
+                Step::Beginning(BeginningStep::Untap),
+                Step::Beginning(BeginningStep::Upkeep),
+                Step::Beginning(BeginningStep::Draw),
+                Step::PreCombatMain,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extra_turn() {
+        let (mut game, [alice, bob, carol]) = three_player_game();
+        TakeExtraTurn { player: alice }.apply(&mut game.game_state);
+
+        assert_eq!(next_turn(&mut game), alice);
+        assert_eq!(game.game_state.turn_number, 2);
+        assert!(game.game_state.extra_turns.is_empty());
+        assert_eq!(next_turn(&mut game), bob);
+        assert_eq!(next_turn(&mut game), carol);
+    }
+
+    #[test]
+    fn test_stacked_extra_turns() {
+        let (mut game, [alice, bob, carol]) = three_player_game();
+
+        // The most recently created extra turn is taken first
+        TakeExtraTurn { player: carol }.apply(&mut game.game_state);
+        TakeExtraTurn { player: alice }.apply(&mut game.game_state);
+
+        assert_eq!(next_turn(&mut game), alice);
+        assert_eq!(next_turn(&mut game), carol);
+        assert_eq!(next_turn(&mut game), bob);
+    }
+
+    #[test]
+    fn test_skip_turn() {
+        let (mut game, [alice, bob, carol]) = three_player_game();
+        SkipTurn { player: bob }.apply(&mut game.game_state);
+        SkipTurn { player: carol }.apply(&mut game.game_state);
+        SkipTurn { player: carol }.apply(&mut game.game_state);
+
+        assert_eq!(next_turn(&mut game), alice);
+        assert_eq!(next_turn(&mut game), bob);
+        assert_eq!(next_turn(&mut game), alice);
+        assert!(game.game_state.skipped_turns.is_empty());
+        assert_eq!(next_turn(&mut game), bob);
+        assert_eq!(next_turn(&mut game), carol);
+    }
+
+    #[test]
+    fn test_turn_structure() {
+        let structure = turn_structure();
+        assert_eq!(structure.len(), 12);
+        assert_eq!(
+            structure.first(),
+            Some(&(Step::Beginning(BeginningStep::Untap), false))
+        );
+        assert_eq!(
+            structure.last(),
+            Some(&(Step::End(EndStep::Cleanup), false))
+        );
+
+        let without_priority = structure
+            .iter()
+            .filter(|(_, has_priority)| !has_priority)
+            .count();
+        assert_eq!(without_priority, 2);
+    }
+
+    /// Plays through the turn without anyone doing anything until the given step is reached,
+    /// recording the step each time a player passes priority
+    fn priority_passes_until(game: &mut Game<Mtg>, last: Step) -> Vec<(Step, PlayerId)> {
+        let mut passes = Vec::new();
+        while game.game_state.step.step != last {
+            let player = game.expecting_input_from().unwrap();
+            match input_kind(game).unwrap() {
+                InputKind::Priority => {
+                    passes.push((game.game_state.step.step, player));
+                    pass_priority(game, player);
+                }
+                InputKind::DeclareAttacker | InputKind::DeclareBlocker => {
+                    input(game, player, MtgInput::Finished);
+                    game.tick_until_player_input();
+                }
+                kind => panic!("Unexpected input request: {}", kind),
+            }
+        }
+
+        passes
+    }
+
+    #[test]
+    fn test_main_phase_and_combat_step_transitions() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        // Each step (and the main phase, which has no steps) gives each player priority exactly
+        // once, starting with the active player, before moving on
+        let passes = priority_passes_until(&mut game, Step::Combat(CombatStep::EndOfCombat));
+        let expected = [
+            Step::PreCombatMain,
+            Step::Combat(CombatStep::StartOfCombat),
+            Step::Combat(CombatStep::DeclareAttackers),
+        ]
+        .iter()
+        .flat_map(|&step| vec![(step, alice), (step, bob)])
+        .collect::<Vec<_>>();
+        assert_eq!(passes, expected);
+        assert_eq!(game.game_state.priority, Some(alice));
+    }
+
+    #[test]
+    fn test_step_waits_for_empty_stack() {
+        let (mut game, _) = game_with_lands_in_hand();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(bob).hand;
+        let instant = create_card(
+            &mut game,
+            bob,
+            hand,
+            AbstractZoneLocation::Undefined,
+            CardDefinition {
+                name: "Test Instant".to_string(),
+                type_line: CardTypeLine {
+                    card_types: vec![CardType::Instant],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        // Bob responds to alice passing by casting a spell, so alice passing doesn't count
+        // towards the round of passes that ends the main phase
+        pass_priority(&mut game, alice);
+        cast_spell(&mut game, bob, instant);
+        pass_priority(&mut game, bob);
+        assert_eq!(game.game_state.step.step, Step::PreCombatMain);
+        pass_priority(&mut game, alice);
+
+        // Passing in succession resolved the spell instead of ending the phase
+        assert!(game.game_state.stack().is_empty());
+        assert_eq!(game.game_state.step.step, Step::PreCombatMain);
+        assert_eq!(game.game_state.priority, Some(alice));
+
+        pass_priority(&mut game, alice);
+        assert_eq!(game.game_state.step.step, Step::PreCombatMain);
+        pass_priority(&mut game, bob);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::StartOfCombat)
+        );
+        assert_eq!(game.game_state.priority, Some(alice));
     }
 }
@@ -0,0 +1,156 @@
+//! Observer that has a player choose who takes the first turn of the game
+//!
+//! See section 103.1 of the comprehensive rules
+
+use core::{
+    actions::{Action, ActionPayload, EngineAction, InputRequest},
+    ActionSink, BaseObserver, PlayerInput,
+};
+
+use crate::{
+    action::{AdvanceStep, MtgAction, MtgActionDowncast, SetStartingPlayer},
+    game::Mtg,
+    player_inputs::{InputKind, MtgInput},
+    steps::{StartingStep, Step, SubStep},
+};
+
+/// Asks the player in the first seat who should take the first turn
+///
+/// Does nothing if the game was set up with a fixed turn order.
+#[derive(Clone, Debug)]
+pub struct StartingPlayerChoice {}
+
+impl BaseObserver<Mtg> for StartingPlayerChoice {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let is_choosing_turn_order = match &action.payload {
+            ActionPayload::DomainAction(da) => da.as_t::<AdvanceStep>().is_some_and(|a| {
+                a.new_step == Step::Starting(StartingStep::ChoosingTurnOrder)
+                    && a.new_substep == SubStep::InProgress
+            }),
+            _ => false,
+        };
+        if !is_choosing_turn_order || !game_state.choose_turn_order {
+            return;
+        }
+
+        let chooser = game_state
+            .turn_order
+            .players()
+            .next()
+            .expect("Every player has been removed");
+        sink.emit_single(ActionPayload::EngineAction(EngineAction::RequestInput(
+            InputRequest {
+                from_player: chooser,
+                kind: InputKind::ChooseStartingPlayer,
+            },
+        )));
+    }
+
+    fn consume_input(
+        &mut self,
+        input: &PlayerInput<Mtg>,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        let player = match input.payload.as_domain_input() {
+            Some(MtgInput::PlayerId(player)) => *player,
+            _ => unreachable!("The engine has already checked that this is a player input"),
+        };
+
+        if !game_state.turn_order.contains(player) {
+            return Err(format!("{} isn't playing in this game", player));
+        }
+
+        emit_action(ActionPayload::DomainAction(
+            Box::new(SetStartingPlayer { player }) as Box<dyn MtgAction>,
+        ));
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        game::{Game, InputError},
+        ids::IdGenerator,
+    };
+
+    use crate::{
+        card::make_card_universe,
+        game::{Mtg, MtgGameBuilder},
+        player_inputs::{InputKind, MtgInput},
+        steps::{StartingStep, Step},
+        test_utils::{input, input_kind, try_input},
+        PlayerId,
+    };
+
+    fn new_game() -> MtgGameBuilder {
+        let deck = vec!["Forest".to_string(); 40];
+        MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_player("carol")
+            .with_card_universe(make_card_universe())
+            .with_deck("alice", deck.clone())
+            .with_deck("bob", deck.clone())
+            .with_deck("carol", deck)
+    }
+
+    fn turn_order(game: &Game<Mtg>) -> Vec<PlayerId> {
+        game.game_state.turn_order.players().collect()
+    }
+
+    #[test]
+    fn test_choose_starting_player() {
+        let mut game = new_game().build().unwrap();
+        game.tick_until_player_input();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let carol = game.game_state.find_player("carol").unwrap();
+
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Starting(StartingStep::ChoosingTurnOrder)
+        );
+        assert_eq!(game.expecting_input_from(), Some(alice));
+        assert_eq!(input_kind(&game), Some(InputKind::ChooseStartingPlayer));
+
+        let mut player_id_gen = IdGenerator::<PlayerId>::new();
+        let stranger = (0..10).map(|_| player_id_gen.next_id()).last().unwrap();
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::PlayerId(stranger)),
+            Err(InputError::Rejected(_))
+        ));
+
+        input(&mut game, alice, MtgInput::PlayerId(bob));
+        assert_eq!(turn_order(&game), vec![bob, carol, alice]);
+
+        // The chosen player is the first to decide on a mulligan, and takes the first turn
+        game.tick_until_player_input();
+        assert_eq!(input_kind(&game), Some(InputKind::Mulligan));
+        assert_eq!(game.expecting_input_from(), Some(bob));
+        assert_eq!(game.game_state.step.active_player, bob);
+    }
+
+    #[test]
+    fn test_fixed_turn_order() {
+        let mut game = new_game()
+            .with_turn_order(&["carol", "alice", "bob"])
+            .build()
+            .unwrap();
+        game.tick_until_player_input();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let carol = game.game_state.find_player("carol").unwrap();
+
+        assert_eq!(turn_order(&game), vec![carol, alice, bob]);
+        assert_eq!(input_kind(&game), Some(InputKind::Mulligan));
+        assert_eq!(game.expecting_input_from(), Some(carol));
+    }
+}
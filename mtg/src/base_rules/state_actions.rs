@@ -3,41 +3,535 @@
 //! See section 704 of the comprehensive rules
 
 use core::{
-    actions::{Action, ActionPayload},
-    BaseObserver,
+    actions::{Action, ActionPayload, EngineAction},
+    ActionSink, BaseObserver,
 };
 
+use super::progression::player_would_receive_priority;
 use crate::{
-    action::{CompositeAction, MtgAction, MtgActionDowncast, SetPriority},
+    action::{CeaseToExist, ChangeObjectZone, CompositeAction, Destroy, MtgAction},
+    card::{CardType, HasType},
     game::Mtg,
+    zone::{AbstractZoneLocation, ZoneLocation},
+    ConcreteObject, Object, ObjectReference,
 };
 
-#[derive(Debug, Clone)]
-pub struct StateBasedActions {}
+fn is_creature(obj: &Object) -> bool {
+    obj.has_type(CardType::Creature)
+}
+
+/// Moves the given permanent from the battlefield to its owner's graveyard
+fn put_into_graveyard(game_state: &Mtg, obj: &Object) -> Box<dyn MtgAction> {
+    Box::new(ChangeObjectZone {
+        obj_ref: ObjectReference::Concrete(ConcreteObject {
+            zone: game_state.shared_zones.battlefield,
+            object: obj.id,
+        }),
+        new_loc: ZoneLocation {
+            zone: game_state.player(obj.owner).graveyard,
+            loc: AbstractZoneLocation::Top,
+        },
+    })
+}
+
+/// Every state-based action that currently applies, as a single simultaneous event (704.3)
+fn applicable_actions(game_state: &Mtg) -> Option<CompositeAction> {
+    let mut components: Vec<Box<dyn MtgAction>> = Vec::new();
+    let battlefield = game_state.battlefield();
+    let stack = game_state.stack();
+
+    // 704.5d If a token is phased out, or is in a zone other than the battlefield, it ceases to
+    // exist.
+    // 704.5e If a copy of a spell is in a zone other than the stack, it ceases to exist. If a copy
+    // of a card is in any zone other than the stack or the battlefield, it ceases to exist.
+    let mut zone_ids = game_state.zones.keys().cloned().collect::<Vec<_>>();
+    zone_ids.sort_unstable();
+    for zone_id in zone_ids {
+        if zone_id == battlefield.id {
+            continue;
+        }
+
+        let ceases_to_exist = game_state.zones[&zone_id]
+            .search(|obj| obj.is_token || (obj.is_copy && zone_id != stack.id));
+        for object in ceases_to_exist {
+            components.push(Box::new(CeaseToExist {
+                object: ConcreteObject {
+                    zone: zone_id,
+                    object,
+                },
+            }));
+        }
+    }
 
-impl StateBasedActions {
-    fn generate_actions(&self, _game_state: &Mtg) -> Option<CompositeAction> {
-        // TODO: actually form a list of state based actions to take
-        println!("Checking for state-based actions");
+    for obj in battlefield.iter().filter(|obj| is_creature(obj)) {
+        let (_, toughness) = game_state.effective_power_toughness(obj.id);
+        if toughness <= 0 {
+            // 704.5f If a creature has toughness 0 or less, it's put into its owner's graveyard.
+            components.push(put_into_graveyard(game_state, obj));
+        } else if obj.damage_marked >= toughness {
+            // 704.5g If a creature has toughness greater than 0, it has damage marked on it, and
+            // the total damage marked on it is greater than or equal to its toughness, that
+            // creature has been dealt lethal damage and is destroyed.
+            components.push(Box::new(Destroy { object: obj.id }));
+        }
+    }
+
+    // 704.5i If a planeswalker has loyalty 0, it's put into its owner's graveyard.
+    for obj in battlefield.iter() {
+        if obj.has_type(CardType::Planeswalker) && obj.loyalty() == 0 {
+            components.push(put_into_graveyard(game_state, obj));
+        }
+    }
+
+    if components.is_empty() {
         None
+    } else {
+        Some(CompositeAction {
+            tag: "state_based_actions",
+            components,
+        })
     }
 }
 
+/// Are there state-based actions that need to be performed before a player can receive priority
+pub(crate) fn state_based_actions_pending(game_state: &Mtg) -> bool {
+    player_would_receive_priority(game_state) && applicable_actions(game_state).is_some()
+}
+
+/// Performs state-based actions whenever a player would receive priority (704.3)
+///
+/// Each time the game quiesces, every applicable state-based action is emitted as a single
+/// simultaneous event. The game quiescing again after that event causes another check, and so on
+/// until no more state-based actions apply. `StepsAndPriority` holds off on granting priority
+/// while there are any state-based actions to perform.
+#[derive(Debug, Clone)]
+pub struct StateBasedActions {}
+
 impl BaseObserver<Mtg> for StateBasedActions {
-    fn propose_replacement(
-        &self,
+    /// Sees each action before any of the other rules do, so that state-based actions are always
+    /// emitted alongside whatever else reacts to the game quiescing
+    fn reaction_order(&self) -> i32 {
+        -1
+    }
+
+    fn observe_action(
+        &mut self,
         action: &Action<Mtg>,
         game_state: &Mtg,
-    ) -> Option<Box<dyn MtgAction>> {
-        if let ActionPayload::DomainAction(a) = &action.payload {
-            if a.is::<SetPriority>() {
-                self.generate_actions(game_state)
-                    .map(|composite| Box::new(composite) as Box<dyn MtgAction>)
-            } else {
-                None
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        if let ActionPayload::EngineAction(EngineAction::NoActions) = action.payload {
+            if !player_would_receive_priority(game_state) {
+                return;
+            }
+
+            if let Some(composite) = applicable_actions(game_state) {
+                sink.emit_single(ActionPayload::DomainAction(
+                    Box::new(composite) as Box<dyn MtgAction>
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use core::game::Game;
+
+    use super::*;
+    use crate::{
+        action::{AddContinuousEffect, BaseMtgAction, DealDamage, MtgActionDowncast},
+        card::{make_card_universe, CardDefinition, CardTypeLine},
+        counter::CounterKind,
+        effect::{ContinuousEffect, EffectKind, PowerToughnessEffect},
+        game::MtgGameBuilder,
+        steps::{Step, SubStep},
+        test_utils::{create_card, create_object, pass_priority},
+        ObjectFilter,
+    };
+
+    fn main_phase() -> Game<Mtg> {
+        MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_applied_before_priority() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            make_card_universe()
+                .find_by_name("Grizzly Bears")
+                .unwrap()
+                .clone(),
+        );
+
+        game.tick_until_player_input();
+        DealDamage {
+            object: bears,
+            amount: 2,
+        }
+        .apply(&mut game.game_state);
+        game.enable_action_history();
+        pass_priority(&mut game, alice);
+
+        // The creature is destroyed before bob is asked for anything
+        let history = game.drain_action_history();
+        let destroyed = history
+            .iter()
+            .position(|a| match &a.payload {
+                ActionPayload::DomainAction(da) => !da.find_all::<Destroy>().is_empty(),
+                _ => false,
+            })
+            .expect("The creature wasn't destroyed");
+        let requested = history
+            .iter()
+            .position(|a| match &a.payload {
+                ActionPayload::EngineAction(EngineAction::RequestInput(request)) => {
+                    request.from_player == bob
+                }
+                _ => false,
+            })
+            .expect("Bob wasn't asked for input");
+        assert!(destroyed < requested);
+        assert_eq!(game.game_state.priority, Some(bob));
+    }
+
+    /// Stand-in for a "whenever one or more creatures die" trigger, recording how many creatures
+    /// died in each event it fires for
+    #[derive(Clone, Debug)]
+    struct DeathTrigger {
+        fired: Rc<RefCell<Vec<usize>>>,
+    }
+
+    impl BaseObserver<Mtg> for DeathTrigger {
+        fn observe_action(
+            &mut self,
+            action: &Action<Mtg>,
+            _game_state: &Mtg,
+            _sink: &mut dyn ActionSink<Mtg>,
+        ) {
+            if let ActionPayload::DomainAction(da) = &action.payload {
+                let deaths = da.find_all::<Destroy>().len();
+                if deaths > 0 {
+                    self.fired.borrow_mut().push(deaths);
+                }
             }
-        } else {
-            None
         }
     }
+
+    #[test]
+    fn test_simultaneous_lethal_damage() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = make_card_universe()
+            .find_by_name("Grizzly Bears")
+            .unwrap()
+            .clone();
+
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        game.attach_observer(Box::new(DeathTrigger {
+            fired: fired.clone(),
+        }));
+
+        let alice_bear = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            bears.clone(),
+        );
+        let bob_bear = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            bears,
+        );
+
+        game.tick_until_player_input();
+        for object in [alice_bear, bob_bear].iter() {
+            DealDamage {
+                object: *object,
+                amount: 2,
+            }
+            .apply(&mut game.game_state);
+        }
+
+        // State-based actions are checked before bob receives priority
+        pass_priority(&mut game, alice);
+        assert_eq!(game.game_state.priority, Some(bob));
+        assert!(game.game_state.battlefield().is_empty());
+        assert_eq!(*fired.borrow(), vec![2]);
+
+        let alice_graveyard = &game.game_state.zones[&game.game_state.player(alice).graveyard];
+        let bob_graveyard = &game.game_state.zones[&game.game_state.player(bob).graveyard];
+        assert_eq!(alice_graveyard.search(|_| true), vec![alice_bear]);
+        assert_eq!(bob_graveyard.search(|_| true), vec![bob_bear]);
+    }
+
+    #[test]
+    fn test_lethal_damage_uses_effective_toughness() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            make_card_universe()
+                .find_by_name("Grizzly Bears")
+                .unwrap()
+                .clone(),
+        );
+        let anthem = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+
+        // "Creatures you control get +1/+1"
+        AddContinuousEffect {
+            effect: ContinuousEffect {
+                source: anthem,
+                affected: ObjectFilter::All(vec![
+                    ObjectFilter::CardType(CardType::Creature),
+                    ObjectFilter::ControlledBy(alice),
+                ]),
+                effect: EffectKind::PowerToughness(PowerToughnessEffect::Modify {
+                    power: 1,
+                    toughness: 1,
+                }),
+                timestamp: game.game_timestamp,
+            },
+        }
+        .apply(&mut game.game_state);
+        game.tick_until_player_input();
+
+        // Two damage isn't lethal to a 3/3
+        DealDamage {
+            object: bears,
+            amount: 2,
+        }
+        .apply(&mut game.game_state);
+        pass_priority(&mut game, alice);
+        assert_eq!(game.game_state.priority, Some(bob));
+        assert!(game.game_state.battlefield().contains(bears));
+
+        // But it is once the anthem is gone
+        game.game_state.battlefield_mut().remove(anthem);
+        pass_priority(&mut game, bob);
+        assert!(!game.game_state.battlefield().contains(bears));
+    }
+
+    #[test]
+    fn test_zero_effective_toughness() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            make_card_universe()
+                .find_by_name("Grizzly Bears")
+                .unwrap()
+                .clone(),
+        );
+        let curse = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+        game.tick_until_player_input();
+
+        // "Creatures your opponents control get -2/-2"
+        AddContinuousEffect {
+            effect: ContinuousEffect {
+                source: curse,
+                affected: ObjectFilter::All(vec![
+                    ObjectFilter::CardType(CardType::Creature),
+                    ObjectFilter::ControlledBy(bob),
+                ]),
+                effect: EffectKind::PowerToughness(PowerToughnessEffect::Modify {
+                    power: -2,
+                    toughness: -2,
+                }),
+                timestamp: game.game_timestamp,
+            },
+        }
+        .apply(&mut game.game_state);
+        pass_priority(&mut game, alice);
+        assert!(!game.game_state.battlefield().contains(bears));
+        let graveyard = &game.game_state.zones[&game.game_state.player(bob).graveyard];
+        assert!(graveyard.contains(bears));
+    }
+
+    /// Records the order in which state-based actions are performed and input is requested
+    #[derive(Clone, Debug)]
+    struct EventLog {
+        events: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl BaseObserver<Mtg> for EventLog {
+        fn observe_action(
+            &mut self,
+            action: &Action<Mtg>,
+            _game_state: &Mtg,
+            _sink: &mut dyn ActionSink<Mtg>,
+        ) {
+            let event = match &action.payload {
+                ActionPayload::EngineAction(EngineAction::RequestInput(_)) => "request_input",
+                ActionPayload::DomainAction(da) => match da.as_t::<CompositeAction>() {
+                    Some(c) if c.tag == "state_based_actions" => "state_based_actions",
+                    _ => return,
+                },
+                _ => return,
+            };
+            self.events.borrow_mut().push(event);
+        }
+    }
+
+    #[test]
+    fn test_sba_before_priority_input() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        game.attach_observer(Box::new(EventLog {
+            events: events.clone(),
+        }));
+
+        let mut shrunken_bears = make_card_universe()
+            .find_by_name("Grizzly Bears")
+            .unwrap()
+            .clone();
+        shrunken_bears.toughness = Some(0);
+        let creature = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            shrunken_bears,
+        );
+
+        game.tick_until_player_input();
+        assert_eq!(game.expecting_input_from(), Some(alice));
+        assert_eq!(
+            *events.borrow(),
+            vec!["state_based_actions", "request_input"]
+        );
+
+        let graveyard = &game.game_state.zones[&game.game_state.player(alice).graveyard];
+        assert_eq!(graveyard.search(|_| true), vec![creature]);
+    }
+
+    #[test]
+    fn test_token_ceases_to_exist() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let graveyard = game.game_state.player(alice).graveyard;
+
+        let token = game.game_state.object_id_gen.next_id();
+        game.game_state.battlefield_mut().insert(
+            Object {
+                is_token: true,
+                ..Object::new(token, alice)
+            },
+            AbstractZoneLocation::Undefined,
+        );
+
+        ChangeObjectZone {
+            obj_ref: ObjectReference::Concrete(ConcreteObject {
+                zone: battlefield,
+                object: token,
+            }),
+            new_loc: ZoneLocation {
+                zone: graveyard,
+                loc: AbstractZoneLocation::Top,
+            },
+        }
+        .apply(&mut game.game_state);
+        assert_eq!(
+            game.game_state.zones[&graveyard].search(|_| true),
+            vec![token]
+        );
+
+        game.tick_until_player_input();
+        assert_eq!(game.expecting_input_from(), Some(alice));
+        assert!(game.game_state.zones[&graveyard].is_empty());
+        assert!(game.game_state.battlefield().is_empty());
+    }
+
+    #[test]
+    fn test_zero_loyalty_planeswalker() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        let definition = CardDefinition {
+            name: "Test Planeswalker".to_string(),
+            type_line: CardTypeLine {
+                card_types: vec![CardType::Planeswalker],
+                ..Default::default()
+            },
+            loyalty: Some(3),
+            ..Default::default()
+        };
+        let planeswalker = game.game_state.object_id_gen.next_id();
+        let mut obj = Object::new(planeswalker, bob).with_definition(Rc::new(definition));
+        obj.counters.insert(CounterKind::Loyalty, 3);
+        game.game_state
+            .battlefield_mut()
+            .insert(obj, AbstractZoneLocation::Undefined);
+
+        game.tick_until_player_input();
+        DealDamage {
+            object: planeswalker,
+            amount: 2,
+        }
+        .apply(&mut game.game_state);
+        pass_priority(&mut game, alice);
+        assert_eq!(
+            game.game_state
+                .battlefield()
+                .iter()
+                .map(|o| o.loyalty())
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+
+        DealDamage {
+            object: planeswalker,
+            amount: 2,
+        }
+        .apply(&mut game.game_state);
+        pass_priority(&mut game, bob);
+        assert!(game.game_state.battlefield().is_empty());
+        let graveyard = &game.game_state.zones[&game.game_state.player(bob).graveyard];
+        assert_eq!(graveyard.search(|_| true), vec![planeswalker]);
+    }
 }
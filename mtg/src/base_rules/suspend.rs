@@ -0,0 +1,264 @@
+//! Observer that implements the triggered abilities of suspend
+//!
+//! See section 702.62 of the comprehensive rules
+
+use core::{
+    actions::{Action, ActionPayload},
+    ActionSink, BaseObserver,
+};
+
+use crate::{
+    action::{AdvanceStep, CastSpell, MtgAction, MtgActionDowncast, RemoveCounter, TriggerAbility},
+    counter::CounterKind,
+    game::Mtg,
+    steps::{BeginningStep, Step, SubStep},
+    ConcreteObject,
+};
+
+/// Removes time counters from suspended cards, and casts them once the last one is removed
+///
+/// 702.62a [...] "At the beginning of your upkeep, if this card is suspended, remove a time
+///     counter from it," and "When the last time counter is removed from this card, if it's
+///     exiled, you may play it without paying its mana cost if able. If you don't, it remains
+///     exiled. If you cast a creature spell this way, it gains haste until you lose control of
+///     the spell or the permanent it becomes."
+///
+/// Whether a card has suspend is checked each time either ability would trigger, so a card that
+/// loses suspend part way through stays exiled with its remaining time counters. The card is
+/// always cast, as there's no way for its owner to decline yet, and haste isn't supported.
+/// Choosing targets for a spell cast this way isn't supported either, so it's cast without any.
+#[derive(Clone, Debug)]
+pub struct SuspendedCards {}
+
+impl BaseObserver<Mtg> for SuspendedCards {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let da = match &action.payload {
+            ActionPayload::DomainAction(da) => da,
+            _ => return,
+        };
+        let exile = game_state.shared_zones.exile;
+
+        let is_upkeep_starting = da.as_t::<AdvanceStep>().is_some_and(|a| {
+            a.new_step == Step::Beginning(BeginningStep::Upkeep)
+                && a.new_substep == SubStep::InProgress
+        });
+        if is_upkeep_starting {
+            let active_player = game_state.step.active_player;
+            for obj in game_state.exile().iter() {
+                if obj.owner != active_player || !game_state.is_suspended(obj.id) {
+                    continue;
+                }
+                sink.emit_single(ActionPayload::DomainAction(Box::new(TriggerAbility {
+                    controller: obj.owner,
+                    effect: Box::new(RemoveCounter {
+                        object: ConcreteObject {
+                            zone: exile,
+                            object: obj.id,
+                        },
+                        kind: CounterKind::Time,
+                        amount: 1,
+                    }),
+                })
+                    as Box<dyn MtgAction>));
+            }
+        }
+
+        for removed in da.find_all::<RemoveCounter>() {
+            if removed.kind != CounterKind::Time || removed.object.zone != exile {
+                continue;
+            }
+            let obj = match game_state.exile().get(removed.object.object) {
+                Some(obj) => obj,
+                None => continue,
+            };
+            if obj.characteristics().suspend.is_none() || obj.counter_count(CounterKind::Time) > 0 {
+                continue;
+            }
+
+            sink.emit_single(ActionPayload::DomainAction(Box::new(TriggerAbility {
+                controller: obj.owner,
+                effect: Box::new(CastSpell {
+                    player: obj.owner,
+                    object: obj.id,
+                    zone: exile,
+                    x: None,
+                    targets: Vec::new(),
+                }),
+            })
+                as Box<dyn MtgAction>));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{actions::ActionPayload, game::Game};
+
+    use crate::{
+        action::{
+            AddCounter, AddMana, BaseMtgAction, ChangeObjectZone, MtgActionDowncast, SuspendCard,
+        },
+        card::{CardDefinition, CardType, CardTypeLine, Suspend},
+        counter::CounterKind,
+        game::{Mtg, MtgGameBuilder},
+        mana::{BaseManaCostComponent, Color, ManaConstraint, ManaCost, ManaCostComponent},
+        player_inputs::{MtgInput, PriorityInput, SpecialAction},
+        steps::{BeginningStep, Step, SubStep},
+        test_utils::{always_pass_agents, create_card, create_object, input},
+        zone::AbstractZoneLocation,
+        ConcreteObject,
+    };
+
+    /// A sorcery with "Suspend 2—{R}"
+    fn suspend_sorcery() -> CardDefinition {
+        CardDefinition {
+            name: "Delayed Blast".to_string(),
+            type_line: CardTypeLine {
+                card_types: vec![CardType::Sorcery],
+                ..Default::default()
+            },
+            suspend: Some(Suspend {
+                time_counters: 2,
+                cost: ManaCost {
+                    components: vec![ManaCostComponent::Base(BaseManaCostComponent::Single(
+                        ManaConstraint::Color(Color::Red),
+                    ))],
+                },
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// A game in alice's first main phase, where both players have a few cards in their library
+    fn new_game() -> Game<Mtg> {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        for player in game.game_state.turn_order.players().collect::<Vec<_>>() {
+            let library = game.game_state.player(player).library;
+            for _ in 0..5 {
+                create_object(&mut game, player, library, AbstractZoneLocation::Top);
+            }
+        }
+        game.tick_until_player_input();
+        game
+    }
+
+    #[test]
+    fn test_suspend_card() {
+        let mut game = new_game();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let card = create_card(
+            &mut game,
+            alice,
+            hand,
+            AbstractZoneLocation::Undefined,
+            suspend_sorcery(),
+        );
+        AddMana {
+            player: alice,
+            color: Some(Color::Red),
+            amount: 1,
+            source: None,
+        }
+        .apply(&mut game.game_state);
+
+        input(
+            &mut game,
+            alice,
+            MtgInput::PriorityInput(PriorityInput::SpecialAction(SpecialAction::SuspendCard)),
+        );
+        game.enable_action_history();
+        input(&mut game, alice, MtgInput::ObjectId(card));
+        game.tick_until_player_input();
+
+        // Exiling the card can be observed like any other zone change
+        let exiled_by = game
+            .drain_action_history()
+            .into_iter()
+            .filter_map(|action| match action.payload {
+                ActionPayload::DomainAction(da) => Some(da),
+                _ => None,
+            })
+            .find(|da| da.is::<SuspendCard>())
+            .expect("The card wasn't suspended");
+        assert!(exiled_by
+            .find_all::<ChangeObjectZone>()
+            .iter()
+            .any(|a| a.new_loc.zone == game.game_state.shared_zones.exile));
+
+        let state = &game.game_state;
+        let exiled = state.exile().get(card).unwrap();
+        assert_eq!(exiled.counter_count(CounterKind::Time), 2);
+        assert!(state.is_suspended(card));
+        assert!(state.player(alice).mana_pool.is_empty());
+        assert_eq!(state.priority, Some(alice));
+
+        // One time counter is removed during alice's next upkeep
+        game.run_with_agents(always_pass_agents(&game), |state| {
+            state.turn_number == 3 && state.step.step == Step::Beginning(BeginningStep::Draw)
+        });
+        let exiled = game.game_state.exile().get(card).unwrap();
+        assert_eq!(exiled.counter_count(CounterKind::Time), 1);
+
+        // The last is removed during the upkeep after that, and the card is cast straight away
+        let mut cast_during = None;
+        game.run_with_agents(always_pass_agents(&game), |state| {
+            if cast_during.is_none() && state.stack().contains(card) {
+                cast_during = Some((state.turn_number, state.step.step));
+            }
+            state.turn_number == 5 && state.step.step == Step::Beginning(BeginningStep::Draw)
+        });
+        assert_eq!(
+            cast_during,
+            Some((5, Step::Beginning(BeginningStep::Upkeep)))
+        );
+        let graveyard = game.game_state.player(alice).graveyard;
+        assert!(game.game_state.zones[&graveyard].contains(card));
+    }
+
+    #[test]
+    fn test_card_without_suspend_keeps_counters() {
+        let mut game = new_game();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let exile = game.game_state.shared_zones.exile;
+
+        // A card that has lost suspend since it was exiled is no longer suspended
+        let card = create_card(
+            &mut game,
+            alice,
+            exile,
+            AbstractZoneLocation::Undefined,
+            CardDefinition {
+                suspend: None,
+                ..suspend_sorcery()
+            },
+        );
+        AddCounter {
+            object: ConcreteObject {
+                zone: exile,
+                object: card,
+            },
+            kind: CounterKind::Time,
+            amount: 1,
+        }
+        .apply(&mut game.game_state);
+        assert!(!game.game_state.is_suspended(card));
+
+        game.run_with_agents(always_pass_agents(&game), |state| {
+            state.turn_number == 3 && state.step.step == Step::Beginning(BeginningStep::Draw)
+        });
+        let exiled = game.game_state.exile().get(card).unwrap();
+        assert_eq!(exiled.counter_count(CounterKind::Time), 1);
+    }
+}
@@ -0,0 +1,533 @@
+//! Observer that puts triggered abilities on the stack at the right time
+//!
+//! See section 603 of the comprehensive rules
+
+use core::{
+    actions::{Action, ActionPayload, EngineAction, InputRequest},
+    ids::{ObjectId, PlayerId},
+    ActionSink, BaseObserver, PlayerInput,
+};
+
+use super::{
+    progression::{acting_for, player_would_receive_priority},
+    state_actions::state_based_actions_pending,
+};
+use crate::{
+    action::{
+        ChangeObjectZone, Destroy, ForgetLastKnownInformation, MtgAction, MtgActionDowncast,
+        PutTriggerOnStack, RemoveDelayedTrigger, TriggerAbility,
+    },
+    game::Mtg,
+    player_inputs::{InputKind, MtgInput},
+    ObjectReference,
+};
+
+/// Are there triggered abilities that are about to be put on the stack
+///
+/// 117.5 Each time a player would receive priority, the game first performs all applicable
+///     state-based actions. Then triggered abilities are put on the stack. These steps repeat
+///     until no further state-based actions are performed and no abilities trigger. Then the
+///     player who would have received priority does so.
+pub(crate) fn triggers_pending(game_state: &Mtg) -> bool {
+    !game_state.pending_triggers.is_empty() && player_would_receive_priority(game_state)
+}
+
+/// The first player in APNAP order with pending triggered abilities, and those abilities
+fn next_controller(game_state: &Mtg) -> Option<(PlayerId, Vec<ObjectId>)> {
+    let first = acting_for(game_state, game_state.step.active_player);
+    (0..game_state.turn_order.len())
+        .map(|n| game_state.turn_order.nth_after(first, n))
+        .map(|player| {
+            let abilities = game_state
+                .pending_triggers
+                .iter()
+                .filter(|a| a.controller == player)
+                .map(|a| a.id)
+                .collect::<Vec<_>>();
+            (player, abilities)
+        })
+        .find(|(_, abilities)| !abilities.is_empty())
+}
+
+fn put_on_stack(ability: ObjectId) -> ActionPayload<Mtg> {
+    ActionPayload::DomainAction(Box::new(PutTriggerOnStack { ability }) as Box<dyn MtgAction>)
+}
+
+/// Puts pending triggered abilities on the stack one at a time, in APNAP order
+///
+/// 603.3b If multiple players have triggered abilities that have triggered since the last time a
+///     player received priority, each player, in APNAP order, puts triggered abilities they control
+///     on the stack in any order they choose.
+///
+/// A player controlling more than one pending ability is asked which to put on the stack next.
+#[derive(Clone, Debug, Default)]
+pub struct TriggeredAbilities {
+    /// The player currently being asked to order their triggered abilities
+    ordering: Option<PlayerId>,
+}
+
+impl TriggeredAbilities {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BaseObserver<Mtg> for TriggeredAbilities {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        if let ActionPayload::EngineAction(EngineAction::NoActions) = action.payload {
+            if !triggers_pending(game_state) || state_based_actions_pending(game_state) {
+                return;
+            }
+
+            let (player, abilities) =
+                next_controller(game_state).expect("There are pending triggers");
+            if abilities.len() == 1 {
+                sink.emit_single(put_on_stack(abilities[0]));
+            } else {
+                self.ordering = Some(player);
+                sink.emit_single(ActionPayload::EngineAction(EngineAction::RequestInput(
+                    InputRequest {
+                        from_player: player,
+                        kind: InputKind::OrderTriggers,
+                    },
+                )));
+            }
+        }
+    }
+
+    fn consume_input(
+        &mut self,
+        input: &PlayerInput<Mtg>,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        let player = self
+            .ordering
+            .expect("Received input when not expecting one");
+        assert_eq!(player, input.source);
+
+        let ability = match input.payload.as_domain_input() {
+            Some(MtgInput::ObjectId(ability)) => *ability,
+            _ => unreachable!("The engine has already checked that this is an object input"),
+        };
+
+        let is_own_pending_ability = game_state
+            .pending_triggers
+            .iter()
+            .any(|a| a.id == ability && a.controller == player);
+        if !is_own_pending_ability {
+            return Err(format!(
+                "{:?} is not a pending triggered ability controlled by {}",
+                ability, player
+            ));
+        }
+
+        self.ordering = None;
+        emit_action(put_on_stack(ability));
+        emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        Ok(())
+    }
+}
+
+/// Triggers each delayed triggered ability the next time its trigger event occurs
+///
+/// 603.7c [...] A delayed triggered ability will trigger only once—the next time its trigger
+///     event occurs—unless it has a stated duration.
+///
+/// The ability is removed as it triggers, which also closes the window for any effect action that
+/// would have stopped it.
+#[derive(Clone, Debug, Default)]
+pub struct DelayedTriggers {}
+
+impl BaseObserver<Mtg> for DelayedTriggers {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let da = match &action.payload {
+            ActionPayload::DomainAction(da) => da,
+            _ => return,
+        };
+
+        for trigger in &game_state.delayed_triggers {
+            if trigger.condition.count_matches(&**da, game_state) == 0 {
+                continue;
+            }
+
+            sink.emit_composite(
+                "delayed_trigger",
+                vec![
+                    ActionPayload::DomainAction(Box::new(RemoveDelayedTrigger {
+                        trigger: trigger.id,
+                    }) as Box<dyn MtgAction>),
+                    ActionPayload::DomainAction(Box::new(TriggerAbility {
+                        controller: trigger.controller,
+                        effect: trigger.effect.clone(),
+                    }) as Box<dyn MtgAction>),
+                ],
+            );
+        }
+    }
+}
+
+/// Forgets the last known information of permanents once their leaving the battlefield has been
+/// seen by every observer
+///
+/// Leaves-the-battlefield abilities trigger based on the last known information of the permanent
+/// (603.10a), which is only needed while they're checked against the action that moved it.
+/// Keeping it any longer would let a later action that doesn't move the object, eg destroying a
+/// card that's already in the graveyard, look like the creature dying again.
+#[derive(Clone, Debug, Default)]
+pub struct LastKnownInformationExpiry {}
+
+impl BaseObserver<Mtg> for LastKnownInformationExpiry {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let da = match &action.payload {
+            ActionPayload::DomainAction(da) => da,
+            _ => return,
+        };
+
+        let battlefield = game_state.shared_zones.battlefield;
+        let destroyed = da.find_all::<Destroy>().into_iter().map(|a| a.object);
+        let moved = da
+            .find_all::<ChangeObjectZone>()
+            .into_iter()
+            .filter_map(|a| match a.obj_ref {
+                ObjectReference::Concrete(c) if c.zone == battlefield => Some(c.object),
+                _ => None,
+            });
+        let objects = destroyed
+            .chain(moved)
+            .filter(|object| game_state.last_known_information.contains_key(object))
+            .collect::<Vec<_>>();
+        if !objects.is_empty() {
+            sink.emit_single(ActionPayload::DomainAction(
+                Box::new(ForgetLastKnownInformation { objects }) as Box<dyn MtgAction>,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use core::{
+        actions::{Action, ActionPayload},
+        game::{Game, InputError},
+        ActionSink, BaseObserver,
+    };
+
+    use crate::{
+        action::{
+            AddMana, BaseMtgAction, CastSpell, CreateDelayedTrigger, DrawCard, GainLife, MtgAction,
+            MtgActionDowncast, RemoveDelayedTrigger, TakeEffectAction, TriggerAbility,
+        },
+        card::{CardDefinition, CardType, CardTypeLine},
+        game::{Mtg, MtgGameBuilder},
+        mana::{BaseManaCostComponent, Color, ManaConstraint, ManaCost, ManaCostComponent},
+        player_inputs::{InputKind, MtgInput, PriorityInput, SpecialAction},
+        steps::{BeginningStep, Step, SubStep},
+        test_utils::{
+            always_pass_agents, create_card, create_object, input, input_kind, pass_priority,
+            try_input,
+        },
+        trigger::TriggerCondition,
+        zone::AbstractZoneLocation,
+    };
+
+    /// "Whenever a player casts a spell, they draw a card"
+    ///
+    /// Records the size of the stack and the number of pending triggers just after each of its
+    /// abilities triggers.
+    #[derive(Clone, Debug)]
+    struct CastTrigger {
+        seen: Rc<RefCell<Vec<(usize, usize)>>>,
+    }
+
+    impl BaseObserver<Mtg> for CastTrigger {
+        fn observe_action(
+            &mut self,
+            action: &Action<Mtg>,
+            game_state: &Mtg,
+            sink: &mut dyn ActionSink<Mtg>,
+        ) {
+            if let ActionPayload::DomainAction(da) = &action.payload {
+                if let Some(cast) = da.as_t::<CastSpell>() {
+                    sink.emit_single(ActionPayload::DomainAction(Box::new(TriggerAbility {
+                        controller: cast.player,
+                        effect: Box::new(DrawCard {
+                            player: cast.player,
+                        }),
+                    })
+                        as Box<dyn MtgAction>));
+                } else if da.is::<TriggerAbility>() {
+                    self.seen
+                        .borrow_mut()
+                        .push((game_state.stack().len(), game_state.pending_triggers.len()));
+                }
+            }
+        }
+    }
+
+    fn main_phase() -> Game<Mtg> {
+        MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_trigger_waits_for_priority() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let library = game.game_state.player(alice).library;
+        create_object(&mut game, alice, library, AbstractZoneLocation::Top);
+        let spell = create_card(
+            &mut game,
+            alice,
+            hand,
+            AbstractZoneLocation::Undefined,
+            CardDefinition {
+                name: "Test Instant".to_string(),
+                type_line: CardTypeLine {
+                    card_types: vec![CardType::Instant],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        game.attach_observer(Box::new(CastTrigger { seen: seen.clone() }));
+        game.tick_until_player_input();
+
+        input(
+            &mut game,
+            alice,
+            MtgInput::PriorityInput(PriorityInput::CastSpell),
+        );
+        input(&mut game, alice, MtgInput::ObjectId(spell));
+        game.tick_until_player_input();
+
+        // When the ability triggered, only the spell was on the stack
+        assert_eq!(*seen.borrow(), vec![(1, 1)]);
+
+        // By the time alice receives priority, the ability is on the stack above the spell
+        let stack = game.game_state.stack();
+        let stack_order = stack.iter_in_order().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(stack_order.len(), 2);
+        assert_eq!(stack_order[0], spell);
+        assert!(game.game_state.pending_triggers.is_empty());
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+        assert_eq!(game.game_state.priority, Some(alice));
+
+        // The ability resolves first, leaving the spell on the stack
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        let state = &game.game_state;
+        assert_eq!(state.stack().search(|_| true), vec![spell]);
+        assert_eq!(state.zones[&hand].len(), 1);
+    }
+
+    #[test]
+    fn test_triggers_in_apnap_order() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        for &player in &[bob, alice] {
+            TriggerAbility {
+                controller: player,
+                effect: Box::new(DrawCard { player }),
+            }
+            .apply(&mut game.game_state);
+        }
+        assert!(game.game_state.stack().is_empty());
+
+        game.tick_until_player_input();
+
+        // The active player's abilities go on the stack first, so the non-active player's
+        // abilities resolve first
+        let controllers = game
+            .game_state
+            .stack()
+            .iter_in_order()
+            .map(|o| o.controller)
+            .collect::<Vec<_>>();
+        assert_eq!(controllers, vec![alice, bob]);
+        assert_eq!(game.game_state.priority, Some(alice));
+    }
+
+    #[test]
+    fn test_player_orders_own_triggers() {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        for &player in &[alice, bob, alice] {
+            TriggerAbility {
+                controller: player,
+                effect: Box::new(DrawCard { player }),
+            }
+            .apply(&mut game.game_state);
+        }
+        let [first, bobs, second] = [0, 1, 2].map(|n| game.game_state.pending_triggers[n].id);
+
+        game.tick_until_player_input();
+        assert_eq!(input_kind(&game), Some(InputKind::OrderTriggers));
+        assert_eq!(game.expecting_input_from(), Some(alice));
+
+        // Alice can only choose between her own pending abilities
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::ObjectId(bobs)),
+            Err(InputError::Rejected(_))
+        ));
+
+        input(&mut game, alice, MtgInput::ObjectId(second));
+        game.tick_until_player_input();
+
+        // Alice's remaining ability follows on automatically, then bob's goes on top
+        let stack_order = game
+            .game_state
+            .stack()
+            .iter_in_order()
+            .map(|o| o.id)
+            .collect::<Vec<_>>();
+        assert_eq!(stack_order, vec![second, first, bobs]);
+        assert!(game.game_state.pending_triggers.is_empty());
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+        assert_eq!(game.game_state.priority, Some(alice));
+    }
+
+    /// A game in alice's main phase where she has created Quenchable Fire's delayed trigger:
+    /// "[...] 3 damage to that player at the beginning of your next upkeep step unless that
+    /// player pays {U} before that step"
+    ///
+    /// Here the delayed trigger waits for bob's upkeep and has him lose 3 life.
+    fn game_with_delayed_trigger() -> Game<Mtg> {
+        let mut game = main_phase();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let library = game.game_state.player(bob).library;
+        create_object(&mut game, bob, library, AbstractZoneLocation::Top);
+
+        CreateDelayedTrigger {
+            controller: alice,
+            condition: TriggerCondition::BeginningOfStep {
+                step: Step::Beginning(BeginningStep::Upkeep),
+                player: bob,
+            },
+            effect: Box::new(GainLife {
+                player: bob,
+                amount: -3,
+            }),
+            prevention: Some((
+                bob,
+                ManaCost {
+                    components: vec![ManaCostComponent::Base(BaseManaCostComponent::Single(
+                        ManaConstraint::Color(Color::Blue),
+                    ))],
+                },
+            )),
+        }
+        .apply(&mut game.game_state);
+        game.tick_until_player_input();
+        game
+    }
+
+    /// Plays on until bob's draw step, with both players passing whenever they can
+    fn run_until_bobs_draw(game: &mut Game<Mtg>) {
+        let bob = game.game_state.find_player("bob").unwrap();
+        game.run_with_agents(always_pass_agents(game), |state| {
+            state.step.active_player == bob
+                && state.step.step == Step::Beginning(BeginningStep::Draw)
+        });
+    }
+
+    #[test]
+    fn test_delayed_trigger() {
+        let mut game = game_with_delayed_trigger();
+        let bob = game.game_state.find_player("bob").unwrap();
+        assert_eq!(game.game_state.delayed_triggers.len(), 1);
+        assert_eq!(game.game_state.effect_actions.len(), 1);
+
+        run_until_bobs_draw(&mut game);
+        assert_eq!(game.game_state.player(bob).life_total, 17);
+
+        // It triggers only once, and can no longer be stopped once it has
+        assert!(game.game_state.delayed_triggers.is_empty());
+        assert!(game.game_state.effect_actions.is_empty());
+    }
+
+    #[test]
+    fn test_effect_action_cancels_delayed_trigger() {
+        let mut game = game_with_delayed_trigger();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let action = game.game_state.effect_actions[0].id;
+        let effect_action =
+            MtgInput::PriorityInput(PriorityInput::SpecialAction(SpecialAction::EffectAction));
+
+        // The action is only available to bob
+        assert!(matches!(
+            try_input(&mut game, alice, effect_action),
+            Err(InputError::Rejected(_))
+        ));
+        pass_priority(&mut game, alice);
+
+        AddMana {
+            player: bob,
+            color: Some(Color::Blue),
+            amount: 1,
+            source: None,
+        }
+        .apply(&mut game.game_state);
+        input(&mut game, bob, effect_action);
+        assert_eq!(input_kind(&game), Some(InputKind::EffectAction));
+        game.enable_action_history();
+        input(&mut game, bob, MtgInput::AbilityId(action));
+        game.tick_until_player_input();
+
+        // The action's effect can be observed like any other action
+        let taken = game
+            .drain_action_history()
+            .into_iter()
+            .filter_map(|action| match action.payload {
+                ActionPayload::DomainAction(da) => Some(da),
+                _ => None,
+            })
+            .find(|da| da.is::<TakeEffectAction>())
+            .expect("The effect action wasn't taken");
+        assert_eq!(taken.find_all::<RemoveDelayedTrigger>().len(), 1);
+
+        let state = &game.game_state;
+        assert!(state.delayed_triggers.is_empty());
+        assert!(state.effect_actions.is_empty());
+        assert!(state.player(bob).mana_pool.is_empty());
+
+        // Taking the action doesn't use the stack or pass priority
+        assert!(state.stack().is_empty());
+        assert_eq!(state.priority, Some(bob));
+
+        run_until_bobs_draw(&mut game);
+        assert_eq!(game.game_state.player(bob).life_total, 20);
+    }
+}
@@ -0,0 +1,394 @@
+//! Observers that implement the turn-based actions that happen at the start of particular steps
+//!
+//! See section 703 of the comprehensive rules
+
+use core::{
+    actions::{Action, ActionPayload, EngineAction, InputRequest},
+    ids::PlayerId,
+    ActionSink, BaseObserver, PlayerInput,
+};
+
+use crate::{
+    action::{
+        AdvanceStep, ClearSummoningSickness, DiscardCard, DrawCard, MtgAction, MtgActionDowncast,
+        RemoveAllDamage, ResetTurnCounts, Untap,
+    },
+    game::Mtg,
+    player_inputs::{InputKind, MtgInput},
+    steps::{BeginningStep, EndStep, Step, SubStep},
+};
+
+/// The number of cards the given player must discard to get down to their maximum hand size
+fn excess_hand_size(game_state: &Mtg, player: PlayerId) -> usize {
+    let player = game_state.player(player);
+    match player.max_hand_size {
+        Some(max) => game_state.zones[&player.hand].len().saturating_sub(max),
+        None => 0,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TurnBasedActions {
+    /// The number of cards the active player has left to discard during the cleanup step
+    pending_discards: usize,
+}
+
+impl Default for TurnBasedActions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TurnBasedActions {
+    pub fn new() -> Self {
+        Self {
+            pending_discards: 0,
+        }
+    }
+}
+
+impl BaseObserver<Mtg> for TurnBasedActions {
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let advance_step = match &action.payload {
+            ActionPayload::DomainAction(da) => match da.as_t::<AdvanceStep>() {
+                Some(a) if a.new_substep == SubStep::InProgress => a,
+                _ => return,
+            },
+            _ => return,
+        };
+
+        // 800.4i If a player leaves the game during their turn, that turn continues to its
+        //     completion without an active player. [...]
+        // There is then nobody to draw or discard.
+        let active_present = game_state
+            .turn_order
+            .contains(advance_step.new_active_player);
+
+        match advance_step.new_step {
+            Step::Beginning(BeginningStep::Untap) => {
+                sink.emit_single(ActionPayload::DomainAction(
+                    Box::new(ResetTurnCounts {}) as Box<dyn MtgAction>
+                ));
+                sink.emit_single(ActionPayload::DomainAction(
+                    Box::new(ClearSummoningSickness {
+                        player: advance_step.new_active_player,
+                    }) as Box<dyn MtgAction>,
+                ));
+
+                // 502.3 Third, the active player determines which permanents they control will
+                //     untap. Then they untap them all simultaneously. This turn-based action
+                //     doesn't use the stack.
+                let tapped = game_state
+                    .battlefield()
+                    .search(|obj| obj.tapped && obj.controller == advance_step.new_active_player);
+                if !tapped.is_empty() {
+                    let untaps = tapped
+                        .into_iter()
+                        .map(|object| {
+                            ActionPayload::DomainAction(
+                                Box::new(Untap { object }) as Box<dyn MtgAction>
+                            )
+                        })
+                        .collect();
+                    sink.emit_composite("untap_step", untaps);
+                }
+            }
+            Step::Beginning(BeginningStep::Draw) => {
+                // 504.1 First, the active player draws a card. This turn-based action doesn't use
+                //     the stack.
+                // 103.8a In a two-player game, the player who plays first skips the draw step of
+                //     their first turn.
+                if !active_present || (game_state.skip_first_draw && game_state.turn_number == 1) {
+                    return;
+                }
+
+                sink.emit_single(ActionPayload::DomainAction(Box::new(DrawCard {
+                    player: advance_step.new_active_player,
+                })
+                    as Box<dyn MtgAction>));
+            }
+            Step::End(EndStep::Cleanup) => {
+                // 514.1 First, if the active player's hand contains more cards than their maximum
+                //     hand size (normally seven), they discard enough cards to reduce their hand
+                //     size to that number. This turn-based action doesn't use the stack.
+                self.pending_discards = if active_present {
+                    excess_hand_size(game_state, advance_step.new_active_player)
+                } else {
+                    0
+                };
+                if self.pending_discards > 0 {
+                    sink.emit_single(ActionPayload::EngineAction(EngineAction::RequestInput(
+                        InputRequest {
+                            from_player: advance_step.new_active_player,
+                            kind: InputKind::Discard,
+                        },
+                    )));
+                } else {
+                    sink.emit_single(ActionPayload::DomainAction(
+                        Box::new(RemoveAllDamage {}) as Box<dyn MtgAction>
+                    ));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn consume_input(
+        &mut self,
+        input: &PlayerInput<Mtg>,
+        game_state: &Mtg,
+        emit_action: &mut dyn FnMut(ActionPayload<Mtg>),
+    ) -> Result<(), String> {
+        assert!(self.pending_discards > 0);
+
+        let object = match input.payload.as_domain_input() {
+            Some(MtgInput::ObjectId(object)) => *object,
+            _ => unreachable!("The engine has already checked that this is an object input"),
+        };
+
+        let hand = game_state.player(input.source).hand;
+        if !game_state.zones[&hand].contains(object) {
+            return Err(format!("{:?} is not in {}'s hand", object, input.source));
+        }
+
+        emit_action(ActionPayload::DomainAction(Box::new(DiscardCard {
+            player: input.source,
+            object,
+        })
+            as Box<dyn MtgAction>));
+
+        self.pending_discards -= 1;
+        if self.pending_discards == 0 {
+            // 514.2 happens once the active player has finished discarding
+            emit_action(ActionPayload::DomainAction(
+                Box::new(RemoveAllDamage {}) as Box<dyn MtgAction>
+            ));
+            emit_action(ActionPayload::EngineAction(EngineAction::EndInput));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::game::{Game, InputError};
+
+    use crate::{
+        card::make_card_universe,
+        game::{Mtg, MtgGameBuilder},
+        player_inputs::{InputKind, MtgInput},
+        steps::{BeginningStep, EndStep, Step, SubStep},
+        test_utils::{create_card, create_object, input, input_kind, pass_priority, try_input},
+        zone::AbstractZoneLocation,
+    };
+
+    fn game_in_upkeep(skip_first_draw: bool) -> Game<Mtg> {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_skip_first_draw(skip_first_draw)
+            .with_initial_step(
+                "alice",
+                Step::Beginning(BeginningStep::Upkeep),
+                SubStep::InProgress,
+            )
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+
+        let alice = game.game_state.find_player("alice").unwrap();
+        let library = game.game_state.player(alice).library;
+        for _ in 0..3 {
+            create_object(&mut game, alice, library, AbstractZoneLocation::Top);
+        }
+
+        game.tick_until_player_input();
+        game
+    }
+
+    #[test]
+    fn test_draw_step_draws_card() {
+        let mut game = game_in_upkeep(false);
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+
+        let state = &game.game_state;
+        assert_eq!(state.step.step, Step::Beginning(BeginningStep::Draw));
+        assert_eq!(state.zones[&state.player(alice).hand].len(), 1);
+        assert_eq!(state.zones[&state.player(alice).library].len(), 2);
+    }
+
+    #[test]
+    fn test_first_draw_skipped() {
+        let mut game = game_in_upkeep(true);
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+
+        let state = &game.game_state;
+        assert_eq!(state.step.step, Step::Beginning(BeginningStep::Draw));
+        assert_eq!(state.zones[&state.player(alice).hand].len(), 0);
+    }
+
+    /// A game about to enter alice's cleanup step, with the given number of cards in alice's hand
+    /// and the given maximum hand size
+    fn game_before_cleanup(hand_size: usize, max_hand_size: Option<usize>) -> Game<Mtg> {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::End(EndStep::EndOfTurn), SubStep::Ending)
+            .with_max_hand_size("alice", max_hand_size)
+            .build()
+            .unwrap();
+
+        let alice = game.game_state.find_player("alice").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        for _ in 0..hand_size {
+            create_object(&mut game, alice, hand, AbstractZoneLocation::Undefined);
+        }
+
+        game.tick_until_player_input();
+        game
+    }
+
+    #[test]
+    fn test_zero_max_hand_size_discards_whole_hand() {
+        let mut game = game_before_cleanup(3, Some(0));
+        let alice = game.game_state.find_player("alice").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let graveyard = game.game_state.player(alice).graveyard;
+
+        assert_eq!(game.game_state.step.step, Step::End(EndStep::Cleanup));
+        assert_eq!(input_kind(&game), Some(InputKind::Discard));
+
+        for object in game.game_state.zones[&hand].search(|_| true) {
+            input(&mut game, alice, MtgInput::ObjectId(object));
+        }
+
+        assert!(game.game_state.zones[&hand].is_empty());
+        assert_eq!(game.game_state.zones[&graveyard].len(), 3);
+        assert_eq!(input_kind(&game), None);
+    }
+
+    #[test]
+    fn test_no_max_hand_size() {
+        let game = game_before_cleanup(10, None);
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+
+        // Nothing to do during the cleanup step, so the game moves straight on to bob's turn
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Beginning(BeginningStep::Untap)
+        );
+        assert_eq!(game.game_state.step.active_player, bob);
+        assert_ne!(input_kind(&game), Some(InputKind::Discard));
+        assert_eq!(game.game_state.zones[&hand].len(), 10);
+    }
+
+    #[test]
+    fn test_discard_down_to_seven() {
+        let mut game = game_before_cleanup(8, Some(7));
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+
+        assert_eq!(game.game_state.step.step, Step::End(EndStep::Cleanup));
+        assert_eq!(input_kind(&game), Some(InputKind::Discard));
+        let discarded = game.game_state.hand_contents(alice)[0];
+        input(&mut game, alice, MtgInput::ObjectId(discarded));
+        game.tick_until_player_input();
+
+        assert_eq!(game.game_state.zones[&hand].len(), 7);
+        assert!(!game.game_state.zones[&hand].contains(discarded));
+        assert_eq!(game.game_state.step.active_player, bob);
+    }
+
+    #[test]
+    fn test_cleanup_repeats_after_state_based_actions() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::End(EndStep::EndOfTurn), SubStep::Ending)
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let battlefield = game.game_state.shared_zones.battlefield;
+
+        // A creature with 0 toughness, which state-based actions don't see until the cleanup step
+        // begins. Damage wouldn't do, as that's removed before they're checked (514.2).
+        let mut shrunken_bears = make_card_universe()
+            .find_by_name("Grizzly Bears")
+            .unwrap()
+            .clone();
+        shrunken_bears.toughness = Some(0);
+        let creature = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            shrunken_bears,
+        );
+        game.tick_until_player_input();
+
+        // 514.3a The creature dies, and then the active player gets priority during the cleanup
+        //     step
+        assert_eq!(game.game_state.step.step, Step::End(EndStep::Cleanup));
+        assert!(!game.game_state.battlefield().contains(creature));
+        assert_eq!(input_kind(&game), Some(InputKind::Priority));
+        assert_eq!(game.expecting_input_from(), Some(alice));
+
+        // Alice ends up with too many cards while players have priority, so has to discard during
+        // the cleanup step that follows
+        for _ in 0..8 {
+            create_object(&mut game, alice, hand, AbstractZoneLocation::Undefined);
+        }
+        pass_priority(&mut game, alice);
+        pass_priority(&mut game, bob);
+        assert_eq!(game.game_state.step.step, Step::End(EndStep::Cleanup));
+        assert_eq!(input_kind(&game), Some(InputKind::Discard));
+
+        let discarded = game.game_state.hand_contents(alice)[0];
+        input(&mut game, alice, MtgInput::ObjectId(discarded));
+        game.tick_until_player_input();
+        assert_eq!(game.game_state.zones[&hand].len(), 7);
+        assert_eq!(game.game_state.step.active_player, bob);
+    }
+
+    #[test]
+    fn test_discard_to_reduced_max_hand_size() {
+        let mut game = game_before_cleanup(4, Some(2));
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let bobs_hand = game.game_state.player(bob).hand;
+        let bobs_card = create_object(&mut game, bob, bobs_hand, AbstractZoneLocation::Undefined);
+
+        // Only cards from alice's own hand can be discarded
+        assert!(matches!(
+            try_input(&mut game, alice, MtgInput::ObjectId(bobs_card)),
+            Err(InputError::Rejected(_))
+        ));
+
+        let cards = game.game_state.zones[&hand].search(|_| true);
+        input(&mut game, alice, MtgInput::ObjectId(cards[0]));
+        assert_eq!(input_kind(&game), Some(InputKind::Discard));
+        input(&mut game, alice, MtgInput::ObjectId(cards[1]));
+        assert_eq!(input_kind(&game), None);
+        assert_eq!(game.game_state.zones[&hand].search(|_| true), cards[2..]);
+    }
+}
@@ -1,6 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::mana::{Color, ManaCost};
+use crate::{
+    mana::{BaseManaCostComponent, Color, ManaConstraint, ManaCost, ManaCostComponent},
+    target::TargetSpec,
+};
 
 /// 205.2a
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -66,10 +69,7 @@ pub enum LandType {
 impl LandType {
     pub fn is_basic(&self) -> bool {
         use LandType::*;
-        match self {
-            Forest | Island | Mountain | Plains | Swamp => true,
-            _ => false,
-        }
+        matches!(self, Forest | Island | Mountain | Plains | Swamp)
     }
 }
 
@@ -507,17 +507,17 @@ impl From<PlanarType> for SubType {
 impl SubType {
     /// Is this subtype correlated with the given card type
     pub fn correlated(&self, card_type: CardType) -> bool {
-        match (card_type, self) {
-            (CardType::Artifact, SubType::Artifact(_)) => true,
-            (CardType::Creature, SubType::Creature(_)) => true,
-            (CardType::Enchantment, SubType::Enchantment(_)) => true,
-            (CardType::Land, SubType::Land(_)) => true,
-            (CardType::Plane, SubType::Planar(_)) => true,
-            (CardType::Planeswalker, SubType::Planeswalker(_)) => true,
-            (CardType::Sorcery, SubType::Spell(_)) => true,
-            (CardType::Instant, SubType::Spell(_)) => true,
-            _ => false,
-        }
+        matches!(
+            (card_type, self),
+            (CardType::Artifact, SubType::Artifact(_))
+                | (CardType::Creature, SubType::Creature(_))
+                | (CardType::Enchantment, SubType::Enchantment(_))
+                | (CardType::Land, SubType::Land(_))
+                | (CardType::Plane, SubType::Planar(_))
+                | (CardType::Planeswalker, SubType::Planeswalker(_))
+                | (CardType::Sorcery, SubType::Spell(_))
+                | (CardType::Instant, SubType::Spell(_))
+        )
     }
 }
 
@@ -549,19 +549,19 @@ pub trait HasType<T> {
 
 impl HasType<SuperType> for CardTypeLine {
     fn has_type(&self, t: SuperType) -> bool {
-        self.super_types.iter().any(|st| *st == t)
+        self.super_types.contains(&t)
     }
 }
 
 impl HasType<CardType> for CardTypeLine {
     fn has_type(&self, t: CardType) -> bool {
-        self.card_types.iter().any(|ct| *ct == t)
+        self.card_types.contains(&t)
     }
 }
 
 impl HasType<SubType> for CardTypeLine {
     fn has_type(&self, t: SubType) -> bool {
-        self.sub_types.iter().any(|st| *st == t)
+        self.sub_types.contains(&t)
     }
 }
 
@@ -593,7 +593,7 @@ pub struct CardRules {
 /// A literal definition of a card as it would appear in real life
 ///
 /// This structure only contains information that is able to
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct CardDefinition {
     pub name: String,
     pub mana_cost: ManaCost,
@@ -605,6 +605,164 @@ pub struct CardDefinition {
     pub loyalty: Option<i32>,
     pub hand_modifier: Option<i32>,
     pub life_modifier: Option<i32>,
+
+    /// The cost to turn this card face up if it has morph, or None if it doesn't
+    ///
+    /// 702.37a Morph is a static ability that functions in any zone from which you could play the
+    ///     card it's on, and the morph effect works any time the card is face down. "Morph [cost]"
+    ///     means "You may cast this card as a 2/2 face-down creature with no text, no name, no
+    ///     subtypes, and no mana cost by paying {3} rather than paying its mana cost."
+    pub morph_cost: Option<ManaCost>,
+
+    /// The suspend ability of this card, if it has one (702.62)
+    pub suspend: Option<Suspend>,
+
+    /// The keyword abilities printed on this card that don't take any parameters
+    pub keywords: Vec<KeywordAbility>,
+
+    /// The targets this card requires as a spell, one for each instance of the word "target"
+    ///
+    /// 115.1a An instant or sorcery spell is targeted if its spell ability identifies something
+    ///     it will affect by using the phrase "target [something]," [...]
+    pub targets: Vec<TargetSpec>,
+
+    /// What this card does as an instant or sorcery spell resolves, if anything
+    pub spell_effect: Option<SpellEffect>,
+}
+
+/// The effect of an instant or sorcery spell's spell ability
+///
+/// Only ever applied to the spell's targets that are still legal as it resolves (608.2b).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpellEffect {
+    /// Deals the given amount of damage to each of the spell's targets, eg "Shock deals 2 damage
+    /// to any target."
+    DamageTargets(i32),
+}
+
+/// The evergreen keyword abilities that are fully described by their name (702.1)
+///
+/// Keywords that take a parameter, such as enchant, equip, protection and ward, aren't included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeywordAbility {
+    /// 702.2b A creature with toughness greater than 0 that's been dealt damage by a source with
+    ///     deathtouch since the last time state-based actions were checked is destroyed as a
+    ///     state-based action.
+    Deathtouch,
+
+    /// 702.3b A creature with defender can't attack.
+    Defender,
+
+    /// 702.4a Double strike is a static ability that modifies the rules for the combat damage
+    ///     step.
+    DoubleStrike,
+
+    /// 702.7a First strike is a static ability that modifies the rules for the combat damage
+    ///     step.
+    FirstStrike,
+
+    /// 702.8a [...] "Flash" means "You may play this card any time you could cast an instant."
+    Flash,
+
+    /// 702.9b A creature with flying can't be blocked except by creatures with flying and/or
+    ///     reach. [...]
+    Flying,
+
+    /// 702.10b If a creature has haste, it can attack even if it hasn't been controlled by its
+    ///     controller continuously since their most recent turn began.
+    Haste,
+
+    /// 702.11b "Hexproof" on a permanent means "This permanent can't be the target of spells or
+    ///     abilities your opponents control."
+    Hexproof,
+
+    /// 702.12b A permanent with indestructible can't be destroyed. [...]
+    Indestructible,
+
+    /// 702.15b Damage dealt by a source with lifelink causes that source's controller, or its
+    ///     owner if it has no controller, to gain that much life [...]
+    Lifelink,
+
+    /// 702.110b A creature with menace can't be blocked except by two or more creatures.
+    Menace,
+
+    /// 702.17b A creature with flying can't be blocked except by creatures with flying and/or
+    ///     reach.
+    Reach,
+
+    /// 702.19a Trample is a static ability that modifies the rules for assigning an attacking
+    ///     creature's combat damage. [...]
+    Trample,
+
+    /// 702.20b Attacking doesn't cause creatures with vigilance to tap.
+    Vigilance,
+}
+
+/// The suspend keyword ability, eg "Suspend 4—{1}{R}"
+///
+/// 702.62a Suspend is a keyword that represents three abilities. [...] "Suspend N—[cost]" means
+///     "If you could begin to cast this card by putting it onto the stack from your hand, you may
+///     pay [cost] and exile it with N time counters on it. This action doesn't use the stack," and
+///     "At the beginning of your upkeep, if this card is suspended, remove a time counter from it,"
+///     and "When the last time counter is removed from this card, if it's exiled, you may play it
+///     without paying its mana cost if able. [...]"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suspend {
+    /// The number of time counters the card is exiled with
+    pub time_counters: i32,
+
+    /// The cost to pay to exile the card
+    pub cost: ManaCost,
+}
+
+impl CardDefinition {
+    /// The characteristics of a face-down permanent
+    ///
+    /// 708.2a If a face-up permanent is turned face down by a spell or ability that doesn't list
+    ///     any characteristics for that object, it becomes a 2/2 face-down creature with no text,
+    ///     no name, no subtypes, and no mana cost.
+    ///
+    /// Having no mana cost or color indicator, it's also colorless.
+    pub fn face_down() -> Self {
+        Self {
+            type_line: CardTypeLine {
+                card_types: vec![CardType::Creature],
+                ..Default::default()
+            },
+            power: Some(2),
+            toughness: Some(2),
+            ..Default::default()
+        }
+    }
+
+    /// The colors of this card
+    ///
+    /// 105.2 An object can be one or more of the five colors, or it can be no color at all. An
+    ///     object is the color or colors of the mana symbols in its mana cost, regardless of the
+    ///     color of its frame. An object's color or colors may also be defined by a color
+    ///     indicator or a characteristic-defining ability.
+    ///
+    /// Characteristic-defining abilities aren't supported yet.
+    pub fn colors(&self) -> HashSet<Color> {
+        let mut colors = self.mana_cost.colors();
+        colors.extend(self.color_indicator.iter().cloned());
+        colors
+    }
+
+    /// The color identity of this card, as used by the Commander variant
+    ///
+    /// 903.4 The Commander variant uses color identity to determine what cards can be in a
+    ///     deck with a certain commander. The color identity of a card is the color or colors of
+    ///     any mana symbols in that card's mana cost or rules text, plus any colors defined by its
+    ///     characteristic-defining abilities or color indicator.
+    pub fn color_identity(&self) -> HashSet<Color> {
+        let mut identity = self.colors();
+        for symbol in self.text.split('{').skip(1) {
+            let symbol = symbol.split('}').next().unwrap_or_default();
+            identity.extend(symbol.chars().filter_map(Color::from_symbol));
+        }
+        identity
+    }
 }
 
 impl<T> HasType<T> for CardDefinition
@@ -688,6 +846,43 @@ pub fn make_card_universe() -> CardUniverse {
         ..Default::default()
     });
 
+    universe.add_card(CardDefinition {
+        name: "Grizzly Bears".to_string(),
+        type_line: CardTypeLine {
+            card_types: vec![CardType::Creature],
+            sub_types: vec![SubType::Creature(CreatureType::Bear)],
+            ..Default::default()
+        },
+        power: Some(2),
+        toughness: Some(2),
+        ..Default::default()
+    });
+
+    universe.add_card(CardDefinition {
+        name: "Serra Angel".to_string(),
+        mana_cost: ManaCost {
+            components: vec![
+                ManaCostComponent::Base(BaseManaCostComponent::ConcreteGeneric(3)),
+                ManaCostComponent::Base(BaseManaCostComponent::Single(ManaConstraint::Color(
+                    Color::White,
+                ))),
+                ManaCostComponent::Base(BaseManaCostComponent::Single(ManaConstraint::Color(
+                    Color::White,
+                ))),
+            ],
+        },
+        type_line: CardTypeLine {
+            card_types: vec![CardType::Creature],
+            sub_types: vec![SubType::Creature(CreatureType::Angel)],
+            ..Default::default()
+        },
+        text: "Flying, vigilance".to_string(),
+        power: Some(4),
+        toughness: Some(4),
+        keywords: vec![KeywordAbility::Flying, KeywordAbility::Vigilance],
+        ..Default::default()
+    });
+
     universe
 }
 
@@ -715,4 +910,48 @@ mod tests {
         assert!(!forest.has_type(CardType::Creature));
         assert!(!forest.has_type(CreatureType::Wizard));
     }
+
+    #[test]
+    fn test_colors_from_mana_cost() {
+        // {2}{U}
+        let card = CardDefinition {
+            mana_cost: ManaCost {
+                components: vec![
+                    ManaCostComponent::Base(BaseManaCostComponent::ConcreteGeneric(2)),
+                    ManaCostComponent::Base(BaseManaCostComponent::Single(ManaConstraint::Color(
+                        Color::Blue,
+                    ))),
+                ],
+            },
+            ..Default::default()
+        };
+        assert_eq!(card.colors(), HashSet::from([Color::Blue]));
+    }
+
+    #[test]
+    fn test_colors_from_color_indicator() {
+        // eg the back face of a double-faced card
+        let card = CardDefinition {
+            color_indicator: vec![Color::Red],
+            ..Default::default()
+        };
+        assert_eq!(card.colors(), HashSet::from([Color::Red]));
+
+        let forest = make_card_universe().find_by_name("Forest").unwrap().clone();
+        assert!(forest.colors().is_empty());
+    }
+
+    #[test]
+    fn test_color_identity() {
+        let card = CardDefinition {
+            color_indicator: vec![Color::Red],
+            text: "{T}: Add {G}. {2}{W/P}: Draw a card.".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(card.colors(), HashSet::from([Color::Red]));
+        assert_eq!(
+            card.color_identity(),
+            HashSet::from([Color::Red, Color::Green, Color::White])
+        );
+    }
 }
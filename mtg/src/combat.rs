@@ -0,0 +1,53 @@
+use core::ids::{ObjectId, PlayerId};
+
+/// Something that a creature can attack
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttackTarget {
+    Player(PlayerId),
+    Planeswalker(ObjectId),
+}
+
+/// A creature that has been declared as an attacker this combat
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Attacker {
+    pub object: ObjectId,
+    pub target: AttackTarget,
+}
+
+/// A creature that has been declared as a blocker this combat
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Blocker {
+    pub object: ObjectId,
+
+    /// The attacking creature that is being blocked
+    pub attacker: ObjectId,
+}
+
+/// The state of the current combat phase
+///
+/// Empty outside of combat.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CombatState {
+    pub attackers: Vec<Attacker>,
+    pub blockers: Vec<Blocker>,
+
+    /// Whether the active player finished declaring attackers without declaring any
+    ///
+    /// 508.8 If no creatures are declared as attackers or put onto the battlefield attacking, skip
+    ///     the declare blockers and combat damage steps.
+    pub no_attackers_declared: bool,
+
+    /// The attackers and blockers that had first strike or double strike as the first-strike
+    /// combat damage step began, or None if this combat hasn't had one (510.4)
+    pub first_strikers: Option<Vec<ObjectId>>,
+}
+
+impl CombatState {
+    pub fn is_attacking(&self, object: ObjectId) -> bool {
+        self.attackers.iter().any(|a| a.object == object)
+    }
+
+    pub fn is_blocking(&self, object: ObjectId) -> bool {
+        self.blockers.iter().any(|b| b.object == object)
+    }
+}
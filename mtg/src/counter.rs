@@ -0,0 +1,25 @@
+/// The kinds of counter that can be placed on an object
+///
+/// See section 122 of the comprehensive rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CounterKind {
+    /// 122.1a A +1/+1 counter on a permanent gives it +1/+1
+    PlusOnePlusOne,
+
+    /// 122.1a A -1/-1 counter on a permanent gives it -1/-1
+    Minus,
+
+    /// 122.1e The number of loyalty counters on a planeswalker is its loyalty
+    Loyalty,
+
+    /// A charge counter, which has no effect of its own but may be referenced by the object's
+    /// abilities
+    Charge,
+
+    /// 702.62a A time counter, which a suspended card has one of removed at the beginning of each
+    /// of its owner's upkeeps
+    Time,
+
+    /// Any other named counter that only matters to the abilities that reference it
+    Generic(&'static str),
+}
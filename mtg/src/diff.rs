@@ -0,0 +1,178 @@
+//! Structured differences between two states of a game, for tooling and tests
+//!
+//! Unlike events, diffs describe what changed in the game state rather than why it changed.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use core::ids::{ObjectId, PlayerId, ZoneId};
+
+use crate::{counter::CounterKind, game::Mtg, Object};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MtgDiff {
+    /// The given player's life total changed
+    LifeChanged {
+        player: PlayerId,
+        from: i32,
+        to: i32,
+    },
+
+    /// The given object moved between zones
+    ///
+    /// `from` is None if the object didn't previously exist, and `to` is None if it no longer
+    /// exists.
+    ObjectMoved {
+        object: ObjectId,
+        from: Option<ZoneId>,
+        to: Option<ZoneId>,
+    },
+
+    /// The number of counters of the given kind on the given object changed
+    CountersChanged {
+        object: ObjectId,
+        kind: CounterKind,
+        from: i32,
+        to: i32,
+    },
+}
+
+fn objects_by_id(game_state: &Mtg) -> BTreeMap<ObjectId, (ZoneId, &Object)> {
+    game_state
+        .zones
+        .values()
+        .flat_map(|zone| zone.iter().map(move |obj| (obj.id, (zone.id, obj))))
+        .collect()
+}
+
+/// Every change between the two given states of the game, ordered by the player or object changed
+pub fn diff(before: &Mtg, after: &Mtg) -> Vec<MtgDiff> {
+    let mut diffs = Vec::new();
+
+    let mut players = after.players.keys().collect::<Vec<_>>();
+    players.sort_unstable();
+    for player in players {
+        let from = before.players.get(player).map(|p| p.life_total);
+        let to = after.players[player].life_total;
+        if let Some(from) = from.filter(|from| *from != to) {
+            diffs.push(MtgDiff::LifeChanged {
+                player: *player,
+                from,
+                to,
+            });
+        }
+    }
+
+    let before_objects = objects_by_id(before);
+    let after_objects = objects_by_id(after);
+    let object_ids = before_objects
+        .keys()
+        .chain(after_objects.keys())
+        .collect::<BTreeSet<_>>();
+    for object in object_ids {
+        let before_obj = before_objects.get(object);
+        let after_obj = after_objects.get(object);
+
+        let from = before_obj.map(|(zone, _)| *zone);
+        let to = after_obj.map(|(zone, _)| *zone);
+        if from != to {
+            diffs.push(MtgDiff::ObjectMoved {
+                object: *object,
+                from,
+                to,
+            });
+        }
+
+        if let (Some((_, before_obj)), Some((_, after_obj))) = (before_obj, after_obj) {
+            let kinds = before_obj
+                .counters
+                .keys()
+                .chain(after_obj.counters.keys())
+                .collect::<BTreeSet<_>>();
+            for kind in kinds {
+                let from = before_obj.counters.get(kind).copied().unwrap_or(0);
+                let to = after_obj.counters.get(kind).copied().unwrap_or(0);
+                if from != to {
+                    diffs.push(MtgDiff::CountersChanged {
+                        object: *object,
+                        kind: *kind,
+                        from,
+                        to,
+                    });
+                }
+            }
+        }
+    }
+
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        actions::{Action, ActionPayload},
+        game::TickResult,
+        Controller,
+    };
+
+    use super::*;
+    use crate::{
+        action::{BaseMtgAction, DrawCard, GainLife, MtgAction},
+        game::MtgGameBuilder,
+        test_utils::create_object,
+        zone::AbstractZoneLocation,
+    };
+
+    #[test]
+    fn test_draw_card_diff() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let library = game.game_state.player(alice).library;
+        let hand = game.game_state.player(alice).hand;
+        let card = create_object(&mut game, alice, library, AbstractZoneLocation::Top);
+
+        game.action_queue.add(Action {
+            payload: ActionPayload::DomainAction(
+                Box::new(DrawCard { player: alice }) as Box<dyn MtgAction>
+            ),
+            source: game.self_id,
+            controller: Controller::Game,
+            id: game.action_id_gen.next_id(),
+            generated_at: game.game_timestamp,
+            original: None,
+        });
+        let (res, diffs) = game.tick_with_diff();
+        assert!(matches!(res, TickResult::Ticked(_)));
+        assert_eq!(
+            diffs,
+            vec![MtgDiff::ObjectMoved {
+                object: card,
+                from: Some(library),
+                to: Some(hand),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_life_diff() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_starting_life_total(20)
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let before = game.game_state.clone();
+        GainLife {
+            player: alice,
+            amount: 3,
+        }
+        .apply(&mut game.game_state);
+        assert_eq!(
+            diff(&before, &game.game_state),
+            vec![MtgDiff::LifeChanged {
+                player: alice,
+                from: 20,
+                to: 23,
+            }]
+        );
+    }
+}
@@ -0,0 +1,321 @@
+//! Continuous effects that modify the characteristics of objects
+//!
+//! Effects are registered with the game state and applied whenever a characteristic is queried,
+//! rather than by modifying an object's printed values. See section 613 of the comprehensive rules.
+
+use core::game::GameTimestamp;
+
+use crate::{card::KeywordAbility, ObjectFilter, ObjectId};
+
+/// What a continuous effect does to the objects it affects, grouped by the layer it applies in
+/// (613.1)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectKind {
+    /// 613.1f Layer 6: Ability-adding effects, keyword counters, ability-removing effects, and
+    ///     effects that say an object can't have an ability are applied.
+    GainKeyword(KeywordAbility),
+
+    /// Removes a keyword ability in layer 6, whether printed or granted by an earlier effect
+    LoseKeyword(KeywordAbility),
+
+    /// 613.1g Layer 7: Power- and/or toughness-changing effects are applied.
+    PowerToughness(PowerToughnessEffect),
+}
+
+/// An effect that modifies a creature's power and/or toughness, in one of the sublayers of layer 7
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerToughnessEffect {
+    /// 613.4a Layer 7a: Effects from characteristic-defining abilities that define power and/or
+    ///     toughness are applied.
+    CharacteristicDefining { power: i32, toughness: i32 },
+
+    /// 613.4b Layer 7b: Effects that set power and/or toughness to a specific number or value are
+    ///     applied.
+    Set { power: i32, toughness: i32 },
+
+    /// 613.4c Layer 7c: Effects and counters that modify power and/or toughness (but don't set
+    ///     power and/or toughness to a specific number or value) are applied.
+    Modify { power: i32, toughness: i32 },
+
+    /// 613.4d Layer 7d: Effects that switch a creature's power and toughness are applied.
+    Switch,
+}
+
+impl PowerToughnessEffect {
+    /// The position of this effect's sublayer within layer 7
+    pub fn sublayer(&self) -> u8 {
+        match self {
+            PowerToughnessEffect::CharacteristicDefining { .. } => 0,
+            PowerToughnessEffect::Set { .. } => 1,
+            PowerToughnessEffect::Modify { .. } => 2,
+            PowerToughnessEffect::Switch => 3,
+        }
+    }
+
+    pub fn apply(&self, (power, toughness): (i32, i32)) -> (i32, i32) {
+        match *self {
+            PowerToughnessEffect::CharacteristicDefining {
+                power: p,
+                toughness: t,
+            }
+            | PowerToughnessEffect::Set {
+                power: p,
+                toughness: t,
+            } => (p, t),
+            PowerToughnessEffect::Modify {
+                power: p,
+                toughness: t,
+            } => (power + p, toughness + t),
+            PowerToughnessEffect::Switch => (toughness, power),
+        }
+    }
+}
+
+/// A continuous effect generated by a static ability of some permanent
+///
+/// 611.3a A continuous effect generated by a static ability isn't "locked in"; it applies at any
+///     given moment to whatever its text indicates.
+/// 611.3b The effect applies at all times that the permanent generating it is on the battlefield.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContinuousEffect {
+    /// The permanent whose static ability generates this effect
+    pub source: ObjectId,
+
+    /// The objects affected by this effect
+    pub affected: ObjectFilter,
+
+    pub effect: EffectKind,
+
+    /// When this effect was created, used to order effects within the same sublayer
+    ///
+    /// 613.7a A continuous effect generated by a static ability has the same timestamp as the
+    ///     object the static ability is on, or the timestamp of the effect that created the
+    ///     ability, whichever is later.
+    pub timestamp: GameTimestamp,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::game::Game;
+
+    use crate::{
+        action::{AddContinuousEffect, AddCounter, BaseMtgAction, Destroy},
+        card::{make_card_universe, CardType},
+        counter::CounterKind,
+        game::{Mtg, MtgGameBuilder},
+        steps::{Step, SubStep},
+        test_utils::{create_card, create_object},
+        zone::AbstractZoneLocation,
+        ConcreteObject,
+    };
+
+    #[test]
+    fn test_anthem() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            make_card_universe()
+                .find_by_name("Grizzly Bears")
+                .unwrap()
+                .clone(),
+        );
+        let anthem = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+        assert_eq!(game.game_state.effective_power_toughness(bears), (2, 2));
+
+        // "Creatures you control get +1/+1"
+        AddContinuousEffect {
+            effect: ContinuousEffect {
+                source: anthem,
+                affected: ObjectFilter::All(vec![
+                    ObjectFilter::CardType(CardType::Creature),
+                    ObjectFilter::ControlledBy(alice),
+                ]),
+                effect: EffectKind::PowerToughness(PowerToughnessEffect::Modify {
+                    power: 1,
+                    toughness: 1,
+                }),
+                timestamp: game.game_timestamp,
+            },
+        }
+        .apply(&mut game.game_state);
+        assert_eq!(game.game_state.effective_power_toughness(bears), (3, 3));
+        assert_eq!(game.game_state.effective_power_toughness(anthem), (0, 0));
+
+        // The printed values are left alone
+        assert_eq!(
+            game.game_state.battlefield().get(bears).unwrap().power(),
+            Some(2)
+        );
+
+        // The effect ends once its source leaves the battlefield
+        Destroy { object: anthem }.apply(&mut game.game_state);
+        assert_eq!(game.game_state.effective_power_toughness(bears), (2, 2));
+    }
+
+    #[test]
+    fn test_keywords() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let angel = create_card(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            make_card_universe()
+                .find_by_name("Serra Angel")
+                .unwrap()
+                .clone(),
+        );
+        let source = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+
+        let has_keyword = |game: &Game<Mtg>, keyword| {
+            let obj = game.game_state.battlefield().get(angel).unwrap();
+            obj.has_keyword(&game.game_state, keyword)
+        };
+        assert!(has_keyword(&game, KeywordAbility::Flying));
+        assert!(has_keyword(&game, KeywordAbility::Vigilance));
+        assert!(!has_keyword(&game, KeywordAbility::Haste));
+
+        // "Creatures you control have haste", and "Creatures lose flying"
+        for effect in [
+            EffectKind::GainKeyword(KeywordAbility::Haste),
+            EffectKind::LoseKeyword(KeywordAbility::Flying),
+        ] {
+            AddContinuousEffect {
+                effect: ContinuousEffect {
+                    source,
+                    affected: ObjectFilter::CardType(CardType::Creature),
+                    effect,
+                    timestamp: game.game_timestamp,
+                },
+            }
+            .apply(&mut game.game_state);
+        }
+        assert!(has_keyword(&game, KeywordAbility::Haste));
+        assert!(!has_keyword(&game, KeywordAbility::Flying));
+        assert!(has_keyword(&game, KeywordAbility::Vigilance));
+
+        // The source isn't a creature, so isn't affected itself
+        let obj = game.game_state.battlefield().get(source).unwrap();
+        assert!(!obj.has_keyword(&game.game_state, KeywordAbility::Haste));
+
+        Destroy { object: source }.apply(&mut game.game_state);
+        assert!(has_keyword(&game, KeywordAbility::Flying));
+        assert!(!has_keyword(&game, KeywordAbility::Haste));
+    }
+
+    #[test]
+    fn test_sublayer_order() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let creature = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+        let source = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+
+        // Registered out of layer order, to check they're applied in layer order regardless
+        for effect in [
+            PowerToughnessEffect::Switch,
+            PowerToughnessEffect::Modify {
+                power: 3,
+                toughness: 0,
+            },
+            PowerToughnessEffect::Set {
+                power: 0,
+                toughness: 4,
+            },
+        ] {
+            AddContinuousEffect {
+                effect: ContinuousEffect {
+                    source,
+                    affected: ObjectFilter::Any,
+                    effect: EffectKind::PowerToughness(effect),
+                    timestamp: game.game_timestamp,
+                },
+            }
+            .apply(&mut game.game_state);
+        }
+
+        // Set to 0/4, then +3/+0 and a +1/+1 counter to 4/5, then switched to 5/4
+        AddCounter {
+            object: ConcreteObject {
+                zone: battlefield,
+                object: creature,
+            },
+            kind: CounterKind::PlusOnePlusOne,
+            amount: 1,
+        }
+        .apply(&mut game.game_state);
+        assert_eq!(game.game_state.effective_power_toughness(creature), (5, 4));
+    }
+
+    #[test]
+    fn test_timestamp_order() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let creature = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+        let source = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+
+        let earlier = game.game_timestamp;
+        game.tick_until_player_input();
+        let later = game.game_timestamp;
+        assert!(earlier < later);
+
+        let set = |power, toughness, timestamp| AddContinuousEffect {
+            effect: ContinuousEffect {
+                source,
+                affected: ObjectFilter::Any,
+                effect: EffectKind::PowerToughness(PowerToughnessEffect::Set { power, toughness }),
+                timestamp,
+            },
+        };
+
+        // Registered in the opposite order to their timestamps, so the later effect should win
+        // regardless of the order they were added in
+        set(4, 4, later).apply(&mut game.game_state);
+        set(1, 1, earlier).apply(&mut game.game_state);
+        assert_eq!(game.game_state.effective_power_toughness(creature), (4, 4));
+    }
+}
@@ -0,0 +1,167 @@
+//! Human-facing events, derived from the actions applied to the game
+//!
+//! These are intended for presenting what has happened in a game to players, eg in a game log.
+//! Observers implementing the rules should keep reacting to actions directly.
+
+use core::ids::{ObjectId, PlayerId};
+
+use crate::{
+    action::{CastSpell, Destroy, DiscardCard, DrawCard, LandPlayed, MtgAction, MtgActionDowncast},
+    game::Mtg,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MtgEvent {
+    /// The given player drew a card
+    CardDrawn { player: PlayerId },
+
+    /// The given player discarded the given card
+    CardDiscarded { player: PlayerId, card: ObjectId },
+
+    /// The given player played a land
+    LandPlayed { player: PlayerId },
+
+    /// The given player cast the given spell
+    SpellCast { player: PlayerId, spell: ObjectId },
+
+    /// The given permanent was destroyed
+    PermanentDestroyed { object: ObjectId },
+}
+
+/// The events the given action, and any of its components, will cause when applied to the given
+/// game state
+///
+/// Actions that would quietly do nothing, eg drawing from an empty library, cause no events.
+/// Components are all checked against the state before any of them are applied, which suits the
+/// simultaneous events they're used for.
+pub fn action_events(action: &dyn MtgAction, game_state: &Mtg) -> Vec<MtgEvent> {
+    let mut events = Vec::new();
+
+    if let Some(a) = action.as_t::<DrawCard>() {
+        let library = game_state.player(a.player).library;
+        if !game_state.zones[&library].is_empty() {
+            events.push(MtgEvent::CardDrawn { player: a.player });
+        }
+    } else if let Some(a) = action.as_t::<DiscardCard>() {
+        let hand = game_state.player(a.player).hand;
+        if game_state.zones[&hand].contains(a.object) {
+            events.push(MtgEvent::CardDiscarded {
+                player: a.player,
+                card: a.object,
+            });
+        }
+    } else if let Some(a) = action.as_t::<LandPlayed>() {
+        events.push(MtgEvent::LandPlayed { player: a.player });
+    } else if let Some(a) = action.as_t::<CastSpell>() {
+        if game_state.zones[&a.zone].contains(a.object) {
+            events.push(MtgEvent::SpellCast {
+                player: a.player,
+                spell: a.object,
+            });
+        }
+    } else if let Some(a) = action.as_t::<Destroy>() {
+        if game_state.battlefield().contains(a.object) {
+            events.push(MtgEvent::PermanentDestroyed { object: a.object });
+        }
+    }
+
+    for component in action.components() {
+        events.extend(action_events(&**component, game_state));
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use core::actions::ActionPayload;
+
+    use super::*;
+    use crate::{
+        card::{CardDefinition, CardType, CardTypeLine},
+        game::MtgGameBuilder,
+        player_inputs::{MtgInput, PriorityInput},
+        steps::{Step, SubStep},
+        test_utils::{create_card, create_object, input},
+        zone::AbstractZoneLocation,
+    };
+
+    #[test]
+    fn test_spell_cast_event() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        game.enable_event_log();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let instant = create_card(
+            &mut game,
+            alice,
+            hand,
+            AbstractZoneLocation::Undefined,
+            CardDefinition {
+                name: "Test Instant".to_string(),
+                type_line: CardTypeLine {
+                    card_types: vec![CardType::Instant],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        game.tick_until_player_input();
+        assert!(game.drain_events().is_empty());
+
+        input(
+            &mut game,
+            alice,
+            MtgInput::PriorityInput(PriorityInput::CastSpell),
+        );
+        input(&mut game, alice, MtgInput::ObjectId(instant));
+        game.tick_until_player_input();
+
+        let events = game
+            .drain_events()
+            .into_iter()
+            .map(|e| e.event)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![MtgEvent::SpellCast {
+                player: alice,
+                spell: instant,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_no_event_without_effect() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .build()
+            .unwrap();
+        game.enable_event_log();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let library = game.game_state.player(alice).library;
+        let draw = || {
+            ActionPayload::DomainAction(Box::new(DrawCard { player: alice }) as Box<dyn MtgAction>)
+        };
+
+        // Drawing from an empty library quietly does nothing
+        game.perform_action(draw());
+        assert!(game.drain_events().is_empty());
+
+        create_object(&mut game, alice, library, AbstractZoneLocation::Top);
+        game.perform_action(draw());
+        let events = game
+            .drain_events()
+            .into_iter()
+            .map(|e| e.event)
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![MtgEvent::CardDrawn { player: alice }]);
+    }
+}
@@ -1,35 +1,283 @@
-use std::collections::HashMap;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
 
 use core::{
-    game::{ActionQueue, GameDomain, GameTimestamp},
-    ids::{IdGenerator, ObserverId, PlayerId, ZoneId},
+    game::{ActionQueue, GameDomain, GameDomainDiff, GameTimestamp},
+    ids::{AbilityId, IdGenerator, ObjectId, ObserverId, PlayerId, ZoneId},
+    rng::GameRng,
+    turn_order::TurnOrder,
+    PlayerInputPayload,
 };
 
 use crate::{
+    ability::ActivatedAbility,
     action::MtgAction,
-    player_inputs::MtgInput,
-    steps::{GameStep, StartingStep, Step, SubStep},
-    zone::{NamedZone, Zone},
-    Player, SharedZones,
+    card::{CardType, CardUniverse, HasType, KeywordAbility},
+    combat::CombatState,
+    counter::CounterKind,
+    diff::MtgDiff,
+    effect::{ContinuousEffect, EffectKind, PowerToughnessEffect},
+    event::MtgEvent,
+    mana::ManaPool,
+    planechase::PlanarDieFace,
+    player_inputs::{InputKind, MtgInput},
+    steps::{GameStep, Phase, StartingStep, Step, SubStep},
+    trigger::{DelayedTrigger, EffectAction},
+    zone::{AbstractZoneLocation, NamedZone, Zone},
+    Object, Player, SharedZones,
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Mtg {
     /// Set of players in turn order
     pub players: HashMap<PlayerId, Player>,
 
-    /// Turn order stored as a map of CurrentPLayer -> NextPlayer
-    pub turn_order: HashMap<PlayerId, PlayerId>,
+    pub turn_order: TurnOrder,
 
     pub step: GameStep,
+
+    /// Steps inserted by effects (eg additional combat phases), to be played in order once the
+    /// current step ends and before the default ordering of steps resumes
+    pub step_queue: VecDeque<Step>,
+
+    /// Extra turns created by effects, the most recently created of which is taken next (500.7)
+    pub extra_turns: Vec<PlayerId>,
+
+    /// Players who will skip their next turn, once for each entry (500.11)
+    pub skipped_turns: Vec<PlayerId>,
+
+    /// Kinds of phase that are skipped for the rest of the current turn, eg "skip your combat
+    /// phase this turn"
+    ///
+    /// Cleared as each new turn begins.
+    pub skipped_phases: Vec<Phase>,
+
+    /// The player who took the most recent turn that wasn't an extra turn
+    ///
+    /// The default turn order resumes from this player once any extra turns have been taken.
+    pub last_regular_turn: PlayerId,
+
     pub priority: Option<PlayerId>,
+
+    /// Triggered abilities that have triggered, but not yet been put on the stack (603.3)
+    ///
+    /// Each of these objects is an ability, to be put on the stack the next time a player would
+    /// receive priority.
+    pub pending_triggers: Vec<Object>,
+
+    /// Delayed triggered abilities that are waiting to trigger (603.7)
+    pub delayed_triggers: Vec<DelayedTrigger>,
+
+    /// Actions that effects allow players to take at a later time (116.2c)
+    pub effect_actions: Vec<EffectAction>,
+
+    /// Continuous effects from static abilities, in the order they were registered
+    ///
+    /// Each effect carries its own timestamp, which decides the order they apply in (613.7).
+    pub continuous_effects: Vec<ContinuousEffect>,
+
+    /// How each permanent that has left the battlefield last existed there, by object id
+    ///
+    /// 608.2h If an effect requires information from the game (such as the number of creatures on
+    ///     the battlefield), the answer is determined only once, when the effect is applied. If
+    ///     the effect requires information from a specific object, including the source of the
+    ///     ability itself, the effect uses the current information of that object if it's in the
+    ///     public zone it was expected to be in; if it's no longer in that zone, or if the effect
+    ///     has moved it from a public zone to a hidden zone, the effect uses the object's last
+    ///     known information.
+    ///
+    /// Recorded just before a permanent leaves, so that eg a dies trigger can still see the
+    /// counters that were on the creature. Each entry is forgotten once every observer has seen
+    /// the action that recorded it, so an entry always describes a permanent that has only just
+    /// left the battlefield.
+    pub last_known_information: HashMap<ObjectId, LastKnownInformation>,
+
     pub zones: HashMap<ZoneId, Zone>,
     pub shared_zones: SharedZones,
+    pub combat: CombatState,
+
+    /// The number of turns that have begun so far
+    ///
+    /// Zero while the game is still in one of the starting steps.
+    pub turn_number: u32,
+
+    /// Whether the player taking the first turn of the game skips their draw step (103.8a)
+    pub skip_first_draw: bool,
+
+    /// Whether a player chooses who takes the first turn during the starting steps (103.1)
+    ///
+    /// False if the turn order was fixed before the game began.
+    pub choose_turn_order: bool,
+
+    pub object_id_gen: IdGenerator<ObjectId>,
+    pub ability_id_gen: IdGenerator<AbilityId>,
+
+    /// The face the planar die landed on the last time it was rolled, if it has been (901.9)
+    pub planar_die_result: Option<PlanarDieFace>,
+
+    /// Source of all randomness in the game, eg for shuffling
+    pub rng: GameRng,
+}
+
+/// A permanent as it last existed on the battlefield
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LastKnownInformation {
+    /// The object, including the counters and other state that it lost when it left
+    pub object: Object,
+
+    /// Its power and toughness after every continuous effect that applied to it at the time
+    pub power_toughness: (i32, i32),
 }
 
 impl GameDomain for Mtg {
     type Input = MtgInput;
     type Action = Box<dyn MtgAction>;
+    type InputKind = InputKind;
+
+    fn input_matches_kind(kind: &InputKind, input: &PlayerInputPayload<Self>) -> bool {
+        match input {
+            PlayerInputPayload::DomainInput(input) => kind.accepts(input),
+            PlayerInputPayload::EngineInput(_) => false,
+        }
+    }
+
+    type Event = MtgEvent;
+
+    fn action_events(action: &Box<dyn MtgAction>, game_state: &Self) -> Vec<MtgEvent> {
+        crate::event::action_events(&**action, game_state)
+    }
+
+    type Outcome = MtgOutcome;
+
+    fn state_hash(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        StateFingerprint::of(self).hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    /// 104.4b If a game that's not using the limited range of influence option (including a
+    ///     two-player game) somehow enters a "loop" of mandatory actions, repeating a sequence of
+    ///     events with no way to stop, the game is a draw.
+    fn loop_outcome(&self) -> Option<MtgOutcome> {
+        Some(MtgOutcome::Draw)
+    }
+}
+
+impl GameDomainDiff for Mtg {
+    type Diff = MtgDiff;
+
+    fn diff(before: &Self, after: &Self) -> Vec<MtgDiff> {
+        crate::diff::diff(before, after)
+    }
+}
+
+/// The parts of the game state that loop detection looks at
+///
+/// Covers everything a mandatory loop could plausibly be cycling through, without walking the
+/// card definitions, effects and last known information that a full comparison of two states
+/// would. Two equal states always have equal fingerprints.
+#[derive(Hash)]
+struct StateFingerprint {
+    step: GameStep,
+    priority: Option<PlayerId>,
+    turn_number: u32,
+
+    /// Each player's id, life total and amount of mana in their pool, sorted by id
+    players: Vec<(PlayerId, i32, usize)>,
+
+    /// The objects in each zone in order, sorted by zone id
+    zones: Vec<(ZoneId, Vec<ObjectFingerprint>)>,
+
+    pending_triggers: usize,
+    delayed_triggers: usize,
+    continuous_effects: usize,
+}
+
+#[derive(Hash)]
+struct ObjectFingerprint {
+    id: ObjectId,
+    controller: PlayerId,
+    tapped: bool,
+    face_down: bool,
+    damage_marked: i32,
+
+    /// Sorted by kind
+    counters: Vec<(CounterKind, i32)>,
+}
+
+impl StateFingerprint {
+    fn of(game_state: &Mtg) -> Self {
+        let mut players = game_state
+            .players
+            .values()
+            .map(|p| (p.id, p.life_total, p.mana_pool.mana.len()))
+            .collect::<Vec<_>>();
+        players.sort_unstable_by_key(|&(id, _, _)| id);
+
+        let mut zones = game_state
+            .zones
+            .values()
+            .map(|zone| {
+                let objects: Vec<&Object> = if zone.is_ordered() {
+                    zone.iter_in_order().collect()
+                } else {
+                    zone.iter().collect()
+                };
+                let objects = objects
+                    .into_iter()
+                    .map(|obj| {
+                        let mut counters = obj
+                            .counters
+                            .iter()
+                            .map(|(&kind, &count)| (kind, count))
+                            .collect::<Vec<_>>();
+                        counters.sort_unstable();
+                        ObjectFingerprint {
+                            id: obj.id,
+                            controller: obj.controller,
+                            tapped: obj.tapped,
+                            face_down: obj.face_down,
+                            damage_marked: obj.damage_marked,
+                            counters,
+                        }
+                    })
+                    .collect();
+                (zone.id, objects)
+            })
+            .collect::<Vec<_>>();
+        zones.sort_unstable_by_key(|(id, _)| *id);
+
+        Self {
+            step: game_state.step,
+            priority: game_state.priority,
+            turn_number: game_state.turn_number,
+            players,
+            zones,
+            pending_triggers: game_state.pending_triggers.len(),
+            delayed_triggers: game_state.delayed_triggers.len(),
+            continuous_effects: game_state.continuous_effects.len(),
+        }
+    }
+}
+
+/// How many times the same game state can come around without any player input before the game
+/// is declared a draw, unless the builder is told otherwise
+pub const DEFAULT_LOOP_LIMIT: usize = 100;
+
+/// How a game of magic ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MtgOutcome {
+    /// 104.2a A player still in the game wins the game if that player's opponents have all left
+    ///     the game.
+    ///
+    /// There may be more than one winner in a multiplayer game between teams.
+    Winners(Vec<PlayerId>),
+
+    /// 104.4a If all the players remaining in a game lose simultaneously, the game is a draw.
+    Draw,
 }
 
 impl Mtg {
@@ -93,6 +341,224 @@ impl Mtg {
             .expect("Can't find the ante zone")
     }
 
+    /// Takes the given player out of the game, eg because they lost a multiplayer game
+    ///
+    /// 800.4a When a player leaves the game, all objects (see rule 109) owned by that player leave
+    ///     the game and any effects which give that player control of any objects or players end.
+    ///     Then, if that player controlled any objects on the stack not represented by cards,
+    ///     those objects cease to exist. Then, if there are any objects still controlled by that
+    ///     player, those objects are exiled. [...] If the player who left the game had priority at
+    ///     the time they left, priority passes to the next player in turn order who's still in the
+    ///     game.
+    ///
+    /// The turn order closes up around the player, and anything waiting to happen for them later
+    /// (their pending triggers, extra turns and so on) is dropped.
+    ///
+    /// If they are the active player, they stay so until the turn ends, and the turn continues
+    /// without them (800.4i). The next turn then belongs to whoever comes after them in the turn
+    /// order.
+    ///
+    /// Within a running game, use the `RemovePlayer` action so that observers (eg the one asking
+    /// for the player's input) can react.
+    pub fn remove_player(&mut self, player: PlayerId) {
+        let removed = match self.players.remove(&player) {
+            Some(removed) => removed,
+            None => return,
+        };
+        self.turn_order.remove(player);
+        for zone in [
+            removed.library,
+            removed.hand,
+            removed.graveyard,
+            removed.planar_deck,
+        ] {
+            self.zones.remove(&zone);
+        }
+
+        for zone in self.zones.values_mut() {
+            for id in zone.search(|obj| obj.owner == player) {
+                zone.remove(id);
+            }
+        }
+
+        // Nothing they controlled is owned by them any more, so control goes back to the owners
+        // of permanents, and anything else they controlled on the stack ceases to exist
+        let stack = self.shared_zones.stack;
+        for zone in self.zones.values_mut() {
+            for id in zone.search(|obj| obj.controller == player) {
+                if zone.id == stack {
+                    zone.remove(id);
+                } else if let Some(obj) = zone.get_mut(id) {
+                    obj.controller = obj.owner;
+                    obj.summoning_sick = true;
+                }
+            }
+        }
+
+        self.pending_triggers.retain(|t| t.controller != player);
+        self.delayed_triggers.retain(|t| t.controller != player);
+        self.effect_actions.retain(|a| a.player != player);
+        self.extra_turns.retain(|&p| p != player);
+        self.skipped_turns.retain(|&p| p != player);
+        if self.priority == Some(player) {
+            self.priority = Some(self.turn_order.next(player));
+        }
+    }
+
+    /// Records how the given permanent currently exists on the battlefield, before it leaves
+    ///
+    /// Quietly does nothing if the object isn't on the battlefield.
+    pub fn record_last_known_information(&mut self, id: ObjectId) {
+        let object = match self.battlefield().get(id) {
+            Some(obj) => obj.clone(),
+            None => return,
+        };
+        let power_toughness = self.effective_power_toughness(id);
+        self.last_known_information.insert(
+            id,
+            LastKnownInformation {
+                object,
+                power_toughness,
+            },
+        );
+    }
+
+    /// Search every zone for the given object, returning the zone it's in along with the object
+    pub fn find_object(&self, id: ObjectId) -> Option<(ZoneId, &Object)> {
+        self.zones
+            .values()
+            .find_map(|zone| zone.get(id).map(|obj| (zone.id, obj)))
+    }
+
+    /// The permanent with the given activated ability, along with that ability
+    ///
+    /// 113.6 Abilities of an instant or sorcery spell usually function only while that object is on
+    ///     the stack. Abilities of all other objects usually function only while that object is on
+    ///     the battlefield.
+    pub fn find_ability(&self, id: AbilityId) -> Option<(&Object, &ActivatedAbility)> {
+        self.battlefield().iter().find_map(|obj| {
+            obj.abilities
+                .iter()
+                .find(|ability| ability.id == id)
+                .map(|ability| (obj, ability))
+        })
+    }
+
+    /// The continuous effects that currently apply to the given object
+    ///
+    /// Effects whose source has left the battlefield no longer apply.
+    fn applicable_effects<'a>(
+        &'a self,
+        obj: &'a Object,
+    ) -> impl Iterator<Item = &'a ContinuousEffect> + 'a {
+        self.continuous_effects
+            .iter()
+            .filter(move |e| self.battlefield().contains(e.source) && e.affected.matches(obj))
+    }
+
+    /// The keyword abilities of the given object after applying every continuous effect
+    ///
+    /// Starts from the printed keywords, then applies the ability-adding and ability-removing
+    /// effects of layer 6 in timestamp order (613.1f, 613.7).
+    pub fn effective_keywords(&self, obj: &Object) -> Vec<KeywordAbility> {
+        let mut keywords = obj.characteristics().keywords.clone();
+
+        let mut effects = self.applicable_effects(obj).collect::<Vec<_>>();
+        effects.sort_by_key(|e| e.timestamp);
+        for effect in effects {
+            match effect.effect {
+                EffectKind::GainKeyword(keyword) if !keywords.contains(&keyword) => {
+                    keywords.push(keyword)
+                }
+                EffectKind::LoseKeyword(keyword) => keywords.retain(|&k| k != keyword),
+                _ => (),
+            }
+        }
+
+        keywords
+    }
+
+    /// The power and toughness of the given object after applying every continuous effect
+    ///
+    /// Starts from the printed values (treating missing values as 0), then applies the effects of
+    /// layer 7 in sublayer order, and in timestamp order within each sublayer (613.4, 613.7).
+    /// Effects whose source has left the battlefield no longer apply.
+    pub fn effective_power_toughness(&self, id: ObjectId) -> (i32, i32) {
+        let (_, obj) = self
+            .find_object(id)
+            .expect("Failed to find object in game state");
+        let printed = (obj.power().unwrap_or(0), obj.toughness().unwrap_or(0));
+
+        let mut effects = self
+            .applicable_effects(obj)
+            .filter_map(|e| match e.effect {
+                EffectKind::PowerToughness(effect) => Some((e.timestamp, effect)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // 613.4c Counters that modify power and toughness apply in layer 7c
+        let counters =
+            obj.counter_count(CounterKind::PlusOnePlusOne) - obj.counter_count(CounterKind::Minus);
+        if counters != 0 {
+            // Modifications commute, so the timestamp given to counters doesn't matter
+            effects.push((
+                GameTimestamp::zero(),
+                PowerToughnessEffect::Modify {
+                    power: counters,
+                    toughness: counters,
+                },
+            ));
+        }
+
+        effects.sort_by_key(|(timestamp, e)| (e.sublayer(), *timestamp));
+        effects
+            .iter()
+            .fold(printed, |pt, (_, effect)| effect.apply(pt))
+    }
+
+    /// Is the given card suspended
+    ///
+    /// 702.62b A card is "suspended" if it's in the exile zone, has suspend, and has a time
+    ///     counter on it.
+    ///
+    /// This looks at the card as it is now, so a card that loses suspend while exiled stops being
+    /// suspended, and one that gains it while it has time counters becomes suspended.
+    pub fn is_suspended(&self, id: ObjectId) -> bool {
+        self.exile().get(id).is_some_and(|obj| {
+            obj.characteristics().suspend.is_some() && obj.counter_count(CounterKind::Time) > 0
+        })
+    }
+
+    /// The plane and phenomenon cards that are face up in the command zone
+    ///
+    /// 901.6 [...] Face-up plane cards and phenomenon cards remain in the command zone until
+    ///     they are planeswalked away from.
+    pub fn face_up_planes(&self) -> impl Iterator<Item = &Object> + '_ {
+        self.command()
+            .iter()
+            .filter(|obj| obj.has_type(CardType::Plane) || obj.has_type(CardType::Phenomenon))
+    }
+
+    /// Every permanent on the battlefield controlled by the given player, in ObjectId order
+    pub fn objects_controlled_by(&self, player: PlayerId) -> impl Iterator<Item = &Object> + '_ {
+        self.battlefield()
+            .iter()
+            .filter(move |obj| obj.controller == player)
+    }
+
+    pub fn player(&self, id: PlayerId) -> &Player {
+        self.players.get(&id).expect("Can't find player")
+    }
+
+    /// The cards in the given player's hand, in ObjectId order
+    pub fn hand_contents(&self, player: PlayerId) -> Vec<ObjectId> {
+        self.zones[&self.player(player).hand]
+            .iter()
+            .map(|obj| obj.id)
+            .collect()
+    }
+
     pub fn find_player<S: AsRef<str>>(&self, name: S) -> Option<PlayerId> {
         self.players
             .values()
@@ -102,6 +568,29 @@ impl Mtg {
     }
 }
 
+/// Why a game couldn't be built from the settings given to `MtgGameBuilder`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// No players were added to the game
+    NoPlayers,
+
+    /// A player with the given name was referred to before being added with `with_player`
+    UnknownPlayer(String),
+
+    /// More than one player was added with the given name
+    DuplicatePlayer(String),
+
+    /// A deck was added with `with_deck` before a card universe was given with
+    /// `with_card_universe`
+    NoCardUniverse,
+
+    /// A deck named a card that isn't in the card universe
+    UnknownCard(String),
+
+    /// The turn order given to `with_turn_order` doesn't name every player exactly once
+    InvalidTurnOrder,
+}
+
 pub struct MtgGameBuilder {
     players: HashMap<PlayerId, Player>,
     step: Option<GameStep>,
@@ -109,9 +598,24 @@ pub struct MtgGameBuilder {
     zones: HashMap<ZoneId, Zone>,
     shared_zones: SharedZones,
     starting_life_total: i32,
+    skip_first_draw: bool,
+    turn_order: Option<Vec<PlayerId>>,
+    seed: u64,
+    card_universe: Option<CardUniverse>,
+    loop_limit: Option<usize>,
+
+    /// The first problem found with the settings so far, returned from `build`
+    error: Option<BuildError>,
 
     player_id_gen: IdGenerator<PlayerId>,
     zone_id_gen: IdGenerator<ZoneId>,
+    object_id_gen: IdGenerator<ObjectId>,
+}
+
+impl Default for MtgGameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MtgGameBuilder {
@@ -152,9 +656,34 @@ impl MtgGameBuilder {
             zones,
             shared_zones,
             starting_life_total: 20,
+            skip_first_draw: false,
+            turn_order: None,
+            seed: 0,
+            card_universe: None,
+            loop_limit: Some(DEFAULT_LOOP_LIMIT),
+            error: None,
             player_id_gen,
             zone_id_gen,
+            object_id_gen: IdGenerator::new(),
+        }
+    }
+
+    /// The ID of the player with the given name, noting an error for `build` to return if there
+    /// isn't one
+    fn find_player_id(&mut self, name: &str) -> Option<PlayerId> {
+        let pid = self
+            .players
+            .values()
+            .find(|player| player.name == name)
+            .map(|player| player.id);
+        if pid.is_none() {
+            self.fail(BuildError::UnknownPlayer(name.to_string()));
         }
+        pid
+    }
+
+    fn fail(&mut self, error: BuildError) {
+        self.error.get_or_insert(error);
     }
 
     pub fn with_starting_life_total(mut self, x: i32) -> Self {
@@ -166,12 +695,98 @@ impl MtgGameBuilder {
         self
     }
 
+    /// Should the player taking the first turn skip the draw step of that turn
+    pub fn with_skip_first_draw(mut self, skip: bool) -> Self {
+        self.skip_first_draw = skip;
+        self
+    }
+
+    /// Fix the turn order ahead of time, rather than having a player choose who goes first
+    ///
+    /// Must name every player exactly once, starting with the player who takes the first turn.
+    /// Without this, players sit in alphabetical order of their names, and the starting player is
+    /// chosen during the starting steps.
+    pub fn with_turn_order<S: AsRef<str>>(mut self, names: &[S]) -> Self {
+        let order = names
+            .iter()
+            .filter_map(|name| self.find_player_id(name.as_ref()))
+            .collect();
+        self.turn_order = Some(order);
+        self
+    }
+
+    /// Seed the game's RNG, which is the source of all randomness in the game
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// How many times the same game state can come around without any player input before the
+    /// game is declared a draw, or None to let a mandatory loop run forever
+    pub fn with_loop_limit(mut self, limit: Option<usize>) -> Self {
+        self.loop_limit = limit;
+        self
+    }
+
+    /// The set of cards that decks are built from
+    pub fn with_card_universe(mut self, universe: CardUniverse) -> Self {
+        self.card_universe = Some(universe);
+        self
+    }
+
+    /// Fill the named player's library with the given cards, the first of which ends up on top
+    ///
+    /// Must be called after `with_card_universe`, and every card must be in that universe.
+    pub fn with_deck<S: AsRef<str>>(mut self, player_name: S, cards: Vec<String>) -> Self {
+        let owner = match self.find_player_id(player_name.as_ref()) {
+            Some(pid) => pid,
+            None => return self,
+        };
+        let library = self.players[&owner].library;
+
+        let universe = match &self.card_universe {
+            Some(universe) => universe,
+            None => {
+                self.fail(BuildError::NoCardUniverse);
+                return self;
+            }
+        };
+        let mut definitions = Vec::new();
+        for name in cards {
+            match universe.find_by_name(&name) {
+                Some(definition) => definitions.push(Rc::new(definition.clone())),
+                None => {
+                    self.fail(BuildError::UnknownCard(name));
+                    return self;
+                }
+            }
+        }
+
+        let library = self
+            .zones
+            .get_mut(&library)
+            .expect("Failed to find zone in game state");
+        for definition in definitions {
+            let object =
+                Object::new(self.object_id_gen.next_id(), owner).with_definition(definition);
+            library.insert(object, AbstractZoneLocation::Bottom);
+        }
+
+        self
+    }
+
+    /// Add a player to the game, whose name must be different to every other player's
     pub fn with_player<S: AsRef<str>>(mut self, name: S) -> Self {
+        if self.players.values().any(|p| p.name == name.as_ref()) {
+            self.fail(BuildError::DuplicatePlayer(name.as_ref().to_string()));
+            return self;
+        }
         let player_id = self.player_id_gen.next_id();
 
         let library_id = self.zone_id_gen.next_id();
         let hand_id = self.zone_id_gen.next_id();
         let graveyard_id = self.zone_id_gen.next_id();
+        let planar_deck_id = self.zone_id_gen.next_id();
 
         self.zones
             .insert(library_id, NamedZone::Library(player_id).build(library_id));
@@ -181,6 +796,10 @@ impl MtgGameBuilder {
             graveyard_id,
             NamedZone::Graveyard(player_id).build(graveyard_id),
         );
+        self.zones.insert(
+            planar_deck_id,
+            NamedZone::PlanarDeck(player_id).build(planar_deck_id),
+        );
 
         let player = Player {
             id: player_id,
@@ -189,67 +808,105 @@ impl MtgGameBuilder {
             library: library_id,
             hand: hand_id,
             graveyard: graveyard_id,
+            planar_deck: planar_deck_id,
+            max_hand_size: Some(7),
+            lands_per_turn: 1,
+            lands_played_this_turn: 0,
+            planar_die_rolls_this_turn: 0,
+            can_be_attacked: true,
+            can_be_targeted: true,
+            mulligans_taken: 0,
+            mana_pool: ManaPool::default(),
         };
         self.players.insert(player_id, player);
 
         self
     }
 
+    /// Set the maximum hand size of the named player, or None for no maximum hand size
+    ///
+    /// Every player's maximum hand size is 7 unless set otherwise.
+    pub fn with_max_hand_size<S: AsRef<str>>(mut self, player_name: S, max: Option<usize>) -> Self {
+        if let Some(pid) = self.find_player_id(player_name.as_ref()) {
+            self.players.get_mut(&pid).unwrap().max_hand_size = max;
+        }
+        self
+    }
+
     pub fn with_initial_step<S: AsRef<str>>(
         mut self,
         player_name: S,
         step: Step,
         substep: SubStep,
     ) -> Self {
-        let pid = self
-            .players
-            .iter()
-            .find(|(_pid, player)| &player.name == player_name.as_ref())
-            .map(|(pid, _player)| pid)
-            .cloned()
-            .expect("Couldn't find player with name");
-
-        self.step = Some(GameStep {
-            active_player: pid,
-            step,
-            substep,
-        });
+        if let Some(pid) = self.find_player_id(player_name.as_ref()) {
+            self.step = Some(GameStep {
+                active_player: pid,
+                step,
+                substep,
+            });
+        }
 
         self
     }
 
-    pub fn with_intial_priority<S: AsRef<str>>(mut self, name: S) -> Self {
-        let pid = self
-            .players
-            .iter()
-            .find(|(_pid, player)| &player.name == name.as_ref())
-            .map(|(pid, _player)| pid)
-            .cloned()
-            .expect("Couldn't find player with name");
-        self.priority = Some(pid);
+    #[deprecated(note = "Renamed to with_initial_priority")]
+    pub fn with_intial_priority<S: AsRef<str>>(self, name: S) -> Self {
+        self.with_initial_priority(name)
+    }
+
+    /// Give the named player priority at the start of the game
+    pub fn with_initial_priority<S: AsRef<str>>(mut self, name: S) -> Self {
+        if let Some(pid) = self.find_player_id(name.as_ref()) {
+            self.priority = Some(pid);
+        }
         self
     }
 
-    pub fn build(self) -> core::game::Game<Mtg> {
-        assert!(self.players.len() > 0);
+    /// Build the game, or say what was wrong with the settings given to the builder
+    ///
+    /// Players are referred to by name when setting up the game, so every name must be unique and
+    /// must belong to a player added before it's used.
+    pub fn build(self) -> Result<core::game::Game<Mtg>, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.players.is_empty() {
+            return Err(BuildError::NoPlayers);
+        }
+
+        let order = match &self.turn_order {
+            Some(order) => {
+                let named_once = self
+                    .players
+                    .keys()
+                    .all(|pid| order.iter().filter(|&p| p == pid).count() == 1);
+                if order.len() != self.players.len() || !named_once {
+                    return Err(BuildError::InvalidTurnOrder);
+                }
+                order.clone()
+            }
+            None => {
+                let mut players = self.players.values().collect::<Vec<_>>();
+                players.sort_by_key(|p| p.name.as_str());
+                players.iter().map(|p| p.id).collect()
+            }
+        };
+        let turn_order = TurnOrder::new(order.clone());
 
         let step = match self.step {
             Some(s) => s,
             None => GameStep {
-                active_player: *self.players.keys().next().unwrap(),
+                active_player: order[0],
                 step: Step::Starting(StartingStep::Init),
                 substep: SubStep::InProgress,
             },
         };
 
-        let mut players = self.players.values().collect::<Vec<_>>();
-        players.sort_by_key(|p| p.name.as_str());
-
-        let mut turn_order = HashMap::new();
-        for i in 0..(players.len() - 1) {
-            turn_order.insert(players[i].id, players[i + 1].id);
-        }
-        turn_order.insert(players[players.len() - 1].id, players[0].id);
+        let turn_number = match step.step {
+            Step::Starting(_) => 0,
+            _ => 1,
+        };
 
         let mut observer_id_gen = IdGenerator::<ObserverId>::new();
         let self_id = observer_id_gen.next_id();
@@ -259,21 +916,475 @@ impl MtgGameBuilder {
                 players: self.players,
                 turn_order,
                 step,
+                step_queue: VecDeque::new(),
+                extra_turns: Vec::new(),
+                skipped_turns: Vec::new(),
+                skipped_phases: Vec::new(),
+                last_regular_turn: step.active_player,
                 priority: self.priority,
+                pending_triggers: Vec::new(),
+                delayed_triggers: Vec::new(),
+                effect_actions: Vec::new(),
+                continuous_effects: Vec::new(),
+                last_known_information: HashMap::new(),
                 zones: self.zones,
                 shared_zones: self.shared_zones,
+                combat: CombatState::default(),
+                turn_number,
+                skip_first_draw: self.skip_first_draw,
+                choose_turn_order: self.turn_order.is_none(),
+                object_id_gen: self.object_id_gen,
+                ability_id_gen: IdGenerator::new(),
+                planar_die_result: None,
+                rng: GameRng::from_seed(self.seed),
             },
             action_id_gen: IdGenerator::new(),
             action_queue: ActionQueue::new(),
             observer_id_gen,
             observers: HashMap::new(),
+            observer_order: Vec::new(),
+            observer_subsets: Default::default(),
             self_id,
             game_timestamp: GameTimestamp::zero(),
             current_input_session: None,
+            event_log: None,
+            action_history: None,
+            outcome: None,
+            tick_limit: None,
+            loop_limit: self.loop_limit,
         };
 
         crate::base_rules::attach(&mut game);
 
-        game
+        Ok(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{
+        actions::{Action, ActionPayload},
+        game::TickResult,
+        ActionSink, BaseObserver,
+    };
+
+    use crate::{
+        action::{BaseMtgAction, DiscardCard, GainLife, MtgActionDowncast, PassPriority},
+        card::make_card_universe,
+        player_inputs::{MtgInput, PriorityInput},
+        steps::{CombatStep, SubStep},
+        test_utils::{create_object, input, pass_priority},
+        zone::AbstractZoneLocation,
+    };
+
+    /// "Whenever a player passes priority, they gain 1 life. Whenever a player gains life, they
+    /// lose that much life. Whenever a player loses life, they gain that much life."
+    #[derive(Clone, Debug)]
+    struct LifeSwap;
+
+    impl BaseObserver<Mtg> for LifeSwap {
+        fn observe_action(
+            &mut self,
+            action: &Action<Mtg>,
+            _game_state: &Mtg,
+            sink: &mut dyn ActionSink<Mtg>,
+        ) {
+            let da = match &action.payload {
+                ActionPayload::DomainAction(da) => da,
+                _ => return,
+            };
+            let swapped = if let Some(pass) = da.as_t::<PassPriority>() {
+                GainLife {
+                    player: pass.player,
+                    amount: 1,
+                }
+            } else if let Some(gain) = da.as_t::<GainLife>() {
+                GainLife {
+                    player: gain.player,
+                    amount: -gain.amount,
+                }
+            } else {
+                return;
+            };
+            sink.emit_single(ActionPayload::DomainAction(
+                Box::new(swapped) as Box<dyn MtgAction>
+            ));
+        }
+    }
+
+    #[test]
+    fn test_mandatory_loop_is_a_draw() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .with_loop_limit(Some(5))
+            .build()
+            .unwrap();
+        game.attach_observer(Box::new(LifeSwap));
+        game.tick_until_player_input();
+        let alice = game.game_state.find_player("alice").unwrap();
+
+        input(
+            &mut game,
+            alice,
+            MtgInput::PriorityInput(PriorityInput::PassPriority),
+        );
+        assert!(matches!(
+            game.tick_until_player_input(),
+            TickResult::Finished(MtgOutcome::Draw)
+        ));
+        assert_eq!(game.outcome, Some(MtgOutcome::Draw));
+    }
+
+    #[test]
+    fn test_max_hand_size() {
+        let game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_max_hand_size("bob", None)
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        assert_eq!(game.game_state.player(alice).max_hand_size, Some(7));
+        assert_eq!(game.game_state.player(bob).max_hand_size, None);
+    }
+
+    #[test]
+    fn test_discard_from_hand() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let graveyard = game.game_state.player(alice).graveyard;
+        let cards = (0..3)
+            .map(|_| create_object(&mut game, alice, hand, AbstractZoneLocation::Undefined))
+            .collect::<Vec<_>>();
+        assert_eq!(game.game_state.hand_contents(alice), cards);
+
+        DiscardCard {
+            player: alice,
+            object: cards[1],
+        }
+        .apply(&mut game.game_state);
+        assert_eq!(
+            game.game_state.hand_contents(alice),
+            vec![cards[0], cards[2]]
+        );
+        assert_eq!(
+            game.game_state.zones[&graveyard].top().map(|o| o.id),
+            Some(cards[1])
+        );
+    }
+
+    #[test]
+    fn test_objects_controlled_by() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let alice_hand = game.game_state.player(alice).hand;
+
+        let alices = vec![
+            create_object(
+                &mut game,
+                alice,
+                battlefield,
+                AbstractZoneLocation::Undefined,
+            ),
+            create_object(
+                &mut game,
+                alice,
+                battlefield,
+                AbstractZoneLocation::Undefined,
+            ),
+        ];
+        create_object(&mut game, bob, battlefield, AbstractZoneLocation::Undefined);
+        create_object(
+            &mut game,
+            alice,
+            alice_hand,
+            AbstractZoneLocation::Undefined,
+        );
+
+        let controlled = game
+            .game_state
+            .objects_controlled_by(alice)
+            .map(|o| o.id)
+            .collect::<Vec<_>>();
+        assert_eq!(controlled, alices);
+    }
+
+    #[test]
+    fn test_find_object() {
+        let mut game = MtgGameBuilder::new().with_player("alice").build().unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let library = game.game_state.player(alice).library;
+        let battlefield = game.game_state.shared_zones.battlefield;
+
+        let in_library = create_object(&mut game, alice, library, AbstractZoneLocation::Top);
+        let on_battlefield = create_object(
+            &mut game,
+            alice,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+        );
+
+        let (zone, obj) = game.game_state.find_object(in_library).unwrap();
+        assert_eq!((zone, obj.id), (library, in_library));
+        let (zone, obj) = game.game_state.find_object(on_battlefield).unwrap();
+        assert_eq!((zone, obj.id), (battlefield, on_battlefield));
+
+        let missing = game.game_state.object_id_gen.next_id();
+        assert!(game.game_state.find_object(missing).is_none());
+    }
+
+    #[test]
+    fn test_state_equality() {
+        let build = || {
+            MtgGameBuilder::new()
+                .with_player("alice")
+                .with_player("bob")
+                .build()
+                .unwrap()
+        };
+        let mut game = build();
+        let expected = build();
+        assert_eq!(game.game_state, expected.game_state);
+
+        let alice = game.game_state.find_player("alice").unwrap();
+        let hand = game.game_state.player(alice).hand;
+        let card = create_object(&mut game, alice, hand, AbstractZoneLocation::Undefined);
+        assert_ne!(game.game_state, expected.game_state);
+
+        // A tapped copy of an object is a different object
+        let mut other = game.game_state.clone();
+        assert_eq!(other, game.game_state);
+        other
+            .zones
+            .get_mut(&hand)
+            .unwrap()
+            .get_mut(card)
+            .unwrap()
+            .tapped = true;
+        assert_ne!(other, game.game_state);
+    }
+
+    #[test]
+    fn test_seeded_shuffle() {
+        let shuffled_library = |seed| {
+            let mut game = MtgGameBuilder::new()
+                .with_player("alice")
+                .with_seed(seed)
+                .build()
+                .unwrap();
+            let alice = game.game_state.find_player("alice").unwrap();
+            let library = game.game_state.player(alice).library;
+            for _ in 0..20 {
+                create_object(&mut game, alice, library, AbstractZoneLocation::Top);
+            }
+
+            let Mtg { zones, rng, .. } = &mut game.game_state;
+            zones.get_mut(&library).unwrap().shuffle(rng);
+            (0..20)
+                .map(|n| {
+                    zones[&library]
+                        .resolve_abstract_zone_location(AbstractZoneLocation::NthFromTop(n))
+                        .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(shuffled_library(42), shuffled_library(42));
+        assert_ne!(shuffled_library(42), shuffled_library(43));
+    }
+
+    #[test]
+    fn test_with_deck() {
+        let deck = |land: &str| {
+            let mut cards = vec!["Grizzly Bears".to_string(); 23];
+            cards.extend(vec![land.to_string(); 17]);
+            cards
+        };
+
+        let game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_card_universe(make_card_universe())
+            .with_deck("alice", deck("Forest"))
+            .with_deck("bob", deck("Mountain"))
+            .build()
+            .unwrap();
+        let state = &game.game_state;
+        let alice = state.find_player("alice").unwrap();
+        let bob = state.find_player("bob").unwrap();
+
+        for (player, land) in [(alice, "Forest"), (bob, "Mountain")] {
+            let library = &state.zones[&state.player(player).library];
+            assert_eq!(library.len(), 40);
+            assert_eq!(library.top().unwrap().definition.name, "Grizzly Bears");
+            let lands = library.search(|obj| obj.definition.name == land);
+            assert_eq!(lands.len(), 17);
+            assert!(lands
+                .iter()
+                .all(|&id| library.get(id).unwrap().owner == player));
+        }
+
+        // Objects created after the game is built don't collide with the deck's objects
+        let mut object_id_gen = state.object_id_gen.clone();
+        let next = object_id_gen.next_id();
+        assert!(state.find_object(next).is_none());
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let error = |builder: MtgGameBuilder| builder.build().err();
+
+        assert_eq!(error(MtgGameBuilder::new()), Some(BuildError::NoPlayers));
+        assert_eq!(
+            error(
+                MtgGameBuilder::new()
+                    .with_player("alice")
+                    .with_initial_step("bob", Step::PreCombatMain, SubStep::InProgress)
+            ),
+            Some(BuildError::UnknownPlayer("bob".to_string()))
+        );
+        assert_eq!(
+            error(
+                MtgGameBuilder::new()
+                    .with_initial_priority("alice")
+                    .with_player("alice")
+            ),
+            Some(BuildError::UnknownPlayer("alice".to_string()))
+        );
+        assert_eq!(
+            error(
+                MtgGameBuilder::new()
+                    .with_player("alice")
+                    .with_player("bob")
+                    .with_player("alice")
+            ),
+            Some(BuildError::DuplicatePlayer("alice".to_string()))
+        );
+
+        assert_eq!(
+            error(
+                MtgGameBuilder::new()
+                    .with_player("alice")
+                    .with_player("bob")
+                    .with_turn_order(&["alice", "alice"])
+            ),
+            Some(BuildError::InvalidTurnOrder)
+        );
+
+        assert_eq!(
+            error(
+                MtgGameBuilder::new()
+                    .with_player("alice")
+                    .with_deck("alice", vec!["Forest".to_string()])
+            ),
+            Some(BuildError::NoCardUniverse)
+        );
+        assert_eq!(
+            error(
+                MtgGameBuilder::new()
+                    .with_player("alice")
+                    .with_card_universe(make_card_universe())
+                    .with_deck("alice", vec!["Forest".to_string(), "Forrest".to_string()])
+            ),
+            Some(BuildError::UnknownCard("Forrest".to_string()))
+        );
+
+        // The first problem found is the one reported
+        assert_eq!(
+            error(
+                MtgGameBuilder::new()
+                    .with_initial_priority("carol")
+                    .with_player("alice")
+                    .with_player("alice")
+            ),
+            Some(BuildError::UnknownPlayer("carol".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_with_turn_order() {
+        let game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_player("carol")
+            .with_turn_order(&["bob", "carol", "alice"])
+            .build()
+            .unwrap();
+        let state = &game.game_state;
+        let alice = state.find_player("alice").unwrap();
+        let bob = state.find_player("bob").unwrap();
+        let carol = state.find_player("carol").unwrap();
+
+        assert_eq!(state.step.active_player, bob);
+        assert!(!state.choose_turn_order);
+        assert_eq!(
+            state.turn_order.players().collect::<Vec<_>>(),
+            vec![bob, carol, alice]
+        );
+        assert_eq!(state.turn_order.next(bob), carol);
+        assert_eq!(state.turn_order.next(carol), alice);
+        assert_eq!(state.turn_order.next(alice), bob);
+    }
+
+    #[test]
+    fn test_replay() {
+        let new_game = || {
+            MtgGameBuilder::new()
+                .with_player("alice")
+                .with_player("bob")
+                .with_starting_life_total(20)
+                .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+                .with_initial_priority("alice")
+                .build()
+                .unwrap()
+        };
+
+        // The same game as the demo: both players pass through to combat, and alice declares no
+        // attackers
+        let mut game = new_game();
+        game.enable_action_history();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        game.tick_until_player_input();
+        for _ in 0..2 {
+            pass_priority(&mut game, alice);
+            pass_priority(&mut game, bob);
+        }
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::DeclareAttackers)
+        );
+        input(&mut game, alice, MtgInput::Finished);
+        game.tick_until_player_input();
+
+        let history = game.drain_action_history();
+        let mut replayed = new_game();
+        replayed.replay(&history);
+        assert_eq!(replayed.game_state, game.game_state);
+        assert_eq!(replayed.expecting_input_from(), game.expecting_input_from());
+
+        // The observers were kept up to date, so the replayed game carries on the same way
+        for game in [&mut game, &mut replayed] {
+            pass_priority(game, alice);
+            pass_priority(game, bob);
+        }
+        assert_eq!(replayed.game_state, game.game_state);
+        assert_eq!(
+            game.game_state.step.step,
+            Step::Combat(CombatStep::EndOfCombat)
+        );
     }
 }
@@ -1,18 +1,40 @@
+pub mod ability;
 pub mod action;
+pub mod agent;
 pub mod base_rules;
 pub mod card;
+pub mod combat;
+pub mod counter;
+pub mod diff;
+pub mod effect;
+pub mod event;
 pub mod game;
 pub mod mana;
+pub mod planechase;
 pub mod player_inputs;
 pub mod steps;
+pub mod target;
+pub mod trigger;
+pub mod view;
 pub mod zone;
 
+#[cfg(test)]
+mod test_utils;
+
+use std::{borrow::Cow, collections::HashMap, rc::Rc};
+
+use ability::ActivatedAbility;
 use action::MtgAction;
-pub use core::ids::{ActionId, IdGenerator, ObserverId, PlayerId};
-use core::ids::{ObjectId, ZoneId};
+use card::{CardDefinition, CardType, HasType, KeywordAbility};
+use core::ids::ZoneId;
+pub use core::ids::{AbilityId, ActionId, IdGenerator, ObjectId, ObserverId, PlayerId};
+use counter::CounterKind;
+use game::Mtg;
+use mana::ManaPool;
+use target::Target;
 use zone::ZoneLocation;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SharedZones {
     pub battlefield: ZoneId,
     pub stack: ZoneId,
@@ -21,7 +43,7 @@ pub struct SharedZones {
     pub ante: ZoneId,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Player {
     pub id: PlayerId,
     pub name: String,
@@ -29,15 +51,99 @@ pub struct Player {
     pub library: ZoneId,
     pub hand: ZoneId,
     pub graveyard: ZoneId,
+
+    /// The player's planar deck, which is empty outside of Planechase games (901.3)
+    pub planar_deck: ZoneId,
+
+    /// The player's maximum hand size, or None if they have no maximum hand size (402.2)
+    ///
+    /// This is the effective limit after any effects that modify it have been applied.
+    pub max_hand_size: Option<usize>,
+
+    /// The number of lands the player may play each turn (305.2)
+    pub lands_per_turn: u32,
+
+    /// The number of lands the player has played so far this turn
+    pub lands_played_this_turn: u32,
+
+    /// The number of times the player has rolled the planar die so far this turn (901.9b)
+    pub planar_die_rolls_this_turn: u32,
+
+    /// False while some effect prevents creatures from attacking this player
+    pub can_be_attacked: bool,
+
+    /// False while some effect says this player can't be the target of spells or abilities
+    pub can_be_targeted: bool,
+
+    /// The number of mulligans the player took before keeping their opening hand (103.5)
+    pub mulligans_taken: u32,
+
+    /// Mana that the player has produced but not yet spent (106.4)
+    pub mana_pool: ManaPool,
 }
 
 /// A game object that can exist in a zone
+///
+/// Two objects are equal if all of their fields other than `resolve_action` are equal, and they
+/// either both have a resolve action or both don't. Actions are trait objects that can't be
+/// compared directly.
 #[derive(Clone, Debug)]
 pub struct Object {
     pub id: ObjectId,
     pub owner: PlayerId,
     pub controller: PlayerId,
 
+    /// The printed card this object represents
+    ///
+    /// Objects that aren't represented by a card (eg abilities on the stack) have an empty
+    /// definition.
+    pub definition: Rc<CardDefinition>,
+
+    /// Is this object a token rather than a card (111.1)
+    pub is_token: bool,
+
+    /// Is this object a copy of a card or spell (707.10)
+    pub is_copy: bool,
+
+    /// Only relevant for permanents on the battlefield
+    pub tapped: bool,
+
+    /// Whether this permanent is face down, hiding its printed characteristics (708.2)
+    pub face_down: bool,
+
+    /// The amount of damage marked on this permanent since the last cleanup step
+    pub damage_marked: i32,
+
+    /// Whether this permanent has come under its controller's control since the start of their
+    /// most recent turn
+    ///
+    /// 302.6 [...] A creature can't attack unless it has been under its controller's control
+    ///     continuously since their most recent turn began. This rule is informally called the
+    ///     "summoning sickness" rule.
+    pub summoning_sick: bool,
+
+    /// Whether this creature attacks each combat if able (508.1d)
+    pub must_attack: bool,
+
+    /// False while some effect says this object can't be the target of spells or abilities
+    pub can_be_targeted: bool,
+
+    /// The number of each kind of counter on this object
+    pub counters: HashMap<CounterKind, i32>,
+
+    /// The activated abilities of this object (602)
+    pub abilities: Vec<ActivatedAbility>,
+
+    /// The value chosen for X in this object's mana cost
+    ///
+    /// Only relevant for objects on the stack (107.3a).
+    pub x: Option<u32>,
+
+    /// The objects and players chosen as this spell's targets, in the order of its target specs
+    ///
+    /// Only relevant for objects on the stack (601.2c).
+    pub targets: Vec<Target>,
+
     /// The action to be executed if/when this object is resolved from the top of the stack.
     ///
     /// Only relevant for objects on the stack.
@@ -46,6 +152,173 @@ pub struct Object {
     pub resolve_action: Option<Box<dyn MtgAction>>,
 }
 
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.owner == other.owner
+            && self.controller == other.controller
+            && self.definition == other.definition
+            && self.is_token == other.is_token
+            && self.is_copy == other.is_copy
+            && self.tapped == other.tapped
+            && self.face_down == other.face_down
+            && self.damage_marked == other.damage_marked
+            && self.summoning_sick == other.summoning_sick
+            && self.must_attack == other.must_attack
+            && self.can_be_targeted == other.can_be_targeted
+            && self.counters == other.counters
+            && self.abilities == other.abilities
+            && self.x == other.x
+            && self.targets == other.targets
+            && self.resolve_action.is_some() == other.resolve_action.is_some()
+    }
+}
+
+impl Eq for Object {}
+
+impl Object {
+    /// Create a new object that is both owned and controlled by the given player
+    pub fn new(id: ObjectId, owner: PlayerId) -> Self {
+        Self {
+            id,
+            owner,
+            controller: owner,
+            definition: Rc::new(CardDefinition::default()),
+            is_token: false,
+            is_copy: false,
+            tapped: false,
+            face_down: false,
+            damage_marked: 0,
+            summoning_sick: false,
+            must_attack: false,
+            can_be_targeted: true,
+            counters: HashMap::new(),
+            abilities: Vec::new(),
+            x: None,
+            targets: Vec::new(),
+            resolve_action: None,
+        }
+    }
+
+    pub fn with_definition(mut self, definition: Rc<CardDefinition>) -> Self {
+        self.definition = definition;
+        self
+    }
+
+    /// The characteristics this object currently has, before any continuous effects
+    ///
+    /// These are the printed characteristics of its card, unless it's face down.
+    pub fn characteristics(&self) -> Cow<'_, CardDefinition> {
+        if self.face_down {
+            Cow::Owned(CardDefinition::face_down())
+        } else {
+            Cow::Borrowed(&self.definition)
+        }
+    }
+
+    pub fn power(&self) -> Option<i32> {
+        self.characteristics().power
+    }
+
+    pub fn toughness(&self) -> Option<i32> {
+        self.characteristics().toughness
+    }
+
+    /// Does this object currently have the given keyword ability
+    ///
+    /// Considers both the keywords printed on the object and any granted or removed by continuous
+    /// effects. Face down permanents have no printed abilities (708.2a).
+    pub fn has_keyword(&self, game_state: &Mtg, keyword: KeywordAbility) -> bool {
+        game_state.effective_keywords(self).contains(&keyword)
+    }
+
+    /// Does this creature deal combat damage in the first-strike combat damage step (510.4)
+    pub fn deals_first_strike_damage(&self, game_state: &Mtg) -> bool {
+        let keywords = game_state.effective_keywords(self);
+        keywords.contains(&KeywordAbility::FirstStrike)
+            || keywords.contains(&KeywordAbility::DoubleStrike)
+    }
+
+    /// The number of counters of the given kind on this object
+    pub fn counter_count(&self, kind: CounterKind) -> i32 {
+        self.counters.get(&kind).cloned().unwrap_or(0)
+    }
+
+    /// Reset the parts of this object that don't carry over when it moves to a new zone
+    ///
+    /// 400.7 An object that moves from one zone to another becomes a new object with no memory
+    ///     of its previous existence.
+    /// 122.2 Counters on an object are not retained if that object moves from one zone to
+    ///     another.
+    ///
+    /// The object goes back to being untapped, undamaged, face up, targetable, without any chosen
+    /// targets, and controlled by its owner.
+    /// Anything moving it onto the battlefield under another player's control sets that
+    /// afterwards.
+    pub fn leave_zone(&mut self) {
+        self.counters.clear();
+        self.x = None;
+        self.targets.clear();
+        self.can_be_targeted = true;
+        self.tapped = false;
+        self.face_down = false;
+        self.damage_marked = 0;
+        self.controller = self.owner;
+    }
+
+    /// The converted mana cost of this object, counting the value chosen for X (202.3e)
+    pub fn converted_mana_cost(&self) -> u32 {
+        self.characteristics()
+            .mana_cost
+            .converted_mana_cost_with_x(self.x.unwrap_or(0))
+    }
+
+    /// 306.5b A planeswalker's loyalty is the number of loyalty counters on it
+    pub fn loyalty(&self) -> i32 {
+        self.counter_count(CounterKind::Loyalty)
+    }
+}
+
+impl<T> HasType<T> for Object
+where
+    CardDefinition: HasType<T>,
+{
+    fn has_type(&self, t: T) -> bool {
+        self.characteristics().has_type(t)
+    }
+}
+
+/// A predicate over objects, eg "creatures your opponents control"
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObjectFilter {
+    /// Matches every object
+    Any,
+
+    /// Matches objects that have the given card type
+    CardType(CardType),
+
+    /// Matches objects controlled by the given player
+    ControlledBy(PlayerId),
+
+    /// Matches objects that match every one of the inner filters
+    All(Vec<ObjectFilter>),
+
+    /// Matches objects that don't match the inner filter
+    Not(Box<ObjectFilter>),
+}
+
+impl ObjectFilter {
+    pub fn matches(&self, object: &Object) -> bool {
+        match self {
+            ObjectFilter::Any => true,
+            ObjectFilter::CardType(t) => object.has_type(*t),
+            ObjectFilter::ControlledBy(p) => object.controller == *p,
+            ObjectFilter::All(filters) => filters.iter().all(|f| f.matches(object)),
+            ObjectFilter::Not(filter) => !filter.matches(object),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct ConcreteObject {
     pub zone: ZoneId,
@@ -65,3 +338,26 @@ pub enum MtgValue {
     Object(ObjectId),
     Player(PlayerId),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use card::{make_card_universe, CreatureType, LandType};
+
+    #[test]
+    fn test_object_types() {
+        let universe = make_card_universe();
+        let id = IdGenerator::<ObjectId>::new().next_id();
+        let owner = IdGenerator::<PlayerId>::new().next_id();
+        let bears = Object::new(id, owner).with_definition(Rc::new(
+            universe.find_by_name("Grizzly Bears").unwrap().clone(),
+        ));
+
+        assert!(bears.has_type(CardType::Creature));
+        assert!(bears.has_type(CreatureType::Bear));
+        assert!(!bears.has_type(CardType::Land));
+        assert!(!bears.has_type(LandType::Forest));
+        assert_eq!(bears.power(), Some(2));
+        assert_eq!(bears.toughness(), Some(2));
+    }
+}
@@ -1,9 +1,9 @@
-use core::ids::ActionId;
+use std::collections::HashSet;
 
 /// The 5 colors of magic
 ///
 /// Explicitly does not include "Colorless" or "Snow", as these are not colors.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Color {
     White,
     Blue,
@@ -12,8 +12,22 @@ pub enum Color {
     Green,
 }
 
+impl Color {
+    /// The color represented by the given letter of a mana symbol, eg 'U' for blue
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        match symbol {
+            'W' => Some(Color::White),
+            'U' => Some(Color::Blue),
+            'B' => Some(Color::Black),
+            'R' => Some(Color::Red),
+            'G' => Some(Color::Green),
+            _ => None,
+        }
+    }
+}
+
 /// The set of possible constraints that can be placed on a single mana cost symbol
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ManaConstraint {
     Color(Color),
     Colorless,
@@ -23,7 +37,7 @@ pub enum ManaConstraint {
 /// A single component of a mana cost
 ///
 /// Maps 1:1 to a single circular symbol in the mana cost on a printed mtg card
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BaseManaCostComponent {
     /// A fixed amount of generic mana
     ConcreteGeneric(u32),
@@ -39,19 +53,17 @@ pub enum BaseManaCostComponent {
 }
 
 impl BaseManaCostComponent {
-    fn converted_mana_cost(&self) -> u32 {
+    fn converted_mana_cost(&self, x: u32) -> u32 {
         use BaseManaCostComponent::*;
         match self {
             ConcreteGeneric(val) => *val,
-            // NB: While an object with an XGeneric cost is on the stack, the CMC of this component
-            // is actually the value of X chosen.
-            XGeneric => 0,
+            XGeneric => x,
             Single(_) | Phyrexian(_) => 1,
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ManaCostComponent {
     /// A regular mana cost component
     Base(BaseManaCostComponent),
@@ -61,51 +73,91 @@ pub enum ManaCostComponent {
 }
 
 impl ManaCostComponent {
-    fn converted_mana_cost(&self) -> u32 {
+    fn converted_mana_cost(&self, x: u32) -> u32 {
         match self {
-            ManaCostComponent::Base(a) => a.converted_mana_cost(),
+            ManaCostComponent::Base(a) => a.converted_mana_cost(x),
             ManaCostComponent::Hybrid(a, b) => {
                 // 202.3f When calculating the converted mana cost of an object with a hybrid mana
                 //     symbol in its mana cost, use the largest component of each hybrid symbol.
-                std::cmp::max(a.converted_mana_cost(), b.converted_mana_cost())
+                std::cmp::max(a.converted_mana_cost(x), b.converted_mana_cost(x))
             }
         }
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ManaCost {
     pub components: Vec<ManaCostComponent>,
 }
 
+impl BaseManaCostComponent {
+    fn color(&self) -> Option<Color> {
+        match self {
+            BaseManaCostComponent::Single(ManaConstraint::Color(c))
+            | BaseManaCostComponent::Phyrexian(ManaConstraint::Color(c)) => Some(*c),
+            _ => None,
+        }
+    }
+}
+
 impl ManaCost {
+    /// The colors of the colored mana symbols in this cost, including both halves of any hybrid
+    /// symbols
+    pub fn colors(&self) -> HashSet<Color> {
+        self.components
+            .iter()
+            .flat_map(|component| match component {
+                ManaCostComponent::Base(a) => [a.color(), None],
+                ManaCostComponent::Hybrid(a, b) => [a.color(), b.color()],
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// The converted mana cost of an object that isn't on the stack, where X is always 0
     pub fn converted_mana_cost(&self) -> u32 {
+        self.converted_mana_cost_with_x(0)
+    }
+
+    /// The converted mana cost of an object with the given value chosen for X
+    ///
+    /// 202.3e When calculating the converted mana cost of an object with an {X} in its mana cost,
+    ///     X is treated as 0 while the object is not on the stack, and X is treated as the number
+    ///     chosen for it while the object is on the stack.
+    pub fn converted_mana_cost_with_x(&self, x: u32) -> u32 {
         self.components
             .iter()
-            .map(ManaCostComponent::converted_mana_cost)
+            .map(|c| c.converted_mana_cost(x))
             .sum()
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Mana {
     pub color: Option<Color>,
 
-    /// The action that created this mana, to enable the imeplementation of certain rules/effects
+    /// Whether the object that produced this mana had the snow supertype when it did so
     ///
-    /// Some effects place additional constraints on how the mana can be used. Eg Ancient Ziggurat.
-    /// These contraints apply even if the producing object no longer exists
+    /// 107.4h [...] A snow mana symbol can be paid with one mana produced by a snow source.
     ///
-    /// Effects that care about the object that produced the mana only (todo: verify) care about the
-    /// state of that object when it produced that mana, not the state of the object when that mana
-    /// is spent.
-    /// Eg snow mana costs only care about whether the producer had the snow supertype when it
-    /// produced it. It doesn't matter if the producing object loses the snow supertype before the
-    /// mana is used.
-    pub producer: Option<ActionId>,
+    /// Only the state of the producer at the time it produced the mana matters. It doesn't matter
+    /// if the producing object loses the snow supertype, or stops existing, before the mana is
+    /// spent.
+    pub snow: bool,
 }
 
-#[derive(Debug, Clone)]
+impl Mana {
+    /// Can this mana be spent on a symbol with the given constraint
+    pub fn satisfies(&self, constraint: ManaConstraint) -> bool {
+        match constraint {
+            ManaConstraint::Color(c) => self.color == Some(c),
+            ManaConstraint::Colorless => self.color.is_none(),
+            ManaConstraint::Snow => self.snow,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ManaPool {
     pub mana: Vec<Mana>,
 }
@@ -114,4 +166,520 @@ impl ManaPool {
     pub fn total_of(&self, color: Option<Color>) -> u32 {
         self.mana.iter().filter(|m| m.color == color).count() as u32
     }
+
+    pub fn add(&mut self, mana: Mana) {
+        self.mana.push(mana);
+    }
+
+    /// Removes and returns the first mana in the pool that satisfies the given constraint, if any
+    pub fn remove_one(&mut self, constraint: ManaConstraint) -> Option<Mana> {
+        let index = self.mana.iter().position(|m| m.satisfies(constraint))?;
+        Some(self.mana.remove(index))
+    }
+
+    /// Empties the pool
+    ///
+    /// 106.4 [...] Mana is removed from a mana pool as steps and phases end.
+    pub fn clear(&mut self) {
+        self.mana.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mana.is_empty()
+    }
+}
+
+/// How a player has chosen to pay for a symbol that can be paid in more than one way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolChoice {
+    /// Pay the first half of a hybrid symbol, eg {W} for {W/U}
+    HybridFirst,
+
+    /// Pay the second half of a hybrid symbol, eg {U} for {W/U}
+    HybridSecond,
+
+    /// Pay a Phyrexian symbol with mana
+    PhyrexianMana,
+
+    /// Pay a Phyrexian symbol with 2 life
+    PhyrexianLife,
+}
+
+/// Why a mana cost couldn't be paid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CostError {
+    /// There isn't enough of the right mana in the pool
+    InsufficientMana,
+
+    /// The player can't pay the life required by their choices for Phyrexian symbols
+    ///
+    /// 119.4 [...] a player can pay life only if their life total is greater than or equal to the
+    ///     payment.
+    InsufficientLife,
+
+    /// The component of the cost at the given index could be paid more than one way, and the
+    /// player needs to choose which
+    ChoiceNeeded(usize),
+
+    /// The choice given for the component at the given index doesn't apply to that component
+    InvalidChoice(usize),
+}
+
+/// The mana and life spent paying a mana cost
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManaPayment {
+    pub mana: Vec<Mana>,
+    pub life: i32,
+}
+
+/// The amount of life paid instead of mana for each Phyrexian symbol
+///
+/// 107.4f [...] A Phyrexian mana symbol can be paid with either one mana of its color or 2 life.
+pub const PHYREXIAN_LIFE: i32 = 2;
+
+/// A single symbol of a mana cost, once any choices between alternatives have been made
+#[derive(Clone, Copy)]
+enum Requirement {
+    Generic(u32),
+    Single(ManaConstraint),
+    Life(i32),
+}
+
+fn base_requirement(component: &BaseManaCostComponent) -> Requirement {
+    match component {
+        BaseManaCostComponent::ConcreteGeneric(n) => Requirement::Generic(*n),
+        // X costs aren't supported yet, so X is always 0
+        BaseManaCostComponent::XGeneric => Requirement::Generic(0),
+        BaseManaCostComponent::Single(c) | BaseManaCostComponent::Phyrexian(c) => {
+            Requirement::Single(*c)
+        }
+    }
+}
+
+/// Which mana in the pool to spend on each of the given symbols, as indices into the pool, or None
+/// if they can't all be paid at once
+///
+/// Searches for a way to match every symbol to a different mana, backtracking whenever a symbol
+/// runs out of mana that could pay it. The symbols with the fewest mana that could pay them are
+/// matched first, eg {S} before {G} when paying {G}{S} with one snow and one non-snow green mana.
+fn match_symbols(constraints: &[ManaConstraint], pool: &ManaPool) -> Option<Vec<usize>> {
+    fn assign(
+        order: &[usize],
+        constraints: &[ManaConstraint],
+        pool: &ManaPool,
+        spent: &mut Vec<Option<usize>>,
+        used: &mut Vec<bool>,
+    ) -> bool {
+        let (&symbol, rest) = match order.split_first() {
+            Some(split) => split,
+            None => return true,
+        };
+        for (index, mana) in pool.mana.iter().enumerate() {
+            if used[index] || !mana.satisfies(constraints[symbol]) {
+                continue;
+            }
+            used[index] = true;
+            spent[symbol] = Some(index);
+            if assign(rest, constraints, pool, spent, used) {
+                return true;
+            }
+            used[index] = false;
+        }
+        false
+    }
+
+    let mut order = (0..constraints.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&symbol| {
+        pool.mana
+            .iter()
+            .filter(|m| m.satisfies(constraints[symbol]))
+            .count()
+    });
+
+    let mut spent = vec![None; constraints.len()];
+    let mut used = vec![false; pool.mana.len()];
+    if assign(&order, constraints, pool, &mut spent, &mut used) {
+        Some(spent.into_iter().map(|index| index.unwrap()).collect())
+    } else {
+        None
+    }
+}
+
+/// Pays for the given symbols from the given pool, returning the payment and what's left of the
+/// pool
+fn pay_requirements(
+    life_total: i32,
+    requirements: &[Requirement],
+    pool: &ManaPool,
+) -> Result<(ManaPayment, ManaPool), CostError> {
+    let mut constraints = Vec::new();
+    let mut payment = ManaPayment::default();
+    let mut generic = 0;
+    for requirement in requirements {
+        match *requirement {
+            Requirement::Generic(n) => generic += n,
+            Requirement::Life(n) => payment.life += n,
+            Requirement::Single(constraint) => constraints.push(constraint),
+        }
+    }
+
+    let spent = match_symbols(&constraints, pool).ok_or(CostError::InsufficientMana)?;
+    payment
+        .mana
+        .extend(spent.iter().map(|&index| pool.mana[index].clone()));
+    let mut remaining = ManaPool {
+        mana: pool
+            .mana
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !spent.contains(index))
+            .map(|(_, mana)| mana.clone())
+            .collect(),
+    };
+
+    if payment.life > life_total {
+        return Err(CostError::InsufficientLife);
+    }
+    if remaining.mana.len() < generic as usize {
+        return Err(CostError::InsufficientMana);
+    }
+    // Stable sort, so colorless mana is spent first and otherwise mana is spent in pool order
+    remaining.mana.sort_by_key(|m| m.color.is_some());
+    payment
+        .mana
+        .extend(remaining.mana.drain(..generic as usize));
+
+    Ok((payment, remaining))
+}
+
+/// Could the whole cost be paid with at least one combination of the options for each symbol
+fn any_payable(
+    life_total: i32,
+    options: &[Vec<Requirement>],
+    chosen: &mut Vec<Requirement>,
+    pool: &ManaPool,
+) -> bool {
+    let (first, rest) = match options.split_first() {
+        Some(split) => split,
+        None => return pay_requirements(life_total, chosen, pool).is_ok(),
+    };
+    first.iter().any(|&option| {
+        chosen.push(option);
+        let payable = any_payable(life_total, rest, chosen, pool);
+        chosen.pop();
+        payable
+    })
+}
+
+/// Pays the given mana cost from the given mana pool, returning the mana and life spent
+///
+/// `choices` says how to pay each hybrid or Phyrexian symbol in the cost, in the order they appear.
+/// A symbol without a choice is paid whichever way is possible, or fails with
+/// `CostError::ChoiceNeeded` if the whole cost could be paid with either, so that the player can
+/// be asked.
+///
+/// Colored, colorless and snow symbols are paid first, each with a different mana that satisfies
+/// it, then generic mana is paid with whatever is left, preferring colorless mana. The pool is left
+/// unchanged if the cost can't be paid. The life isn't taken from the player; that's up to the
+/// caller.
+pub fn pay_mana_cost(
+    life_total: i32,
+    cost: &ManaCost,
+    pool: &mut ManaPool,
+    choices: &[SymbolChoice],
+) -> Result<ManaPayment, CostError> {
+    let mut choices = choices.iter();
+    let mut options = Vec::new();
+    let mut undecided = Vec::new();
+    for (index, component) in cost.components.iter().enumerate() {
+        let alternatives = match component {
+            ManaCostComponent::Base(BaseManaCostComponent::Phyrexian(c)) => [
+                (SymbolChoice::PhyrexianMana, Requirement::Single(*c)),
+                (
+                    SymbolChoice::PhyrexianLife,
+                    Requirement::Life(PHYREXIAN_LIFE),
+                ),
+            ],
+            ManaCostComponent::Base(c) => {
+                options.push(vec![base_requirement(c)]);
+                continue;
+            }
+            ManaCostComponent::Hybrid(a, b) => [
+                (SymbolChoice::HybridFirst, base_requirement(a)),
+                (SymbolChoice::HybridSecond, base_requirement(b)),
+            ],
+        };
+
+        match choices.next() {
+            Some(choice) => {
+                let requirement = alternatives
+                    .iter()
+                    .find(|(option, _)| option == choice)
+                    .map(|&(_, requirement)| requirement)
+                    .ok_or(CostError::InvalidChoice(index))?;
+                options.push(vec![requirement]);
+            }
+            None => {
+                undecided.push(index);
+                options.push(alternatives.iter().map(|&(_, r)| r).collect());
+            }
+        }
+    }
+
+    // Narrow each symbol without a choice down to the ways it could be paid that still leave the
+    // rest of the cost payable
+    for index in undecided {
+        let payable = options[index]
+            .iter()
+            .cloned()
+            .filter(|&option| {
+                let mut trial = options.clone();
+                trial[index] = vec![option];
+                any_payable(life_total, &trial, &mut Vec::new(), pool)
+            })
+            .collect::<Vec<_>>();
+        match payable.len() {
+            0 => return Err(CostError::InsufficientMana),
+            1 => options[index] = payable,
+            _ => return Err(CostError::ChoiceNeeded(index)),
+        }
+    }
+
+    let requirements = options.iter().map(|o| o[0]).collect::<Vec<_>>();
+    let (payment, remaining) = pay_requirements(life_total, &requirements, pool)?;
+    *pool = remaining;
+    Ok(payment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(colors: &[Option<Color>]) -> ManaPool {
+        ManaPool {
+            mana: colors
+                .iter()
+                .map(|&color| Mana { color, snow: false })
+                .collect(),
+        }
+    }
+
+    fn single(color: Color) -> BaseManaCostComponent {
+        BaseManaCostComponent::Single(ManaConstraint::Color(color))
+    }
+
+    #[test]
+    fn test_exact_payment() {
+        // {1}{G}
+        let cost = ManaCost {
+            components: vec![
+                ManaCostComponent::Base(BaseManaCostComponent::ConcreteGeneric(1)),
+                ManaCostComponent::Base(single(Color::Green)),
+            ],
+        };
+        let mut pool = pool(&[Some(Color::Red), Some(Color::Green)]);
+
+        let payment = pay_mana_cost(20, &cost, &mut pool, &[]).unwrap();
+        assert_eq!(payment.mana.len(), 2);
+        assert_eq!(payment.mana[0].color, Some(Color::Green));
+        assert_eq!(payment.life, 0);
+        assert!(pool.mana.is_empty());
+    }
+
+    #[test]
+    fn test_underpayment() {
+        // {2}{G}
+        let cost = ManaCost {
+            components: vec![
+                ManaCostComponent::Base(BaseManaCostComponent::ConcreteGeneric(2)),
+                ManaCostComponent::Base(single(Color::Green)),
+            ],
+        };
+        let mut pool = pool(&[Some(Color::Red), Some(Color::Green)]);
+        let before = pool.clone();
+
+        assert_eq!(
+            pay_mana_cost(20, &cost, &mut pool, &[]),
+            Err(CostError::InsufficientMana)
+        );
+        assert_eq!(pool, before);
+    }
+
+    #[test]
+    fn test_hybrid_choice() {
+        // {W/U}
+        let cost = ManaCost {
+            components: vec![ManaCostComponent::Hybrid(
+                single(Color::White),
+                single(Color::Blue),
+            )],
+        };
+
+        // Either half could be paid, so the player has to choose
+        let mut both = pool(&[Some(Color::White), Some(Color::Blue)]);
+        assert_eq!(
+            pay_mana_cost(20, &cost, &mut both, &[]),
+            Err(CostError::ChoiceNeeded(0))
+        );
+        assert_eq!(
+            pay_mana_cost(20, &cost, &mut both, &[SymbolChoice::PhyrexianLife]),
+            Err(CostError::InvalidChoice(0))
+        );
+        let payment = pay_mana_cost(20, &cost, &mut both, &[SymbolChoice::HybridSecond]).unwrap();
+        assert_eq!(payment.mana[0].color, Some(Color::Blue));
+        assert_eq!(both, pool(&[Some(Color::White)]));
+
+        // Only one half can be paid, so no choice is needed
+        let mut blue = pool(&[Some(Color::Blue)]);
+        assert!(pay_mana_cost(20, &cost, &mut blue, &[]).is_ok());
+        assert!(blue.mana.is_empty());
+    }
+
+    #[test]
+    fn test_phyrexian() {
+        // {G/P}
+        let cost = ManaCost {
+            components: vec![ManaCostComponent::Base(BaseManaCostComponent::Phyrexian(
+                ManaConstraint::Color(Color::Green),
+            ))],
+        };
+
+        let mut empty = pool(&[]);
+        let payment = pay_mana_cost(20, &cost, &mut empty, &[]).unwrap();
+        assert_eq!(payment.life, PHYREXIAN_LIFE);
+        assert!(payment.mana.is_empty());
+
+        assert_eq!(
+            pay_mana_cost(1, &cost, &mut empty, &[SymbolChoice::PhyrexianLife]),
+            Err(CostError::InsufficientLife)
+        );
+    }
+
+    #[test]
+    fn test_snow_mana() {
+        let snow = Mana {
+            color: Some(Color::Green),
+            snow: true,
+        };
+        assert!(snow.satisfies(ManaConstraint::Snow));
+        assert!(snow.satisfies(ManaConstraint::Color(Color::Green)));
+
+        // {S}{S}
+        let cost = ManaCost {
+            components: vec![
+                ManaCostComponent::Base(BaseManaCostComponent::Single(
+                    ManaConstraint::Snow
+                ));
+                2
+            ],
+        };
+        let mut mixed = pool(&[Some(Color::Green)]);
+        mixed.mana.push(snow);
+        assert_eq!(
+            pay_mana_cost(20, &cost, &mut mixed, &[]),
+            Err(CostError::InsufficientMana)
+        );
+        assert_eq!(mixed.mana.len(), 2);
+    }
+
+    #[test]
+    fn test_most_constrained_symbol_paid_first() {
+        // {G}{S}, with the snow mana first in the pool
+        let cost = ManaCost {
+            components: vec![
+                ManaCostComponent::Base(single(Color::Green)),
+                ManaCostComponent::Base(BaseManaCostComponent::Single(ManaConstraint::Snow)),
+            ],
+        };
+        let mut pool = pool(&[Some(Color::Green)]);
+        pool.mana.insert(
+            0,
+            Mana {
+                color: Some(Color::Green),
+                snow: true,
+            },
+        );
+
+        let payment = pay_mana_cost(20, &cost, &mut pool, &[]).unwrap();
+        assert!(!payment.mana[0].snow);
+        assert!(payment.mana[1].snow);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn test_choice_only_needed_if_rest_payable() {
+        // {G}{G/P}
+        let cost = ManaCost {
+            components: vec![
+                ManaCostComponent::Base(single(Color::Green)),
+                ManaCostComponent::Base(BaseManaCostComponent::Phyrexian(ManaConstraint::Color(
+                    Color::Green,
+                ))),
+            ],
+        };
+
+        // The only green mana has to go on {G}, so {G/P} can only be paid with life
+        let mut one = pool(&[Some(Color::Green)]);
+        let payment = pay_mana_cost(20, &cost, &mut one, &[]).unwrap();
+        assert_eq!(payment.mana.len(), 1);
+        assert_eq!(payment.life, PHYREXIAN_LIFE);
+
+        let mut two = pool(&[Some(Color::Green), Some(Color::Green)]);
+        assert_eq!(
+            pay_mana_cost(20, &cost, &mut two, &[]),
+            Err(CostError::ChoiceNeeded(1))
+        );
+    }
+
+    #[test]
+    fn test_colorless_mana_constraints() {
+        let colorless = Mana {
+            color: None,
+            snow: false,
+        };
+        assert!(colorless.satisfies(ManaConstraint::Colorless));
+        assert!(!colorless.satisfies(ManaConstraint::Color(Color::Blue)));
+        assert!(!colorless.satisfies(ManaConstraint::Snow));
+
+        // {U} can't be paid with colorless mana, even though {1} could be
+        let cost = ManaCost {
+            components: vec![ManaCostComponent::Base(single(Color::Blue))],
+        };
+        let mut pool = pool(&[None]);
+        assert_eq!(
+            pay_mana_cost(20, &cost, &mut pool, &[]),
+            Err(CostError::InsufficientMana)
+        );
+    }
+
+    #[test]
+    fn test_pool_add_remove() {
+        let mut pool = pool(&[]);
+        assert!(pool.is_empty());
+
+        pool.add(Mana {
+            color: Some(Color::Black),
+            snow: false,
+        });
+        pool.add(Mana {
+            color: None,
+            snow: false,
+        });
+        assert_eq!(pool.total_of(Some(Color::Black)), 1);
+        assert_eq!(pool.total_of(None), 1);
+
+        assert_eq!(pool.remove_one(ManaConstraint::Color(Color::White)), None);
+        let removed = pool.remove_one(ManaConstraint::Colorless).unwrap();
+        assert_eq!(removed.color, None);
+        assert_eq!(pool.total_of(None), 0);
+        assert!(!pool.is_empty());
+    }
+
+    #[test]
+    fn test_pool_clear() {
+        let mut pool = pool(&[Some(Color::Red), None, Some(Color::Green)]);
+        pool.clear();
+        assert!(pool.is_empty());
+        assert_eq!(pool.total_of(Some(Color::Red)), 0);
+    }
 }
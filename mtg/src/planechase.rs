@@ -0,0 +1,174 @@
+//! Planechase, a casual variant played with planar decks and the planar die
+//!
+//! See section 901 of the comprehensive rules
+
+use core::rng::GameRng;
+
+/// A face of the planar die
+///
+/// 901.3 [...] The planar die is a six-sided die with a {PW} symbol on one face and a {CHAOS}
+///     symbol on another face. The other four faces are blank.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanarDieFace {
+    Blank,
+    Chaos,
+    Planeswalk,
+}
+
+impl PlanarDieFace {
+    /// Roll the planar die, with each of its six faces equally likely
+    pub fn roll(rng: &mut GameRng) -> Self {
+        match rng.below(6) {
+            0 => PlanarDieFace::Planeswalk,
+            1 => PlanarDieFace::Chaos,
+            _ => PlanarDieFace::Blank,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        action::{AddMana, BaseMtgAction, GainLife},
+        card::{CardDefinition, CardType, CardTypeLine},
+        game::MtgGameBuilder,
+        player_inputs::{MtgInput, PriorityInput, SpecialAction},
+        steps::{Step, SubStep},
+        test_utils::{create_card, input, pass_priority},
+        trigger::{TriggerCondition, TriggeredAbility},
+        zone::AbstractZoneLocation,
+    };
+
+    #[test]
+    fn test_seeded_rolls() {
+        let rolls = |seed| {
+            let mut rng = GameRng::from_seed(seed);
+            (0..30)
+                .map(|_| PlanarDieFace::roll(&mut rng))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(rolls(1234), rolls(1234));
+        assert_ne!(rolls(1234), rolls(4321));
+
+        let faces = rolls(1234);
+        for face in [
+            PlanarDieFace::Blank,
+            PlanarDieFace::Chaos,
+            PlanarDieFace::Planeswalk,
+        ] {
+            assert!(faces.contains(&face));
+        }
+    }
+
+    /// A seed under which the planar die rolls blank, then chaos, then planeswalk
+    const SEED: u64 = 1;
+
+    #[test]
+    fn test_roll_planar_die() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .with_seed(SEED)
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let command = game.game_state.shared_zones.command;
+        let planar_deck = game.game_state.player(alice).planar_deck;
+
+        let plane = |name: &str| CardDefinition {
+            name: name.to_string(),
+            type_line: CardTypeLine {
+                card_types: vec![CardType::Plane],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let first = create_card(
+            &mut game,
+            alice,
+            command,
+            AbstractZoneLocation::Undefined,
+            plane("First Plane"),
+        );
+        let next = create_card(
+            &mut game,
+            alice,
+            planar_deck,
+            AbstractZoneLocation::Top,
+            plane("Next Plane"),
+        );
+
+        // "Whenever you roll {CHAOS}, you gain 1 life"
+        game.attach_observer(Box::new(TriggeredAbility {
+            controller: alice,
+            condition: TriggerCondition::ChaosRolled(first),
+            effect: Box::new(GainLife {
+                player: alice,
+                amount: 1,
+            }),
+        }));
+        game.tick_until_player_input();
+
+        let mut faces = Vec::new();
+        while !faces.contains(&PlanarDieFace::Planeswalk) {
+            // Each roll after the first in a turn costs one more than the last
+            AddMana {
+                player: alice,
+                color: None,
+                amount: faces.len() as u32,
+                source: None,
+            }
+            .apply(&mut game.game_state);
+            let life = game.game_state.player(alice).life_total;
+
+            input(
+                &mut game,
+                alice,
+                MtgInput::PriorityInput(PriorityInput::SpecialAction(SpecialAction::RollPlanarDie)),
+            );
+            game.tick_until_player_input();
+            assert!(game.game_state.player(alice).mana_pool.is_empty());
+
+            let face = game.game_state.planar_die_result.unwrap();
+            faces.push(face);
+            match face {
+                PlanarDieFace::Blank => {
+                    assert_eq!(game.game_state.player(alice).life_total, life);
+                }
+                PlanarDieFace::Chaos => {
+                    // The chaos ability uses the stack
+                    assert_eq!(game.game_state.stack().len(), 1);
+                    pass_priority(&mut game, alice);
+                    pass_priority(&mut game, bob);
+                    assert_eq!(game.game_state.player(alice).life_total, life + 1);
+                }
+                PlanarDieFace::Planeswalk => {
+                    assert!(game.game_state.command().contains(next));
+                    assert_eq!(
+                        game.game_state.zones[&planar_deck]
+                            .resolve_abstract_zone_location(AbstractZoneLocation::Bottom),
+                        Some(first)
+                    );
+                }
+            }
+
+            // Rolling the die doesn't pass priority
+            assert_eq!(game.game_state.priority, Some(alice));
+            assert_eq!(game.game_state.step.step, Step::PreCombatMain);
+        }
+
+        assert_eq!(
+            faces,
+            vec![
+                PlanarDieFace::Blank,
+                PlanarDieFace::Chaos,
+                PlanarDieFace::Planeswalk
+            ]
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use core::ids::{ObjectId, PlayerId};
+use std::fmt::Display;
+
+use core::ids::{AbilityId, ObjectId, PlayerId};
 
 /// The 10 special actions defined in 116.2
 #[derive(Clone, Copy, Debug)]
@@ -73,12 +75,26 @@ pub enum PriorityInput {
     SpecialAction(SpecialAction),
 }
 
+/// A player's decision about their opening hand (103.5)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MulliganInput {
+    /// Keep the current hand
+    Keep,
+
+    /// Shuffle the current hand away and draw a new one
+    Mulligan,
+}
+
 /// The input type specific to the game of Magic
 #[derive(Clone, Copy, Debug)]
 pub enum MtgInput {
     /// When a player has priority, this variant of input is expected
     PriorityInput(PriorityInput),
 
+    /// When a player is deciding whether to keep their opening hand, this variant of input is
+    /// expected
+    MulliganInput(MulliganInput),
+
     /// Any time the engine is expecting a game object, including but not limited to:
     /// - After choosing 'CastSpell' as a PriorityInput
     /// - When declaring a creature that should attack
@@ -91,6 +107,10 @@ pub enum MtgInput {
     /// - When declaring a player as the target of a spell
     PlayerId(PlayerId),
 
+    /// Any time the engine is expecting an activated ability, eg after choosing 'ActivateAbility'
+    /// as a PriorityInput
+    AbilityId(AbilityId),
+
     /// Any time the engine is expecting an arbitrary integer as an input, including but not
     /// limited to:
     /// - When choosing some X value
@@ -118,3 +138,156 @@ impl MtgInput {
         }
     }
 }
+
+/// The kinds of input that the game may request from a player
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputKind {
+    /// The player holds priority, expects an `MtgInput::PriorityInput`
+    Priority,
+
+    /// The active player is declaring attackers, expects either an `MtgInput::ObjectId` for the
+    /// next attacking creature, or `MtgInput::Finished`
+    DeclareAttacker,
+
+    /// Expects an `MtgInput::PlayerId` or `MtgInput::ObjectId` for the player or planeswalker that
+    /// the given creature is attacking
+    ChooseAttackTarget(ObjectId),
+
+    /// A defending player is declaring blockers, expects either an `MtgInput::ObjectId` for the
+    /// next blocking creature, or `MtgInput::Finished`
+    DeclareBlocker,
+
+    /// Expects an `MtgInput::ObjectId` for the attacking creature that the given creature is
+    /// blocking
+    ChooseBlockedAttacker(ObjectId),
+
+    /// Expects an `MtgInput::ObjectId` for a card in the player's hand to discard
+    Discard,
+
+    /// The player is choosing who takes the first turn, expects an `MtgInput::PlayerId`
+    ChooseStartingPlayer,
+
+    /// The player is deciding whether to keep their opening hand, expects an
+    /// `MtgInput::MulliganInput`
+    Mulligan,
+
+    /// The player has kept a hand after taking mulligans, expects an `MtgInput::ObjectId` for a
+    /// card in their hand to put on the bottom of their library
+    BottomCard,
+
+    /// The player has chosen to play a land, expects either an `MtgInput::ObjectId` for a land
+    /// card in their hand, or `MtgInput::Finished` to change their mind
+    PlayLand,
+
+    /// The player has chosen to turn a face-down creature face up, expects either an
+    /// `MtgInput::ObjectId` for a face-down permanent they control, or `MtgInput::Finished` to
+    /// change their mind
+    FlipCreature,
+
+    /// The player has chosen to take an action allowed by some effect, expects either an
+    /// `MtgInput::AbilityId` for one of the effect actions available to them (see
+    /// `Mtg::effect_actions`), or `MtgInput::Finished` to change their mind
+    EffectAction,
+
+    /// The player has chosen to suspend a card, expects either an `MtgInput::ObjectId` for a card
+    /// with suspend in their hand, or `MtgInput::Finished` to change their mind
+    SuspendCard,
+
+    /// The player has chosen to cast a spell, expects either an `MtgInput::ObjectId` for a card in
+    /// their hand, or `MtgInput::Finished` to change their mind
+    CastSpell,
+
+    /// The player has chosen to activate an ability, expects either an `MtgInput::AbilityId` for
+    /// an ability of a permanent they control, or `MtgInput::Finished` to change their mind
+    ActivateAbility,
+
+    /// The player controls several triggered abilities waiting to be put on the stack, expects an
+    /// `MtgInput::ObjectId` for the one to put on the stack next
+    OrderTriggers,
+
+    /// Expects an `MtgInput::ObjectId` or `MtgInput::PlayerId` for the target of a spell or
+    /// ability, or `MtgInput::Finished` to stop casting the spell
+    ChooseTarget,
+
+    /// Expects an `MtgInput::Value` in the (inclusive) range `min..=max`
+    ChooseNumber { min: i32, max: i32 },
+}
+
+impl InputKind {
+    /// Does the given input have the right shape for this kind of input
+    ///
+    /// This only checks the shape of the input, eg that an object was given when an object was
+    /// expected, not whether that object is a legal choice.
+    pub fn accepts(&self, input: &MtgInput) -> bool {
+        match self {
+            InputKind::Priority => matches!(input, MtgInput::PriorityInput(_)),
+            InputKind::Mulligan => matches!(input, MtgInput::MulliganInput(_)),
+            InputKind::ChooseStartingPlayer => matches!(input, MtgInput::PlayerId(_)),
+            InputKind::DeclareAttacker
+            | InputKind::DeclareBlocker
+            | InputKind::PlayLand
+            | InputKind::FlipCreature
+            | InputKind::SuspendCard
+            | InputKind::CastSpell => {
+                matches!(input, MtgInput::ObjectId(_) | MtgInput::Finished)
+            }
+            InputKind::ActivateAbility | InputKind::EffectAction => {
+                matches!(input, MtgInput::AbilityId(_) | MtgInput::Finished)
+            }
+            InputKind::ChooseAttackTarget(_) => {
+                matches!(input, MtgInput::ObjectId(_) | MtgInput::PlayerId(_))
+            }
+            InputKind::ChooseTarget => matches!(
+                input,
+                MtgInput::ObjectId(_) | MtgInput::PlayerId(_) | MtgInput::Finished
+            ),
+            InputKind::ChooseBlockedAttacker(_)
+            | InputKind::Discard
+            | InputKind::BottomCard
+            | InputKind::OrderTriggers => {
+                matches!(input, MtgInput::ObjectId(_))
+            }
+            InputKind::ChooseNumber { min, max } => match input {
+                MtgInput::Value(x) => min <= x && x <= max,
+                _ => false,
+            },
+        }
+    }
+}
+
+impl Display for InputKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputKind::Priority => write!(f, "Priority"),
+            InputKind::DeclareAttacker => write!(f, "Declare an attacker, or finish"),
+            InputKind::ChooseAttackTarget(attacker) => {
+                write!(f, "Choose what {:?} is attacking", attacker)
+            }
+            InputKind::DeclareBlocker => write!(f, "Declare a blocker, or finish"),
+            InputKind::ChooseBlockedAttacker(blocker) => {
+                write!(f, "Choose which attacker {:?} is blocking", blocker)
+            }
+            InputKind::Discard => write!(f, "Choose a card to discard"),
+            InputKind::ChooseStartingPlayer => write!(f, "Choose who takes the first turn"),
+            InputKind::Mulligan => write!(f, "Keep your hand, or take a mulligan"),
+            InputKind::BottomCard => {
+                write!(f, "Choose a card to put on the bottom of your library")
+            }
+            InputKind::PlayLand => write!(f, "Choose a land to play, or finish"),
+            InputKind::FlipCreature => {
+                write!(f, "Choose a face-down creature to turn face up, or finish")
+            }
+            InputKind::EffectAction => write!(f, "Choose an effect action to take, or finish"),
+            InputKind::SuspendCard => write!(f, "Choose a card to suspend, or finish"),
+            InputKind::CastSpell => write!(f, "Choose a spell to cast, or finish"),
+            InputKind::ActivateAbility => write!(f, "Choose an ability to activate, or finish"),
+            InputKind::OrderTriggers => {
+                write!(f, "Choose a triggered ability to put on the stack next")
+            }
+            InputKind::ChooseTarget => write!(f, "Choose a target"),
+            InputKind::ChooseNumber { min, max } => {
+                write!(f, "Choose a number from {} to {}", min, max)
+            }
+        }
+    }
+}
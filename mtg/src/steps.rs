@@ -2,7 +2,7 @@ use crate::PlayerId;
 
 /// StartingSteps aren't technically steps in the game, but are defined here so that the start of a
 /// game can leverage the same state transition machinery as the main body of the game.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum StartingStep {
     /// Pseudo-step that the game starts up in
     ///
@@ -21,14 +21,14 @@ pub enum StartingStep {
     InitialHandDraw,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum BeginningStep {
     Untap,
     Upkeep,
     Draw,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum CombatStep {
     StartOfCombat,
     DeclareAttackers,
@@ -37,13 +37,13 @@ pub enum CombatStep {
     EndOfCombat,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum EndStep {
     EndOfTurn,
     Cleanup,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Step {
     Starting(StartingStep),
     Beginning(BeginningStep),
@@ -53,7 +53,102 @@ pub enum Step {
     End(EndStep),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+impl Step {
+    /// The step that follows this one under the default ordering of a turn
+    pub fn default_next(&self) -> Step {
+        use BeginningStep::*;
+        use CombatStep::*;
+        use EndStep::*;
+        use StartingStep::*;
+        use Step::*;
+
+        match self {
+            Starting(Init) => Starting(ChoosingTurnOrder),
+            Starting(ChoosingTurnOrder) => Starting(InitialHandDraw),
+            Starting(InitialHandDraw) => Beginning(Untap),
+            Beginning(Untap) => Beginning(Upkeep),
+            Beginning(Upkeep) => Beginning(Draw),
+            Beginning(Draw) => PreCombatMain,
+            PreCombatMain => Combat(StartOfCombat),
+            Combat(StartOfCombat) => Combat(DeclareAttackers),
+            Combat(DeclareAttackers) => Combat(DeclareBlockers),
+            Combat(DeclareBlockers) => Combat(CombatDamage),
+            Combat(CombatDamage) => Combat(EndOfCombat),
+            Combat(EndOfCombat) => PostCombatMain,
+            PostCombatMain => End(EndOfTurn),
+            End(EndOfTurn) => End(Cleanup),
+            End(Cleanup) => Beginning(Untap),
+        }
+    }
+
+    /// The steps that follow this one in the same phase under the default ordering
+    ///
+    /// Empty if this is the last step of its phase, or if the step is a main phase (which has no
+    /// steps, 505.1)
+    pub fn remaining_in_phase(&self) -> Vec<Step> {
+        let mut remaining = Vec::new();
+        let mut step = *self;
+        loop {
+            step = step.default_next();
+            if std::mem::discriminant(&step) != std::mem::discriminant(self) {
+                return remaining;
+            }
+            remaining.push(step);
+        }
+    }
+
+    /// Which kind of phase this step is part of
+    pub fn phase(&self) -> Phase {
+        match self {
+            Step::Starting(_) => Phase::Starting,
+            Step::Beginning(_) => Phase::Beginning,
+            Step::PreCombatMain | Step::PostCombatMain => Phase::Main,
+            Step::Combat(_) => Phase::Combat,
+            Step::End(_) => Phase::End,
+        }
+    }
+
+    /// Is this either of the main phases
+    pub fn is_main(&self) -> bool {
+        self.phase() == Phase::Main
+    }
+
+    /// Is this any step of a combat phase
+    pub fn is_combat(&self) -> bool {
+        self.phase() == Phase::Combat
+    }
+
+    /// Every step of a combat phase, in order (506.1)
+    pub fn combat_phase() -> Vec<Step> {
+        use CombatStep::*;
+
+        [
+            StartOfCombat,
+            DeclareAttackers,
+            DeclareBlockers,
+            CombatDamage,
+            EndOfCombat,
+        ]
+        .iter()
+        .map(|&s| Step::Combat(s))
+        .collect()
+    }
+}
+
+/// The kinds of phase that make up a turn (500.1), plus the steps before the first turn
+///
+/// Both main phases are the same kind of phase, so this doesn't tell a turn's phases apart when
+/// there is more than one of a kind, eg additional combat phases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Starting,
+    Beginning,
+    Main,
+    Combat,
+    End,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SubStep {
     InProgress,
     Ending,
@@ -71,9 +166,119 @@ impl SubStep {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct GameStep {
     pub active_player: PlayerId,
     pub step: Step,
     pub substep: SubStep,
 }
+
+impl GameStep {
+    /// Is this either of the two main phases of a turn
+    pub fn is_main_phase(&self) -> bool {
+        self.step.is_main()
+    }
+
+    /// Is this any step of a combat phase
+    pub fn is_combat(&self) -> bool {
+        self.step.is_combat()
+    }
+
+    /// Has the given step just begun, ie is it in progress rather than ending
+    ///
+    /// "At the beginning of [step]" abilities trigger as the step begins (603.2), which is when
+    /// it moves to being in progress.
+    pub fn is_beginning_of(&self, step: Step) -> bool {
+        self.step == step && self.substep.is_in_progress()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ids::IdGenerator;
+
+    #[test]
+    fn test_phases() {
+        use BeginningStep::*;
+        use CombatStep::*;
+        use EndStep::*;
+        use StartingStep::*;
+
+        let expected = [
+            (Step::Starting(Init), Phase::Starting),
+            (Step::Starting(ChoosingTurnOrder), Phase::Starting),
+            (Step::Starting(InitialHandDraw), Phase::Starting),
+            (Step::Beginning(Untap), Phase::Beginning),
+            (Step::Beginning(Upkeep), Phase::Beginning),
+            (Step::Beginning(Draw), Phase::Beginning),
+            (Step::PreCombatMain, Phase::Main),
+            (Step::Combat(StartOfCombat), Phase::Combat),
+            (Step::Combat(DeclareAttackers), Phase::Combat),
+            (Step::Combat(DeclareBlockers), Phase::Combat),
+            (Step::Combat(CombatDamage), Phase::Combat),
+            (Step::Combat(EndOfCombat), Phase::Combat),
+            (Step::PostCombatMain, Phase::Main),
+            (Step::End(EndOfTurn), Phase::End),
+            (Step::End(Cleanup), Phase::End),
+        ];
+        for (step, phase) in expected {
+            assert_eq!(step.phase(), phase, "{:?}", step);
+            assert_eq!(step.is_main(), phase == Phase::Main);
+            assert_eq!(step.is_combat(), phase == Phase::Combat);
+        }
+
+        // Every step is covered, following the default ordering from the start of the game
+        let mut step = Step::Starting(Init);
+        for (expected_step, _) in &expected[1..] {
+            step = step.default_next();
+            assert_eq!(step, *expected_step);
+        }
+    }
+
+    #[test]
+    fn test_game_step_helpers() {
+        let mut player_id_gen = IdGenerator::<PlayerId>::new();
+        let active_player = player_id_gen.next_id();
+        let other_player = player_id_gen.next_id();
+        let game_step = |step, substep| GameStep {
+            active_player,
+            step,
+            substep,
+        };
+
+        let upkeep = Step::Beginning(BeginningStep::Upkeep);
+        let in_upkeep = game_step(upkeep, SubStep::InProgress);
+        assert!(!in_upkeep.is_main_phase());
+        assert!(!in_upkeep.is_combat());
+        assert!(in_upkeep.is_beginning_of(upkeep));
+        assert!(!in_upkeep.is_beginning_of(Step::Beginning(BeginningStep::Draw)));
+        assert!(!game_step(upkeep, SubStep::Ending).is_beginning_of(upkeep));
+
+        for step in [Step::PreCombatMain, Step::PostCombatMain] {
+            let main = game_step(step, SubStep::InProgress);
+            assert!(main.is_main_phase());
+            assert!(!main.is_combat());
+        }
+
+        for step in Step::combat_phase() {
+            let combat = game_step(step, SubStep::Ending);
+            assert!(combat.is_combat());
+            assert!(!combat.is_main_phase());
+        }
+
+        let cleanup = game_step(Step::End(EndStep::Cleanup), SubStep::InProgress);
+        assert!(!cleanup.is_main_phase());
+        assert!(!cleanup.is_combat());
+
+        // Comparing whole steps takes the active player into account too
+        assert_eq!(in_upkeep, game_step(upkeep, SubStep::InProgress));
+        assert_ne!(
+            in_upkeep,
+            GameStep {
+                active_player: other_player,
+                ..in_upkeep
+            }
+        );
+    }
+}
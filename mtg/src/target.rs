@@ -0,0 +1,154 @@
+//! Targets of spells and abilities
+//!
+//! See section 115 of the comprehensive rules
+
+use crate::{
+    card::{CardType, HasType},
+    game::Mtg,
+    ObjectFilter, ObjectId, PlayerId,
+};
+
+/// A single object or player chosen as a target
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Target {
+    Object(ObjectId),
+    Player(PlayerId),
+}
+
+/// What a spell's target must be, eg "target creature" or "target player"
+///
+/// 115.1 Some spells and abilities require their controller to choose one or more targets for
+///     them. The targets are object(s) and/or player(s) the spell or ability will affect. These
+///     targets are declared as part of the process of putting the spell or ability on the stack.
+///
+/// Only targets on the battlefield or players are supported so far.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TargetSpec {
+    /// A permanent matching the filter, eg "target creature"
+    ///
+    /// 115.2 Only permanents are legal targets for spells and abilities, unless a spell or
+    ///     ability (a) specifies that it can target an object in another zone or (b) targets a
+    ///     player.
+    Permanent(ObjectFilter),
+
+    /// "target player"
+    Player,
+
+    /// 115.4 Some spells and abilities that refer to damage require "any target." This means
+    ///     the controller of the spell or ability must choose a creature, player, planeswalker,
+    ///     or battle as the target.
+    Any,
+}
+
+impl TargetSpec {
+    /// Is the given object or player currently a legal target for this spec
+    ///
+    /// Objects and players that some effect says can't be targeted are never legal.
+    pub fn is_legal(&self, game_state: &Mtg, target: Target) -> bool {
+        match target {
+            Target::Object(id) => {
+                let obj = match game_state.battlefield().get(id) {
+                    Some(obj) => obj,
+                    None => return false,
+                };
+                let matches = match self {
+                    TargetSpec::Permanent(filter) => filter.matches(obj),
+                    TargetSpec::Player => false,
+                    TargetSpec::Any => {
+                        obj.has_type(CardType::Creature) || obj.has_type(CardType::Planeswalker)
+                    }
+                };
+                matches && obj.can_be_targeted
+            }
+            Target::Player(id) => {
+                let is_player_spec = matches!(self, TargetSpec::Player | TargetSpec::Any);
+                is_player_spec
+                    && game_state
+                        .players
+                        .get(&id)
+                        .is_some_and(|player| player.can_be_targeted)
+            }
+        }
+    }
+
+    /// Is there anything in the game that could be chosen as a target for this spec
+    pub fn has_legal_target(&self, game_state: &Mtg) -> bool {
+        let objects = game_state
+            .battlefield()
+            .iter()
+            .map(|obj| Target::Object(obj.id));
+        let players = game_state.players.keys().map(|&id| Target::Player(id));
+        objects
+            .chain(players)
+            .any(|target| self.is_legal(game_state, target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        card::{CardDefinition, CardTypeLine},
+        game::MtgGameBuilder,
+        test_utils::create_card,
+        zone::AbstractZoneLocation,
+    };
+
+    #[test]
+    fn test_target_legality() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let hand = game.game_state.player(alice).hand;
+
+        let creature = CardDefinition {
+            name: "Grizzly Bears".to_string(),
+            type_line: CardTypeLine {
+                card_types: vec![CardType::Creature],
+                ..Default::default()
+            },
+            power: Some(2),
+            toughness: Some(2),
+            ..Default::default()
+        };
+        let bears = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            creature.clone(),
+        );
+        let in_hand = create_card(
+            &mut game,
+            alice,
+            hand,
+            AbstractZoneLocation::Undefined,
+            creature,
+        );
+
+        let target_creature = TargetSpec::Permanent(ObjectFilter::CardType(CardType::Creature));
+        let state = &game.game_state;
+        assert!(target_creature.is_legal(state, Target::Object(bears)));
+        assert!(!target_creature.is_legal(state, Target::Object(in_hand)));
+        assert!(!target_creature.is_legal(state, Target::Player(alice)));
+        assert!(TargetSpec::Player.is_legal(state, Target::Player(alice)));
+        assert!(!TargetSpec::Player.is_legal(state, Target::Object(bears)));
+        assert!(TargetSpec::Any.is_legal(state, Target::Object(bears)));
+        assert!(TargetSpec::Any.is_legal(state, Target::Player(bob)));
+
+        game.game_state
+            .battlefield_mut()
+            .get_mut(bears)
+            .unwrap()
+            .can_be_targeted = false;
+        let state = &game.game_state;
+        assert!(!target_creature.is_legal(state, Target::Object(bears)));
+        assert!(!target_creature.has_legal_target(state));
+        assert!(TargetSpec::Any.has_legal_target(state));
+    }
+}
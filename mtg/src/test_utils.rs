@@ -0,0 +1,89 @@
+//! Helpers for driving a game of magic from unit tests
+
+use std::{collections::HashMap, rc::Rc};
+
+use core::{
+    agent::Agent,
+    game::{Game, InputError},
+    ids::ZoneId,
+    PlayerInput, PlayerInputPayload,
+};
+
+use crate::{
+    agent::AlwaysPassAgent,
+    card::CardDefinition,
+    game::Mtg,
+    player_inputs::{InputKind, MtgInput, PriorityInput},
+    zone::AbstractZoneLocation,
+    Object, ObjectId, PlayerId,
+};
+
+pub fn try_input(
+    game: &mut Game<Mtg>,
+    player: PlayerId,
+    input: MtgInput,
+) -> Result<(), InputError> {
+    game.player_input(PlayerInput {
+        source: player,
+        payload: PlayerInputPayload::DomainInput(input),
+    })
+}
+
+pub fn input(game: &mut Game<Mtg>, player: PlayerId, input: MtgInput) {
+    try_input(game, player, input).expect("Expected to succeed in giving input");
+}
+
+/// An agent for every player in the game that always passes, for `Game::run_with_agents`
+pub fn always_pass_agents(game: &Game<Mtg>) -> HashMap<PlayerId, Box<dyn Agent<Mtg>>> {
+    let mut agents: HashMap<_, Box<dyn Agent<Mtg>>> = HashMap::new();
+    for player in game.game_state.players.keys() {
+        agents.insert(*player, Box::new(AlwaysPassAgent::default()));
+    }
+    agents
+}
+
+/// The kind of input the game is currently waiting for, if any
+pub fn input_kind(game: &Game<Mtg>) -> Option<InputKind> {
+    game.current_input_request().map(|request| request.kind)
+}
+
+pub fn pass_priority(game: &mut Game<Mtg>, player: PlayerId) {
+    input(
+        game,
+        player,
+        MtgInput::PriorityInput(PriorityInput::PassPriority),
+    );
+    game.tick_until_player_input();
+}
+
+/// Creates a new object owned by the given player at the given location
+pub fn create_object(
+    game: &mut Game<Mtg>,
+    owner: PlayerId,
+    zone: ZoneId,
+    loc: AbstractZoneLocation,
+) -> ObjectId {
+    create_card(game, owner, zone, loc, CardDefinition::default())
+}
+
+/// Creates a new object representing the given card, owned by the given player at the given
+/// location
+pub fn create_card(
+    game: &mut Game<Mtg>,
+    owner: PlayerId,
+    zone: ZoneId,
+    loc: AbstractZoneLocation,
+    definition: CardDefinition,
+) -> ObjectId {
+    let state = &mut game.game_state;
+    let id = state.object_id_gen.next_id();
+    state
+        .zones
+        .get_mut(&zone)
+        .expect("Failed to find zone in game state")
+        .insert(
+            Object::new(id, owner).with_definition(Rc::new(definition)),
+            loc,
+        );
+    id
+}
@@ -0,0 +1,504 @@
+//! Generic "whenever X happens, do Y" triggered abilities
+//!
+//! See section 603 of the comprehensive rules
+
+use core::{
+    actions::{Action, ActionPayload},
+    ids::PlayerId,
+    ActionSink, BaseObserver, ObserverInterests,
+};
+
+use crate::{
+    action::{
+        AdvanceStep, CastSpell, ChangeObjectZone, Destroy, MtgAction, MtgActionDowncast,
+        RollPlanarDie, TriggerAbility,
+    },
+    card::{CardType, HasType},
+    game::Mtg,
+    mana::ManaCost,
+    planechase::PlanarDieFace,
+    steps::{Step, SubStep},
+    AbilityId, ObjectId, ObjectReference,
+};
+
+/// The event that a triggered ability is waiting for
+#[derive(Clone, Debug)]
+pub enum TriggerCondition {
+    /// Whenever a creature dies
+    ///
+    /// 700.4 The term dies means "is put into a graveyard from the battlefield."
+    CreatureDies,
+
+    /// Whenever a creature leaves the battlefield, for any reason
+    ///
+    /// 603.6c Leaves-the-battlefield abilities trigger when a permanent moves from the battlefield
+    ///     to another zone [...]
+    CreatureLeavesBattlefield,
+
+    /// Whenever the given player casts a spell, or whenever any player does if None
+    SpellCast(Option<PlayerId>),
+
+    /// Whenever the planar die is rolled to {CHAOS} while the given plane is face up
+    ///
+    /// 311.7 Each plane card has a triggered ability that triggers "Whenever you roll {CHAOS}."
+    ///     These are called "chaos abilities."
+    ChaosRolled(ObjectId),
+
+    /// At the beginning of the given player's next step of the given kind
+    BeginningOfStep { step: Step, player: PlayerId },
+}
+
+impl TriggerCondition {
+    /// Was the given object a creature when it was last on the battlefield
+    ///
+    /// Leaves-the-battlefield abilities look back in time (603.10a), so a creature that stops
+    /// being a creature once it's in the graveyard has still died.
+    fn was_creature(game_state: &Mtg, object: ObjectId) -> bool {
+        game_state
+            .last_known_information
+            .get(&object)
+            .is_some_and(|lki| lki.object.has_type(CardType::Creature))
+    }
+
+    /// Has the given creature been put into a graveyard from the battlefield
+    fn creature_in_graveyard(game_state: &Mtg, object: ObjectId) -> bool {
+        match game_state.find_object(object) {
+            Some((zone, _)) => {
+                Self::was_creature(game_state, object)
+                    && game_state.players.values().any(|p| p.graveyard == zone)
+            }
+            None => false,
+        }
+    }
+
+    /// Has the given creature moved from the battlefield to another zone
+    fn creature_left_battlefield(game_state: &Mtg, object: ObjectId) -> bool {
+        match game_state.find_object(object) {
+            Some((zone, _)) => {
+                Self::was_creature(game_state, object)
+                    && zone != game_state.shared_zones.battlefield
+            }
+            None => false,
+        }
+    }
+
+    /// The number of times the given action (including its components) meets this condition
+    ///
+    /// The action has already been applied to the given game state.
+    pub fn count_matches(&self, action: &dyn MtgAction, game_state: &Mtg) -> usize {
+        let matched = match self {
+            TriggerCondition::CreatureDies => {
+                if let Some(a) = action.as_t::<Destroy>() {
+                    Self::creature_in_graveyard(game_state, a.object)
+                } else if let Some(a) = action.as_t::<ChangeObjectZone>() {
+                    match a.obj_ref {
+                        ObjectReference::Concrete(c) => {
+                            c.zone == game_state.shared_zones.battlefield
+                                && Self::creature_in_graveyard(game_state, c.object)
+                        }
+                        ObjectReference::Abstract(_) => false,
+                    }
+                } else {
+                    false
+                }
+            }
+            TriggerCondition::CreatureLeavesBattlefield => {
+                if let Some(a) = action.as_t::<Destroy>() {
+                    Self::creature_left_battlefield(game_state, a.object)
+                } else if let Some(a) = action.as_t::<ChangeObjectZone>() {
+                    match a.obj_ref {
+                        ObjectReference::Concrete(c) => {
+                            c.zone == game_state.shared_zones.battlefield
+                                && Self::creature_left_battlefield(game_state, c.object)
+                        }
+                        ObjectReference::Abstract(_) => false,
+                    }
+                } else {
+                    false
+                }
+            }
+            TriggerCondition::SpellCast(player) => action
+                .as_t::<CastSpell>()
+                .is_some_and(|a| player.is_none_or(|p| p == a.player)),
+            TriggerCondition::BeginningOfStep { step, player } => {
+                action.as_t::<AdvanceStep>().is_some_and(|a| {
+                    a.new_step == *step
+                        && a.new_substep == SubStep::InProgress
+                        && a.new_active_player == *player
+                })
+            }
+            TriggerCondition::ChaosRolled(plane) => {
+                action.is::<RollPlanarDie>()
+                    && game_state.planar_die_result == Some(PlanarDieFace::Chaos)
+                    && game_state.face_up_planes().any(|p| p.id == *plane)
+            }
+        };
+
+        let from_components = action
+            .components()
+            .iter()
+            .map(|component| self.count_matches(&**component, game_state))
+            .sum::<usize>();
+
+        matched as usize + from_components
+    }
+}
+
+/// A triggered ability, which triggers once each time its condition is met
+///
+/// Each time it triggers, the ability waits in `Mtg::pending_triggers` until the next time a player
+/// would receive priority, when it is put on the stack (603.3).
+#[derive(Clone, Debug)]
+pub struct TriggeredAbility {
+    pub controller: PlayerId,
+    pub condition: TriggerCondition,
+
+    /// What the ability does when it resolves
+    pub effect: Box<dyn MtgAction>,
+}
+
+impl BaseObserver<Mtg> for TriggeredAbility {
+    fn interests(&self) -> ObserverInterests {
+        ObserverInterests::ACTIONS
+    }
+
+    fn observe_action(
+        &mut self,
+        action: &Action<Mtg>,
+        game_state: &Mtg,
+        sink: &mut dyn ActionSink<Mtg>,
+    ) {
+        let da = match &action.payload {
+            ActionPayload::DomainAction(da) => da,
+            _ => return,
+        };
+
+        for _ in 0..self.condition.count_matches(&**da, game_state) {
+            sink.emit_single(ActionPayload::DomainAction(Box::new(TriggerAbility {
+                controller: self.controller,
+                effect: self.effect.clone(),
+            })
+                as Box<dyn MtgAction>));
+        }
+    }
+}
+
+/// A triggered ability created by a spell or ability, which triggers only once
+///
+/// 603.7 An effect may create a delayed triggered ability that can do something at a later time.
+/// 603.7c A delayed triggered ability that refers to a particular object still affects it even if
+///     the object changes zones. [...] A delayed triggered ability will trigger only once—the next
+///     time its trigger event occurs—unless it has a stated duration.
+///
+/// Delayed triggered abilities live in `Mtg::delayed_triggers` until they trigger.
+#[derive(Clone, Debug)]
+pub struct DelayedTrigger {
+    pub id: AbilityId,
+    pub controller: PlayerId,
+    pub condition: TriggerCondition,
+
+    /// What the ability does when it resolves
+    pub effect: Box<dyn MtgAction>,
+}
+
+impl PartialEq for DelayedTrigger {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.controller == other.controller
+    }
+}
+
+impl Eq for DelayedTrigger {}
+
+/// An action that some effect allows a player to take at a later time
+///
+/// 116.2c Some effects allow a player to take an action at a later time, usually to end a
+///     continuous effect or to stop a delayed triggered ability from triggering. Doing so is a
+///     special action.
+///
+/// Effect actions live in `Mtg::effect_actions` until they're taken or their window closes.
+#[derive(Clone, Debug)]
+pub struct EffectAction {
+    pub id: AbilityId,
+
+    /// The player who may take this action
+    pub player: PlayerId,
+
+    /// The cost of taking this action
+    pub cost: ManaCost,
+
+    /// What taking this action does
+    pub effect: Box<dyn MtgAction>,
+
+    /// The delayed triggered ability this action's window is tied to, if any
+    ///
+    /// The action can no longer be taken once that ability has triggered.
+    pub expires_with: Option<AbilityId>,
+}
+
+impl PartialEq for EffectAction {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.player == other.player
+            && self.cost == other.cost
+            && self.expires_with == other.expires_with
+    }
+}
+
+impl Eq for EffectAction {}
+
+#[cfg(test)]
+mod tests {
+    use core::actions::{Action, ActionPayload, Controller};
+
+    use super::*;
+    use crate::{
+        action::{AddCounter, BaseMtgAction, DealDamage, GainLife, MoveObjects},
+        card::make_card_universe,
+        counter::CounterKind,
+        game::MtgGameBuilder,
+        player_inputs::{InputKind, MtgInput},
+        steps::{Step, SubStep},
+        test_utils::{create_card, input, input_kind, pass_priority},
+        zone::{AbstractZoneLocation, ZoneLocation},
+        ConcreteObject,
+    };
+
+    #[test]
+    fn test_creature_dies_trigger() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_starting_life_total(20)
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        let universe = make_card_universe();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            universe.find_by_name("Grizzly Bears").unwrap().clone(),
+        );
+
+        // "Whenever a creature dies, you gain 1 life"
+        game.attach_observer(Box::new(TriggeredAbility {
+            controller: alice,
+            condition: TriggerCondition::CreatureDies,
+            effect: Box::new(GainLife {
+                player: alice,
+                amount: 1,
+            }),
+        }));
+        game.tick_until_player_input();
+        assert_eq!(game.game_state.stack().len(), 0);
+
+        DealDamage {
+            object: bears,
+            amount: 2,
+        }
+        .apply(&mut game.game_state);
+        pass_priority(&mut game, alice);
+
+        // The bears died as a state-based action, and the trigger went on the stack before bob
+        // received priority
+        let bobs_graveyard = game.game_state.player(bob).graveyard;
+        assert!(game.game_state.zones[&bobs_graveyard].contains(bears));
+        assert_eq!(game.game_state.stack().len(), 1);
+        assert_eq!(game.game_state.priority, Some(bob));
+        assert_eq!(game.game_state.player(alice).life_total, 20);
+
+        pass_priority(&mut game, bob);
+        assert!(game.game_state.stack().is_empty());
+        assert_eq!(game.game_state.player(alice).life_total, 21);
+    }
+
+    #[test]
+    fn test_simultaneous_leaves_battlefield_trigger() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_starting_life_total(20)
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        let universe = make_card_universe();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = (0..3)
+            .map(|_| {
+                create_card(
+                    &mut game,
+                    bob,
+                    battlefield,
+                    AbstractZoneLocation::Undefined,
+                    universe.find_by_name("Grizzly Bears").unwrap().clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // "Whenever a creature leaves the battlefield, you gain 1 life"
+        game.attach_observer(Box::new(TriggeredAbility {
+            controller: alice,
+            condition: TriggerCondition::CreatureLeavesBattlefield,
+            effect: Box::new(GainLife {
+                player: alice,
+                amount: 1,
+            }),
+        }));
+        game.tick_until_player_input();
+
+        // "Return all creatures to their owners' hands"
+        let bobs_hand = game.game_state.player(bob).hand;
+        let moves = bears
+            .iter()
+            .map(|&object| {
+                let obj_ref = ObjectReference::Concrete(ConcreteObject {
+                    zone: battlefield,
+                    object,
+                });
+                let new_loc = ZoneLocation {
+                    zone: bobs_hand,
+                    loc: AbstractZoneLocation::Undefined,
+                };
+                (obj_ref, new_loc)
+            })
+            .collect();
+        let bounce = MoveObjects::new(moves, &game.game_state);
+        game.action_queue.add(Action {
+            payload: ActionPayload::DomainAction(Box::new(bounce.clone()) as Box<dyn MtgAction>),
+            source: game.self_id,
+            controller: Controller::Game,
+            id: game.action_id_gen.next_id(),
+            generated_at: game.game_timestamp,
+            original: None,
+        });
+        pass_priority(&mut game, alice);
+
+        // All three creatures moved as part of the one event, which triggered the ability once
+        // for each of them
+        for &bear in &bears {
+            assert!(game.game_state.zones[&bobs_hand].contains(bear));
+        }
+        assert_eq!(game.game_state.pending_triggers.len(), 3);
+
+        // Their last known information has been forgotten, so looking at the same event again
+        // doesn't find any creatures leaving
+        let condition = TriggerCondition::CreatureLeavesBattlefield;
+        assert_eq!(condition.count_matches(&bounce, &game.game_state), 0);
+
+        // Alice puts her three triggers on the stack in whichever order she likes
+        while input_kind(&game) == Some(InputKind::OrderTriggers) {
+            let next = game.game_state.pending_triggers[0].id;
+            input(&mut game, alice, MtgInput::ObjectId(next));
+            game.tick_until_player_input();
+        }
+        assert_eq!(game.game_state.stack().len(), 3);
+        assert_eq!(game.game_state.priority, Some(bob));
+
+        pass_priority(&mut game, bob);
+        for _ in 0..2 {
+            pass_priority(&mut game, alice);
+            pass_priority(&mut game, bob);
+        }
+        assert!(game.game_state.stack().is_empty());
+        assert_eq!(game.game_state.player(alice).life_total, 23);
+    }
+
+    /// "Whenever a creature dies, you gain life equal to its power"
+    #[derive(Clone, Debug)]
+    struct GainDeadCreaturesPower {
+        controller: PlayerId,
+    }
+
+    impl BaseObserver<Mtg> for GainDeadCreaturesPower {
+        fn interests(&self) -> ObserverInterests {
+            ObserverInterests::ACTIONS
+        }
+
+        fn observe_action(
+            &mut self,
+            action: &Action<Mtg>,
+            game_state: &Mtg,
+            sink: &mut dyn ActionSink<Mtg>,
+        ) {
+            let da = match &action.payload {
+                ActionPayload::DomainAction(da) => da,
+                _ => return,
+            };
+
+            for destroy in da.find_all::<Destroy>() {
+                let (power, _) = game_state.last_known_information[&destroy.object].power_toughness;
+                sink.emit_single(ActionPayload::DomainAction(Box::new(TriggerAbility {
+                    controller: self.controller,
+                    effect: Box::new(GainLife {
+                        player: self.controller,
+                        amount: power,
+                    }),
+                })
+                    as Box<dyn MtgAction>));
+            }
+        }
+    }
+
+    #[test]
+    fn test_dies_trigger_uses_last_known_information() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .with_starting_life_total(20)
+            .with_initial_step("alice", Step::PreCombatMain, SubStep::InProgress)
+            .with_initial_priority("alice")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+
+        let universe = make_card_universe();
+        let battlefield = game.game_state.shared_zones.battlefield;
+        let bears = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            universe.find_by_name("Grizzly Bears").unwrap().clone(),
+        );
+        AddCounter {
+            object: ConcreteObject {
+                zone: battlefield,
+                object: bears,
+            },
+            kind: CounterKind::PlusOnePlusOne,
+            amount: 1,
+        }
+        .apply(&mut game.game_state);
+
+        game.attach_observer(Box::new(GainDeadCreaturesPower { controller: alice }));
+        game.tick_until_player_input();
+
+        DealDamage {
+            object: bears,
+            amount: 3,
+        }
+        .apply(&mut game.game_state);
+        pass_priority(&mut game, alice);
+
+        // The bears lost their counter on the way to the graveyard, but the trigger still saw
+        // them as the 3/3 they were when they died. Nothing needs that information any more.
+        let bobs_graveyard = game.game_state.player(bob).graveyard;
+        assert!(game.game_state.zones[&bobs_graveyard].contains(bears));
+        assert_eq!(game.game_state.effective_power_toughness(bears), (2, 2));
+        assert!(game.game_state.last_known_information.is_empty());
+
+        pass_priority(&mut game, bob);
+        assert_eq!(game.game_state.player(alice).life_total, 23);
+    }
+}
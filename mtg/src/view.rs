@@ -0,0 +1,384 @@
+//! What a single player is allowed to see of a game and the actions applied to it, eg for sending
+//! to a networked client
+//!
+//! 400.2 Public zones are zones in which all players can see the cards' faces, except for those
+//!     cards that some rule or effect specifically allow to be face down. Library and hand are
+//!     hidden zones, even if all the cards in one such zone happen to be revealed.
+
+use std::{collections::HashMap, rc::Rc};
+
+use core::{
+    actions::{Action, ActionPayload},
+    ids::{PlayerId, ZoneId},
+    rng::GameRng,
+};
+
+use crate::{
+    action::{
+        AddCounter, BaseMtgAction, CeaseToExist, ChangeObjectZone, CompositeAction, MtgAction,
+        MtgActionDowncast, RemoveCounter, SearchLibrary,
+    },
+    card::CardDefinition,
+    game::Mtg,
+    ObjectReference,
+};
+
+/// A copy of the game state with everything the viewer can't see taken out
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MtgView {
+    /// The player this view is for
+    pub viewer: PlayerId,
+
+    /// The game state as the viewer sees it
+    ///
+    /// Hidden zones are empty, and face-down permanents the viewer doesn't control have no
+    /// printed characteristics, including in their last known information. The random number
+    /// generator is reset, so that the viewer can't use it to work out the results of shuffles.
+    pub state: Mtg,
+
+    /// The number of objects in each zone whose contents are hidden from the viewer
+    pub hidden_zone_sizes: HashMap<ZoneId, usize>,
+}
+
+impl MtgView {
+    /// The number of objects in the given zone, whether or not the viewer can see them
+    pub fn zone_size(&self, zone: ZoneId) -> usize {
+        match self.hidden_zone_sizes.get(&zone) {
+            Some(size) => *size,
+            None => self.state.zones[&zone].len(),
+        }
+    }
+}
+
+impl Mtg {
+    /// Can the given player see the contents of the given zone
+    ///
+    /// Every public zone can be seen by everyone, and each player can look at their own hand
+    /// (402.3). No one can look at the contents of a library (401.2) or a planar deck.
+    pub fn can_see_zone(&self, player: PlayerId, zone: ZoneId) -> bool {
+        self.zones[&zone].public || self.player(player).hand == zone
+    }
+
+    /// Everything the given player is allowed to see of the game
+    ///
+    /// Only the controller of a face-down permanent can look at what it really is.
+    pub fn visible_to(&self, player: PlayerId) -> MtgView {
+        let mut state = self.clone();
+        state.rng = GameRng::from_seed(0);
+
+        let mut hidden_zone_sizes = HashMap::new();
+        let hidden_zones = self
+            .zones
+            .keys()
+            .filter(|&&zone| !self.can_see_zone(player, zone))
+            .cloned()
+            .collect::<Vec<_>>();
+        for zone in hidden_zones {
+            let zone = state.zones.get_mut(&zone).unwrap();
+            hidden_zone_sizes.insert(zone.id, zone.len());
+            zone.clear();
+        }
+
+        for id in state.battlefield().search(|obj| obj.face_down) {
+            let obj = state.battlefield_mut().get_mut(id).unwrap();
+            if obj.controller != player {
+                obj.definition = Rc::new(CardDefinition::default());
+            }
+        }
+        for lki in state.last_known_information.values_mut() {
+            if lki.object.face_down && lki.object.controller != player {
+                lki.object.definition = Rc::new(CardDefinition::default());
+            }
+        }
+
+        MtgView {
+            viewer: player,
+            state,
+            hidden_zone_sizes,
+        }
+    }
+
+    /// The given action as the given player is allowed to see it
+    ///
+    /// Anything that would tell the viewer which object was involved in something that happened
+    /// out of their sight is replaced by a `HiddenAction`, eg a specific card being moved from an
+    /// opponent's library to their hand. Actions the viewer could see happening are unchanged.
+    pub fn redact_action(&self, action: &Action<Mtg>, viewer: PlayerId) -> Action<Mtg> {
+        let payload = match &action.payload {
+            ActionPayload::DomainAction(da) => {
+                ActionPayload::DomainAction(self.redact_domain_action(&**da, viewer))
+            }
+            ActionPayload::Composite { tag, actions } => ActionPayload::Composite {
+                tag,
+                actions: actions
+                    .iter()
+                    .map(|a| self.redact_action(a, viewer))
+                    .collect(),
+            },
+            ActionPayload::EngineAction(_) => action.payload.clone(),
+        };
+
+        Action {
+            payload,
+            original: action
+                .original
+                .as_ref()
+                .map(|original| Rc::new(self.redact_action(original, viewer))),
+            ..action.clone()
+        }
+    }
+
+    fn redact_domain_action(&self, action: &dyn MtgAction, viewer: PlayerId) -> Box<dyn MtgAction> {
+        if let Some(composite) = action.as_t::<CompositeAction>() {
+            return Box::new(CompositeAction {
+                tag: composite.tag,
+                components: composite
+                    .components
+                    .iter()
+                    .map(|c| self.redact_domain_action(&**c, viewer))
+                    .collect(),
+            });
+        }
+
+        let mut zones = Vec::new();
+        self.hidden_zones_involved(action, viewer, &mut zones);
+        if zones.is_empty() {
+            action.clone_box()
+        } else {
+            Box::new(HiddenAction { zones })
+        }
+    }
+
+    /// Collects the zones involved in the given action, or any of its components, if it refers to
+    /// an object that the viewer can't see in any of them
+    fn hidden_zones_involved(
+        &self,
+        action: &dyn MtgAction,
+        viewer: PlayerId,
+        zones: &mut Vec<ZoneId>,
+    ) {
+        let involved = if let Some(a) = action.as_t::<ChangeObjectZone>() {
+            match a.obj_ref {
+                ObjectReference::Concrete(obj) => vec![obj.zone, a.new_loc.zone],
+                ObjectReference::Abstract(_) => Vec::new(),
+            }
+        } else if let Some(a) = action.as_t::<SearchLibrary>() {
+            // 701.19b The searching player looks at every card in their library
+            match a.found {
+                Some(_) if a.player != viewer => {
+                    vec![self.player(a.player).library, a.new_loc.zone]
+                }
+                _ => Vec::new(),
+            }
+        } else if let Some(a) = action.as_t::<CeaseToExist>() {
+            vec![a.object.zone]
+        } else if let Some(a) = action.as_t::<AddCounter>() {
+            vec![a.object.zone]
+        } else if let Some(a) = action.as_t::<RemoveCounter>() {
+            vec![a.object.zone]
+        } else {
+            Vec::new()
+        };
+
+        let is_hidden =
+            !involved.is_empty() && !involved.iter().any(|&zone| self.can_see_zone(viewer, zone));
+        if is_hidden {
+            for zone in involved {
+                if !zones.contains(&zone) {
+                    zones.push(zone);
+                }
+            }
+        }
+
+        for component in action.components() {
+            self.hidden_zones_involved(&**component, viewer, zones);
+        }
+    }
+}
+
+/// Stands in for an action whose details the viewer isn't allowed to see
+///
+/// Only says which zones were involved, eg that some card moved from a player's hand to their
+/// library. Does nothing if applied.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HiddenAction {
+    pub zones: Vec<ZoneId>,
+}
+
+impl BaseMtgAction for HiddenAction {
+    fn apply(&self, _game_state: &mut Mtg) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        actions::{Action, ActionPayload, Controller},
+        game::Game,
+    };
+
+    use super::*;
+    use crate::{
+        card::make_card_universe,
+        game::MtgGameBuilder,
+        test_utils::{create_card, create_object},
+        zone::{AbstractZoneLocation, ZoneLocation},
+        ConcreteObject, ObjectId,
+    };
+
+    fn action(game: &mut Game<Mtg>, action: Box<dyn MtgAction>) -> Action<Mtg> {
+        Action {
+            payload: ActionPayload::DomainAction(action),
+            source: game.self_id,
+            controller: Controller::Game,
+            id: game.action_id_gen.next_id(),
+            generated_at: game.game_timestamp,
+            original: None,
+        }
+    }
+
+    #[test]
+    fn test_hidden_zones() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let bobs_hand = game.game_state.player(bob).hand;
+        let alices_hand = game.game_state.player(alice).hand;
+        let alices_library = game.game_state.player(alice).library;
+        let battlefield = game.game_state.shared_zones.battlefield;
+
+        for _ in 0..3 {
+            create_object(&mut game, bob, bobs_hand, AbstractZoneLocation::Undefined);
+            create_object(&mut game, alice, alices_library, AbstractZoneLocation::Top);
+        }
+        let in_hand = create_object(
+            &mut game,
+            alice,
+            alices_hand,
+            AbstractZoneLocation::Undefined,
+        );
+        let bears = make_card_universe()
+            .find_by_name("Grizzly Bears")
+            .unwrap()
+            .clone();
+        let face_down = create_card(
+            &mut game,
+            bob,
+            battlefield,
+            AbstractZoneLocation::Undefined,
+            bears,
+        );
+        game.game_state
+            .battlefield_mut()
+            .get_mut(face_down)
+            .unwrap()
+            .face_down = true;
+
+        // Alice can see how many cards are in bob's hand, but not what they are
+        let view = game.game_state.visible_to(alice);
+        assert!(view.state.zones[&bobs_hand].is_empty());
+        assert_eq!(view.zone_size(bobs_hand), 3);
+
+        // She can see her own hand, but not even her own library
+        assert!(view.state.zones[&alices_hand].contains(in_hand));
+        assert_eq!(view.zone_size(alices_hand), 1);
+        assert!(view.state.zones[&alices_library].is_empty());
+        assert_eq!(view.zone_size(alices_library), 3);
+
+        // Only bob knows what his face-down creature really is
+        let hidden = view.state.battlefield().get(face_down).unwrap();
+        assert!(hidden.definition.name.is_empty());
+        assert_eq!(hidden.power(), Some(2));
+        let view = game.game_state.visible_to(bob);
+        let known = view.state.battlefield().get(face_down).unwrap();
+        assert_eq!(known.definition.name, "Grizzly Bears");
+        assert_eq!(view.state.zones[&bobs_hand].len(), 3);
+
+        // The same goes for its last known information once it leaves the battlefield
+        game.game_state.record_last_known_information(face_down);
+        let lki = |viewer| {
+            game.game_state
+                .visible_to(viewer)
+                .state
+                .last_known_information[&face_down]
+                .object
+                .definition
+                .name
+                .clone()
+        };
+        assert!(lki(alice).is_empty());
+        assert_eq!(lki(bob), "Grizzly Bears");
+    }
+
+    #[test]
+    fn test_redacted_draws() {
+        let mut game = MtgGameBuilder::new()
+            .with_player("alice")
+            .with_player("bob")
+            .build()
+            .unwrap();
+        let alice = game.game_state.find_player("alice").unwrap();
+        let bob = game.game_state.find_player("bob").unwrap();
+        let library = game.game_state.player(bob).library;
+        let hand = game.game_state.player(bob).hand;
+        let graveyard = game.game_state.player(bob).graveyard;
+        let cards = (0..3)
+            .map(|_| create_object(&mut game, bob, library, AbstractZoneLocation::Top))
+            .collect::<Vec<ObjectId>>();
+        let mentions_card = |action: &Action<Mtg>| {
+            let debug = format!("{:?}", action);
+            cards
+                .iter()
+                .any(|card| debug.contains(&format!("{:?}", card)))
+        };
+
+        // Putting a specific card from bob's library into his hand is only hidden from alice
+        let tutor = action(
+            &mut game,
+            Box::new(ChangeObjectZone {
+                obj_ref: ObjectReference::Concrete(ConcreteObject {
+                    zone: library,
+                    object: cards[1],
+                }),
+                new_loc: ZoneLocation {
+                    zone: hand,
+                    loc: AbstractZoneLocation::Undefined,
+                },
+            }),
+        );
+        let redacted = game.game_state.redact_action(&tutor, alice);
+        assert!(!mentions_card(&redacted));
+        match &redacted.payload {
+            ActionPayload::DomainAction(da) => assert_eq!(
+                da.as_t::<HiddenAction>(),
+                Some(&HiddenAction {
+                    zones: vec![library, hand]
+                })
+            ),
+            payload => panic!("Expected a domain action, got {:?}", payload),
+        }
+        assert!(mentions_card(&game.game_state.redact_action(&tutor, bob)));
+
+        // Anyone can see a card that ends up in a public zone
+        let mill = action(
+            &mut game,
+            Box::new(CompositeAction {
+                tag: "mill",
+                components: vec![Box::new(ChangeObjectZone {
+                    obj_ref: ObjectReference::Concrete(ConcreteObject {
+                        zone: library,
+                        object: cards[2],
+                    }),
+                    new_loc: ZoneLocation {
+                        zone: graveyard,
+                        loc: AbstractZoneLocation::Top,
+                    },
+                })],
+            }),
+        );
+        assert!(mentions_card(&game.game_state.redact_action(&mill, alice)));
+    }
+}
@@ -1,7 +1,10 @@
 use std::{cmp::min, collections::HashMap};
 
 use crate::Object;
-use core::ids::{ObjectId, PlayerId, ZoneId};
+use core::{
+    ids::{ObjectId, PlayerId, ZoneId},
+    rng::GameRng,
+};
 
 #[derive(Clone, Copy, Debug)]
 pub enum AbstractZoneLocation {
@@ -31,7 +34,7 @@ pub struct ZoneLocation {
     pub loc: AbstractZoneLocation,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Zone {
     /// Unique ID of this zone
     pub id: ZoneId,
@@ -58,6 +61,10 @@ impl Zone {
         self.storage.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.storage.is_empty()
+    }
+
     pub fn resolve_abstract_zone_location(&self, loc: AbstractZoneLocation) -> Option<ObjectId> {
         assert!(loc.implies_ordering());
         assert!(self.ordering.is_some());
@@ -67,7 +74,7 @@ impl Zone {
             AbstractZoneLocation::Top => ordering.last(),
             AbstractZoneLocation::Bottom => ordering.first(),
             AbstractZoneLocation::NthFromTop(n) => ordering.iter().rev().nth(n),
-            AbstractZoneLocation::NthFromBottom(n) => ordering.iter().nth(n),
+            AbstractZoneLocation::NthFromBottom(n) => ordering.get(n),
             AbstractZoneLocation::Undefined => unreachable!(),
         }
         .cloned()
@@ -91,6 +98,19 @@ impl Zone {
         self.storage.insert(object.id, object);
     }
 
+    pub fn get(&self, id: ObjectId) -> Option<&Object> {
+        self.storage.get(&id)
+    }
+
+    /// Mutable access to an object in this zone, leaving its position in the zone unchanged
+    pub fn get_mut(&mut self, id: ObjectId) -> Option<&mut Object> {
+        self.storage.get_mut(&id)
+    }
+
+    pub fn contains(&self, id: ObjectId) -> bool {
+        self.storage.contains_key(&id)
+    }
+
     pub fn remove(&mut self, id: ObjectId) -> Option<Object> {
         assert!(self.storage.contains_key(&id));
         let obj = self.storage.remove(&id)?;
@@ -106,19 +126,116 @@ impl Zone {
         Some(obj)
     }
 
-    pub fn top(&self) -> Option<&Object> {
-        if let Some(ordering) = &self.ordering {
-            ordering.last().map(|id| self.storage.get(id)).flatten()
-        } else {
-            None
+    /// Remove every object from this zone
+    ///
+    /// For ordered zones the objects are returned from bottom to top, for unordered zones they are
+    /// returned in ObjectId order.
+    pub fn drain(&mut self) -> Vec<Object> {
+        let ids = match &mut self.ordering {
+            Some(ordering) => std::mem::take(ordering),
+            None => {
+                let mut ids = self.storage.keys().cloned().collect::<Vec<_>>();
+                ids.sort_unstable();
+                ids
+            }
+        };
+
+        ids.iter()
+            .map(|id| {
+                self.storage
+                    .remove(id)
+                    .expect("Object in ordered zone is missing from storage")
+            })
+            .collect()
+    }
+
+    /// Remove every object from this zone, discarding them
+    pub fn clear(&mut self) {
+        self.storage.clear();
+        if let Some(ordering) = &mut self.ordering {
+            ordering.clear();
         }
     }
+
+    /// Every object in this zone, in ObjectId order
+    ///
+    /// Like `search`, the iteration order doesn't reveal anything about the ordering of the zone.
+    pub fn iter(&self) -> impl Iterator<Item = &Object> + '_ {
+        let mut ids = self.storage.keys().collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.into_iter().map(move |id| &self.storage[id])
+    }
+
+    /// Every object in this ordered zone, from bottom to top
+    ///
+    /// Panics if this zone is unordered.
+    pub fn iter_in_order(&self) -> impl Iterator<Item = &Object> + '_ {
+        let ordering = self
+            .ordering
+            .as_ref()
+            .expect("Can't iterate an unordered zone in order");
+        ordering.iter().map(move |id| {
+            self.storage
+                .get(id)
+                .expect("Object in ordered zone is missing from storage")
+        })
+    }
+
+    /// The object with the lowest ObjectId that matches the given predicate
+    pub fn find<F: Fn(&Object) -> bool>(&self, predicate: F) -> Option<&Object> {
+        self.iter().find(|obj| predicate(obj))
+    }
+
+    /// Find every object in this zone that matches the given predicate
+    ///
+    /// The results are sorted by ObjectId rather than by their position in the zone, so that
+    /// searching a hidden ordered zone (eg a library) doesn't reveal anything about its ordering.
+    pub fn search<F: Fn(&Object) -> bool>(&self, predicate: F) -> Vec<ObjectId> {
+        let mut found = self
+            .storage
+            .values()
+            .filter(|obj| predicate(obj))
+            .map(|obj| obj.id)
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        found
+    }
+
+    /// Randomize the order of this zone
+    ///
+    /// Does nothing if this zone is unordered.
+    pub fn shuffle(&mut self, rng: &mut GameRng) {
+        if let Some(ordering) = &mut self.ordering {
+            rng.shuffle(ordering);
+        }
+    }
+
+    pub fn top(&self) -> Option<&Object> {
+        self.nth_from_top(0)
+    }
+
+    /// The object n places below the top of this ordered zone, so that 0 is the top object
+    ///
+    /// Returns None if this zone is unordered or doesn't have that many objects.
+    pub fn nth_from_top(&self, n: usize) -> Option<&Object> {
+        let ordering = self.ordering.as_ref()?;
+        ordering
+            .iter()
+            .rev()
+            .nth(n)
+            .and_then(|id| self.storage.get(id))
+    }
+
+    pub fn is_ordered(&self) -> bool {
+        self.ordering.is_some()
+    }
 }
 
 pub enum NamedZone {
     Library(PlayerId),
     Hand(PlayerId),
     Graveyard(PlayerId),
+    PlanarDeck(PlayerId),
     Battlefield,
     Stack,
     Exile,
@@ -134,6 +251,7 @@ impl NamedZone {
             Library(p) => format!("{}'s library", p),
             Hand(p) => format!("{}'s hand", p),
             Graveyard(p) => format!("{}'s graveyard", p),
+            PlanarDeck(p) => format!("{}'s planar deck", p),
             Battlefield => String::from("battlefield"),
             Stack => String::from("stack"),
             Exile => String::from("exile"),
@@ -142,19 +260,16 @@ impl NamedZone {
         };
 
         let owner = match self {
-            Library(p) | Hand(p) | Graveyard(p) => Some(p),
+            Library(p) | Hand(p) | Graveyard(p) | PlanarDeck(p) => Some(p),
             _ => None,
         };
 
-        let public = match self {
-            Library(_) | Hand(_) => false,
-            _ => true,
-        };
+        let public = !matches!(self, Library(_) | Hand(_) | PlanarDeck(_));
 
         let storage = HashMap::new();
 
         let ordering = match self {
-            Library(_) | Graveyard(_) | Stack => Some(Vec::new()),
+            Library(_) | Graveyard(_) | PlanarDeck(_) | Stack => Some(Vec::new()),
             _ => None,
         };
 
@@ -168,3 +283,119 @@ impl NamedZone {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ids::IdGenerator;
+
+    #[test]
+    fn test_drain_ordered_zone() {
+        let owner = IdGenerator::<PlayerId>::new().next_id();
+        let mut object_ids = IdGenerator::<ObjectId>::new();
+        let mut zone = NamedZone::Graveyard(owner).build(IdGenerator::<ZoneId>::new().next_id());
+
+        let ids = (0..3).map(|_| object_ids.next_id()).collect::<Vec<_>>();
+        zone.insert(Object::new(ids[1], owner), AbstractZoneLocation::Top);
+        zone.insert(Object::new(ids[2], owner), AbstractZoneLocation::Top);
+        zone.insert(Object::new(ids[0], owner), AbstractZoneLocation::Bottom);
+
+        let drained = zone.drain().iter().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(drained, ids);
+        assert!(zone.is_empty());
+        assert!(zone.top().is_none());
+
+        zone.insert(
+            Object::new(object_ids.next_id(), owner),
+            AbstractZoneLocation::Top,
+        );
+        zone.clear();
+        assert!(zone.is_empty());
+        assert!(zone.top().is_none());
+    }
+
+    #[test]
+    fn test_iter_ordered_zone() {
+        let owner = IdGenerator::<PlayerId>::new().next_id();
+        let mut object_ids = IdGenerator::<ObjectId>::new();
+        let mut zone = NamedZone::Library(owner).build(IdGenerator::<ZoneId>::new().next_id());
+
+        let ids = (0..3).map(|_| object_ids.next_id()).collect::<Vec<_>>();
+        zone.insert(Object::new(ids[2], owner), AbstractZoneLocation::Top);
+        zone.insert(Object::new(ids[0], owner), AbstractZoneLocation::Top);
+        zone.insert(Object::new(ids[1], owner), AbstractZoneLocation::Bottom);
+
+        let in_order = zone.iter_in_order().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(in_order, vec![ids[1], ids[2], ids[0]]);
+
+        let by_id = zone.iter().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(by_id, ids);
+
+        assert_eq!(zone.find(|o| o.id != ids[0]).map(|o| o.id), Some(ids[1]));
+        assert!(zone.find(|o| o.tapped).is_none());
+    }
+
+    #[test]
+    fn test_iter_unordered_zone() {
+        let mut player_ids = IdGenerator::<PlayerId>::new();
+        let alice = player_ids.next_id();
+        let bob = player_ids.next_id();
+        let mut object_ids = IdGenerator::<ObjectId>::new();
+        let mut zone = NamedZone::Battlefield.build(IdGenerator::<ZoneId>::new().next_id());
+
+        let ids = (0..4).map(|_| object_ids.next_id()).collect::<Vec<_>>();
+        for (i, &id) in ids.iter().enumerate().rev() {
+            let owner = if i % 2 == 0 { alice } else { bob };
+            zone.insert(Object::new(id, owner), AbstractZoneLocation::Undefined);
+        }
+
+        let by_id = zone.iter().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(by_id, ids);
+
+        let bobs = zone.find(|o| o.controller == bob).map(|o| o.id);
+        assert_eq!(bobs, Some(ids[1]));
+    }
+
+    #[test]
+    fn test_get_mut_preserves_ordering() {
+        let owner = IdGenerator::<PlayerId>::new().next_id();
+        let mut object_ids = IdGenerator::<ObjectId>::new();
+        let mut zone = NamedZone::Library(owner).build(IdGenerator::<ZoneId>::new().next_id());
+
+        let ids = (0..3).map(|_| object_ids.next_id()).collect::<Vec<_>>();
+        for &id in &ids {
+            zone.insert(Object::new(id, owner), AbstractZoneLocation::Top);
+        }
+
+        zone.get_mut(ids[1]).unwrap().tapped = true;
+        assert!(zone.get(ids[1]).unwrap().tapped);
+        assert!(!zone.get(ids[0]).unwrap().tapped);
+
+        let in_order = zone.iter_in_order().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(in_order, ids);
+
+        let missing = object_ids.next_id();
+        assert!(zone.get(missing).is_none());
+        assert!(zone.get_mut(missing).is_none());
+    }
+
+    #[test]
+    fn test_nth_from_top() {
+        let owner = IdGenerator::<PlayerId>::new().next_id();
+        let mut object_ids = IdGenerator::<ObjectId>::new();
+        let mut zone = NamedZone::Graveyard(owner).build(IdGenerator::<ZoneId>::new().next_id());
+
+        let ids = (0..3).map(|_| object_ids.next_id()).collect::<Vec<_>>();
+        for &id in &ids {
+            zone.insert(Object::new(id, owner), AbstractZoneLocation::Top);
+        }
+
+        assert_eq!(zone.top().map(|o| o.id), Some(ids[2]));
+        assert_eq!(zone.nth_from_top(0).map(|o| o.id), Some(ids[2]));
+        assert_eq!(zone.nth_from_top(2).map(|o| o.id), Some(ids[0]));
+        assert!(zone.nth_from_top(3).is_none());
+
+        let hand = NamedZone::Hand(owner).build(IdGenerator::<ZoneId>::new().next_id());
+        assert!(hand.nth_from_top(0).is_none());
+    }
+}